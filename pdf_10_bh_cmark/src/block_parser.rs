@@ -0,0 +1,1146 @@
+/* 17/10/2026 */
+
+//! Block-level Markdown constructs which `bh_cmark` does not produce: they
+//! would otherwise come out of its parser as plain paragraphs.
+//!
+//! The Markdown text is scanned line by line. Lines belonging to one of these
+//! constructs are collected into a [`RawBlock`], all other lines are gathered
+//! into [`RawBlock::Markdown`] runs, which are handed to `bh_cmark` unchanged.
+//! Inline text within the constructs, such as a list item, is also parsed by
+//! `bh_cmark`, so `**bold**` and `*italic*` work everywhere.
+//!
+//! Recognised constructs:
+//!
+//! * Lists — bullet items (`-`, `*`, `+`) and ordered items (`1.` or `1)`),
+//!   nested by indentation.
+//!
+//! * Fenced code blocks — between ```` ``` ```` or `~~~` fences. The text is
+//!   kept verbatim, it is not parsed by `bh_cmark`. A fence which is never
+//!   closed runs to the end of the document.
+//!
+//! * Blockquotes — lines starting with `>`. The text inside a quote is split
+//!   into blocks again, so quotes nest and can hold lists and code. A quote
+//!   becomes [`Block::QuoteStart`], its blocks, then [`Block::QuoteEnd`].
+//!
+//! * Tables — GFM-style: a header row, a delimiter row such as
+//!   `| :--- | ---: |` setting the column alignments, then the body rows.
+//!   Body rows with missing cells are padded, extra cells are dropped.
+//!
+//! * Table of contents marker — `[TOC]` on a line of its own, in any case.
+//!
+//! * Column markers — `[columns=N]` on a line of its own, in any case. The
+//!   blocks after it flow through `N` columns, see [`crate::column_layout`].
+//!
+//! * Figure rows — two or more lines each holding only an image,
+//!   `![caption](path)`, with no blank line between them. The images are
+//!   laid out side by side. A row holds up to four images: longer runs are
+//!   split into rows of four, and a single image left over stays a plain
+//!   image.
+//!
+//! * Image attributes — an image line may end in `{...}` attributes,
+//!   separated by spaces:
+//!
+//!   - `float=left` or `float=right` makes a floating image, such as
+//!     `![caption](path){float=left}`. The paragraphs after it flow around
+//!     it. A floating image is never part of a figure row.
+//!
+//!   - `#fig:name` labels the image for `@fig:name` references, see
+//!     [`crate::figure_numbering`].
+//!
+//!   - `span` makes the image span all the columns of a multi-column page
+//!     rather than fit inside one. A floating image always fits inside one.
+//!
+//!   A line with attributes which are not recognised is left as text.
+//!
+//! # Limitations
+//!
+//! 1. A list item holds a single paragraph: a continuation paragraph after
+//!    a blank line is joined to the item text.
+//!
+//! 2. A list item cannot contain other block constructs such as images or
+//!    fenced code: these end the list.
+//!
+//! 3. Indented code blocks are not recognised.
+//!
+//! 4. Within a blockquote, only paragraph text continues lazily onto lines
+//!    without a `>`.
+//!
+
+use bh_cmark::{
+	ast::{AstBlock, InlineContent}, parser::parser::Parser, scanner::Scanner
+};
+
+use crate::document::{
+    Block, 
+    CodeBlock, 
+    ColumnAlignment, 
+    ListBlock, 
+    ListItem, 
+    TableBlock,
+    Figure,
+    FigureRowBlock,
+    FloatImageBlock,
+    FloatSide,
+};
+use crate::figure_numbering::is_label;
+
+/// The most images in a figure row.
+const MAX_ROW_FIGURES: usize = 4;
+
+/// Tabs advance to the next multiple of this many columns.
+const TAB_STOP: usize = 4;
+
+/// A list item before its text has gone through `bh_cmark`.
+#[derive(Debug, PartialEq)]
+pub struct RawListItem {
+    /// Nesting level, `0` is the outermost list.
+    pub level: usize,
+    /// `true` for `1.` / `1)` items, `false` for bullet items.
+    pub ordered: bool,
+    /// The item number: only meaningful for ordered items.
+    pub number: u32,
+    /// The item text with its marker and indentation removed.
+    pub text: String,
+}
+
+/// Consecutive lines of the Markdown text, grouped by construct.
+#[derive(Debug, PartialEq)]
+pub enum RawBlock {
+    /// Lines `bh_cmark` handles itself.
+    Markdown(String),
+    List(Vec<RawListItem>),
+    /// The verbatim text of a fenced code block.
+    Code(String),
+    /// The text of a blockquote with its `>` markers removed.
+    Quote(String),
+    Table(RawTable),
+    /// The `[TOC]` marker.
+    Toc,
+    /// A `[columns=N]` marker: the number of columns.
+    Columns(usize),
+    /// The image lines of a figure row, each `![caption](path)` with 
+    /// optional attributes.
+    FigureRow(Vec<String>),
+    /// A single image line with attributes: the image without them, and 
+    /// the attributes.
+    Image(String, ImageAttributes),
+}
+
+/// The `{...}` attributes after an image, such as `{float=left #fig:map}`.
+#[derive(Debug, Default, PartialEq)]
+pub struct ImageAttributes {
+    pub float: Option<FloatSide>,
+    /// The `#fig:` label, without the `fig:`.
+    pub label: Option<String>,
+    pub span: bool,
+}
+
+/// A table before the text of its cells has gone through `bh_cmark`.
+#[derive(Debug, PartialEq)]
+pub struct RawTable {
+    pub alignments: Vec<ColumnAlignment>,
+    pub header: Vec<String>,
+    /// Each row has as many cells as the header.
+    pub rows: Vec<Vec<String>>,
+}
+
+/// A list item marker found at the start of a line.
+#[derive(Debug, Clone, Copy)]
+struct ListMarker {
+    /// Columns before the marker.
+    marker_indent: usize,
+    /// Columns before the item text.
+    content_indent: usize,
+    /// Byte offset of the item text within the line.
+    content_start: usize,
+    ordered: bool,
+    number: u32,
+}
+
+/// The opening fence of a fenced code block.
+#[derive(Debug, Clone, Copy)]
+struct CodeFence {
+    /// Columns before the fence: removed from each line of the code, too.
+    indent: usize,
+    /// ```` ` ```` or `~`.
+    fence_char: char,
+    /// Number of fence characters, at least 3.
+    fence_len: usize,
+}
+
+/// Count leading whitespace columns, returning the column count and the byte
+/// offset of the first non-whitespace character.
+fn leading_columns(line: &str) -> (usize, usize) {
+    let mut columns = 0;
+
+    for (index, c) in line.char_indices() {
+        match c {
+            ' ' => columns += 1,
+            '\t' => columns += TAB_STOP - (columns % TAB_STOP),
+            _ => return (columns, index),
+        }
+    }
+
+    (columns, line.len())
+}
+
+fn is_blank(line: &str) -> bool {
+    line.trim().is_empty()
+}
+
+/// `***`, `---`, `___`, optionally with spaces between the characters.
+fn is_thematic_break(line: &str) -> bool {
+    let chars: Vec<char> = line.chars().filter(|c| !c.is_whitespace()).collect();
+
+    chars.len() >= 3
+        && matches!(chars[0], '*' | '-' | '_')
+        && chars.iter().all(|c| *c == chars[0])
+}
+
+/// ATX headers: `#` to `######` followed by a space or the end of the line.
+fn is_header(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    let hashes = trimmed.chars().take_while(|c| *c == '#').count();
+
+    (1..=6).contains(&hashes)
+        && trimmed[hashes..].chars().next().is_none_or(|c| c == ' ' || c == '\t')
+}
+
+fn is_image(line: &str) -> bool {
+    line.trim_start().starts_with("![")
+}
+
+/// A line holding only an image, `![caption](path)`.
+fn is_image_line(line: &str) -> bool {
+    let trimmed = line.trim();
+    trimmed.starts_with("![") && trimmed.ends_with(')') && trimmed.contains("](")
+}
+
+/// An image line, optionally followed by `{...}` attributes: the image 
+/// without them, and the attributes. `None` when the line does not hold 
+/// only an image, or holds an attribute which is not recognised.
+fn image_attributes(line: &str) -> Option<(&str, ImageAttributes)> {
+    let trimmed = line.trim();
+    let mut attributes = ImageAttributes::default();
+
+    let Some(inner) = trimmed.strip_suffix('}') else {
+        return is_image_line(trimmed).then_some((trimmed, attributes));
+    };
+
+    let open = inner.rfind('{')?;
+    let image = inner[..open].trim_end();
+    let tokens: Vec<&str> = inner[open + 1..].split_whitespace().collect();
+
+    if tokens.is_empty() || !is_image_line(image) { return None; }
+
+    for token in tokens {
+        match token {
+            "float=left" => attributes.float = Some(FloatSide::Left),
+            "float=right" => attributes.float = Some(FloatSide::Right),
+            "span" => attributes.span = true,
+            _ => {
+                let label = token.strip_prefix("#fig:").filter(|label| is_label(label))?;
+                attributes.label = Some(label.to_string());
+            },
+        }
+    }
+
+    Some((image, attributes))
+}
+
+/// An image line which can be part of a figure row: one which does not 
+/// float.
+fn is_row_image(line: &str) -> bool {
+    image_attributes(line).is_some_and(|(_, attributes)| attributes.float.is_none())
+}
+
+/// `[TOC]` on a line of its own, in any case.
+fn is_toc_marker(line: &str) -> bool {
+    line.trim().eq_ignore_ascii_case("[toc]")
+}
+
+/// `[columns=N]` on a line of its own, in any case: the number of columns, 
+/// at least `1`.
+fn columns_marker(line: &str) -> Option<usize> {
+    let trimmed = line.trim();
+    let inner = trimmed.strip_prefix('[')?.strip_suffix(']')?;
+    let (key, count) = inner.split_once('=')?;
+
+    if !key.eq_ignore_ascii_case("columns") { return None; }
+    count.parse::<usize>().ok().filter(|count| *count > 0)
+}
+
+/// Whether `line` opens a fenced code block: at least 3 backticks or tildes,
+/// indented by no more than 3 spaces. A backtick fence's info string cannot
+/// contain backticks.
+fn code_fence(line: &str) -> Option<CodeFence> {
+    let (indent, fence_start) = leading_columns(line);
+    if indent > 3 { return None; }
+
+    let rest = &line[fence_start..];
+    let fence_char = rest.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let fence_len = rest.chars().take_while(|c| *c == fence_char).count();
+
+    if fence_len < 3 { return None; }
+    if fence_char == '`' && rest[fence_len..].contains('`') { return None; }
+
+    Some(CodeFence { indent, fence_char, fence_len })
+}
+
+/// Whether `line` closes the code block opened by `fence`: the same fence
+/// character, at least as many of them, and nothing else on the line.
+fn is_closing_fence(line: &str, fence: &CodeFence) -> bool {
+    let (indent, fence_start) = leading_columns(line);
+    if indent > 3 { return false; }
+
+    let rest = &line[fence_start..];
+    let fence_len = rest.chars().take_while(|c| *c == fence.fence_char).count();
+
+    fence_len >= fence.fence_len && rest[fence_len..].trim().is_empty()
+}
+
+/// Remove up to `columns` leading spaces from a line of code.
+fn strip_indent(line: &str, columns: usize) -> &str {
+    let spaces = line.chars().take(columns).take_while(|c| *c == ' ').count();
+    &line[spaces..]
+}
+
+/// Whether `line` is a blockquote line: `>` indented by no more than 3
+/// spaces. Returns the byte offset of the text after the `>` and one
+/// optional space.
+fn quote_marker(line: &str) -> Option<usize> {
+    let (indent, marker_start) = leading_columns(line);
+    if indent > 3 || !line[marker_start..].starts_with('>') { return None; }
+
+    let text_start = marker_start + 1;
+    Some(if line[text_start..].starts_with(' ') { text_start + 1 } else { text_start })
+}
+
+/// Split a table row into its cell texts. Leading and trailing pipes are
+/// optional, `\|` is a literal pipe within a cell.
+fn table_cells(line: &str) -> Vec<String> {
+    let line = line.trim();
+    let line = line.strip_prefix('|').unwrap_or(line);
+    let line = match line.strip_suffix('|') {
+        Some(stripped) if !stripped.ends_with('\\') => stripped,
+        _ => line,
+    };
+
+    let mut cells: Vec<String> = Vec::new();
+    let mut cell = String::new();
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&'|') => {
+                cell.push('|');
+                chars.next();
+            },
+            '|' => cells.push(std::mem::take(&mut cell).trim().to_string()),
+            _ => cell.push(c),
+        }
+    }
+    cells.push(cell.trim().to_string());
+
+    cells
+}
+
+/// Whether `line` is a table delimiter row, such as `| :--- | :---: |`. 
+/// Returns the column alignments.
+fn table_delimiter(line: &str) -> Option<Vec<ColumnAlignment>> {
+    if !line.contains('-') || !(line.contains('|') || line.contains(':')) { return None; }
+
+    table_cells(line).iter().map(|cell| {
+        let dashes = cell.trim_start_matches(':').trim_end_matches(':');
+        if dashes.is_empty() || !dashes.chars().all(|c| c == '-') { return None; }
+
+        Some(match (cell.starts_with(':'), cell.ends_with(':')) {
+            (true, true) => ColumnAlignment::Centre,
+            (false, true) => ColumnAlignment::Right,
+            _ => ColumnAlignment::Left,
+        })
+    }).collect()
+}
+
+/// Whether `lines[index]` starts a table: a row containing a pipe, followed
+/// by a delimiter row with the same number of cells. Returns the column 
+/// alignments.
+fn table_start(lines: &[&str], index: usize) -> Option<Vec<ColumnAlignment>> {
+    let header = lines[index];
+    if !header.contains('|') || leading_columns(header).0 > 3 { return None; }
+
+    let alignments = table_delimiter(lines.get(index + 1)?)?;
+
+    (alignments.len() == table_cells(header).len()).then_some(alignments)
+}
+
+/// Whether `line` starts a list item, and if so, where its text begins.
+fn list_marker(line: &str) -> Option<ListMarker> {
+    if is_thematic_break(line) { return None; }
+
+    let (marker_indent, marker_start) = leading_columns(line);
+    let rest = &line[marker_start..];
+
+    let (ordered, number, marker_len) = match rest.chars().next()? {
+        '-' | '*' | '+' => (false, 0, 1),
+        '0'..='9' => {
+            let digits = rest.chars().take_while(|c| c.is_ascii_digit()).count();
+            if digits > 9 { return None; }
+
+            match rest[digits..].chars().next() {
+                Some('.') | Some(')') => (true, rest[..digits].parse().ok()?, digits + 1),
+                _ => return None,
+            }
+        },
+        _ => return None,
+    };
+
+    let after_marker = &rest[marker_len..];
+
+    // The marker must be followed by whitespace, or end the line.
+    if !after_marker.is_empty() && !after_marker.starts_with([' ', '\t']) {
+        return None;
+    }
+
+    let (gap, text_offset) = leading_columns(after_marker);
+    // At least one space, and the text starts right after it when the gap is
+    // wide enough to be an indented code block.
+    let gap = if gap == 0 || gap > 4 { 1 } else { gap };
+
+    Some(ListMarker {
+        marker_indent,
+        content_indent: marker_indent + marker_len + gap,
+        content_start: marker_start + marker_len + text_offset,
+        ordered,
+        number,
+    })
+}
+
+/// A line which ends a list rather than continuing its last item.
+fn interrupts_list(line: &str) -> bool {
+    is_thematic_break(line) || is_header(line) || is_image(line)
+        || code_fence(line).is_some() || quote_marker(line).is_some()
+}
+
+/// A list item line may interrupt a paragraph: bullet items always, ordered
+/// items only when numbered `1`, so that a wrapped sentence beginning with a
+/// number is not taken as a list.
+fn can_interrupt_paragraph(marker: &ListMarker) -> bool {
+    !marker.ordered || marker.number == 1
+}
+
+fn append_text(text: &mut String, line: &str) {
+    let line = line.trim();
+    if line.is_empty() { return; }
+
+    if !text.is_empty() { text.push(' '); }
+    text.push_str(line);
+}
+
+/// Group the lines of a Markdown text into [`RawBlock`]s.
+pub fn split_raw_blocks(text: &str) -> Vec<RawBlock> {
+    let lines: Vec<&str> = text.lines().collect();
+
+    let mut raw_blocks: Vec<RawBlock> = Vec::new();
+    let mut markdown = String::new();
+    // Whether the previous line belongs to a paragraph in `markdown`.
+    let mut in_paragraph = false;
+
+    let mut index = 0;
+    while index < lines.len() {
+        let line = lines[index];
+
+        if let Some(fence) = code_fence(line) {
+            push_markdown(&mut raw_blocks, &mut markdown);
+
+            let (code, next_index) = collect_code(&lines, index, &fence);
+            raw_blocks.push(RawBlock::Code(code));
+
+            index = next_index;
+            in_paragraph = false;
+            continue;
+        }
+
+        if quote_marker(line).is_some() {
+            push_markdown(&mut raw_blocks, &mut markdown);
+
+            let (quote, next_index) = collect_quote(&lines, index);
+            raw_blocks.push(RawBlock::Quote(quote));
+
+            index = next_index;
+            in_paragraph = false;
+            continue;
+        }
+
+        if let Some(alignments) = table_start(&lines, index) {
+            push_markdown(&mut raw_blocks, &mut markdown);
+
+            let (table, next_index) = collect_table(&lines, index, alignments);
+            raw_blocks.push(RawBlock::Table(table));
+
+            index = next_index;
+            in_paragraph = false;
+            continue;
+        }
+
+        if is_toc_marker(line) {
+            push_markdown(&mut raw_blocks, &mut markdown);
+            raw_blocks.push(RawBlock::Toc);
+
+            index += 1;
+            in_paragraph = false;
+            continue;
+        }
+
+        if let Some(columns) = columns_marker(line) {
+            push_markdown(&mut raw_blocks, &mut markdown);
+            raw_blocks.push(RawBlock::Columns(columns));
+
+            index += 1;
+            in_paragraph = false;
+            continue;
+        }
+
+        if is_row_image(line) && lines.get(index + 1).is_some_and(|next| is_row_image(next)) {
+            push_markdown(&mut raw_blocks, &mut markdown);
+
+            let run = lines[index..].iter().take_while(|line| is_row_image(line)).count();
+            for row in lines[index..index + run].chunks(MAX_ROW_FIGURES) {
+                match row {
+                    [single] => push_image_line(&mut raw_blocks, &mut markdown, single),
+                    _ => raw_blocks.push(RawBlock::FigureRow(
+                        row.iter().map(|line| line.trim().to_string()).collect())),
+                }
+            }
+
+            index += run;
+            in_paragraph = false;
+            continue;
+        }
+
+        if image_attributes(line)
+            .is_some_and(|(_, attributes)| attributes != ImageAttributes::default()) {
+            push_image_line(&mut raw_blocks, &mut markdown, line);
+
+            index += 1;
+            in_paragraph = false;
+            continue;
+        }
+
+        if let Some(marker) = list_marker(line)
+            && (!in_paragraph || can_interrupt_paragraph(&marker)) {
+            push_markdown(&mut raw_blocks, &mut markdown);
+
+            let (items, next_index) = collect_list(&lines, index);
+            raw_blocks.push(RawBlock::List(items));
+
+            index = next_index;
+            in_paragraph = false;
+            continue;
+        }
+
+        in_paragraph = !is_blank(line) && !is_header(line)
+            && !is_thematic_break(line) && !is_image(line);
+        markdown.push_str(line);
+        markdown.push('\n');
+        index += 1;
+    }
+
+    push_markdown(&mut raw_blocks, &mut markdown);
+
+    raw_blocks
+}
+
+/// Add a single image line: a [`RawBlock::Image`] when it has attributes, 
+/// otherwise a Markdown line.
+fn push_image_line(raw_blocks: &mut Vec<RawBlock>, markdown: &mut String, line: &str) {
+    match image_attributes(line) {
+        Some((image, attributes)) if attributes != ImageAttributes::default() => {
+            push_markdown(raw_blocks, markdown);
+            raw_blocks.push(RawBlock::Image(image.to_string(), attributes));
+        },
+        _ => {
+            markdown.push_str(line);
+            markdown.push('\n');
+        },
+    }
+}
+
+/// Move the Markdown lines gathered so far into a [`RawBlock::Markdown`],
+/// unless they are all blank.
+fn push_markdown(raw_blocks: &mut Vec<RawBlock>, markdown: &mut String) {
+    if !markdown.trim().is_empty() {
+        raw_blocks.push(RawBlock::Markdown(std::mem::take(markdown)));
+    }
+    markdown.clear();
+}
+
+/// Collect the fenced code block whose opening fence is `lines[start]`.
+///
+/// # Returns
+///
+/// The code text, without the fences and without a trailing newline, and
+/// the index of the first line after the closing fence.
+fn collect_code(lines: &[&str], start: usize, fence: &CodeFence) -> (String, usize) {
+    let mut code_lines: Vec<&str> = Vec::new();
+
+    let mut index = start + 1;
+    while index < lines.len() {
+        let line = lines[index];
+        index += 1;
+
+        if is_closing_fence(line, fence) { break; }
+
+        code_lines.push(strip_indent(line, fence.indent));
+    }
+
+    (code_lines.join("\n"), index)
+}
+
+/// Collect the blockquote starting at `lines[start]`, which is known to be a
+/// quote line.
+///
+/// The quote ends at a blank line. A line without `>` continues the quote
+/// only if it continues a paragraph, so cannot start a block of its own.
+///
+/// # Returns
+///
+/// The quote text with one level of `>` markers removed, and the index of
+/// the first line after the quote.
+fn collect_quote(lines: &[&str], start: usize) -> (String, usize) {
+    let mut quote = String::new();
+    // Whether the previous line is paragraph text within the quote.
+    let mut in_paragraph = false;
+
+    let mut index = start;
+    while index < lines.len() {
+        let line = lines[index];
+
+        let text = match quote_marker(line) {
+            Some(text_start) => &line[text_start..],
+            None if in_paragraph && !is_blank(line) && !interrupts_list(line)
+                && list_marker(line).is_none() => line,
+            None => break,
+        };
+
+        in_paragraph = !is_blank(text) && !interrupts_list(text);
+        quote.push_str(text);
+        quote.push('\n');
+        index += 1;
+    }
+
+    (quote, index)
+}
+
+/// Collect the table whose header row is `lines[start]`, which is known to
+/// start a table. The table ends at a blank line, or a line starting another
+/// block construct.
+///
+/// # Returns
+///
+/// The table, and the index of the first line after it.
+fn collect_table(lines: &[&str], 
+    start: usize, 
+    alignments: Vec<ColumnAlignment>
+) -> (RawTable, usize) {
+    let columns = alignments.len();
+    let header = table_cells(lines[start]);
+    let mut rows: Vec<Vec<String>> = Vec::new();
+
+    // Skip the header and the delimiter rows.
+    let mut index = start + 2;
+    while index < lines.len() {
+        let line = lines[index];
+        if is_blank(line) || interrupts_list(line) { break; }
+
+        let mut cells = table_cells(line);
+        cells.resize(columns, String::new());
+        rows.push(cells);
+
+        index += 1;
+    }
+
+    (RawTable { alignments, header, rows }, index)
+}
+
+/// Collect the list starting at `lines[start]`, which is known to be a list
+/// item line.
+///
+/// # Returns
+///
+/// The list items, and the index of the first line after the list.
+fn collect_list(lines: &[&str], start: usize) -> (Vec<RawListItem>, usize) {
+    // Open nesting levels: the marker of the latest item at each level.
+    let mut stack: Vec<ListMarker> = Vec::new();
+    let mut items: Vec<RawListItem> = Vec::new();
+    let mut blank_before = false;
+
+    let mut index = start;
+    while index < lines.len() {
+        let line = lines[index];
+
+        if is_blank(line) {
+            blank_before = true;
+            index += 1;
+            continue;
+        }
+
+        if let Some(marker) = list_marker(line) {
+            let mut number = marker.number;
+
+            loop {
+                match stack.last_mut() {
+                    Some(top) if marker.marker_indent >= top.content_indent => {
+                        // A nested list.
+                        stack.push(marker);
+                        break;
+                    },
+                    Some(top) if marker.marker_indent >= top.marker_indent => {
+                        // A sibling item: ordered items number on from the
+                        // first item of the list.
+                        if marker.ordered && top.ordered { number = top.number + 1; }
+                        *top = ListMarker { number, ..marker };
+                        break;
+                    },
+                    Some(_) => { stack.pop(); },
+                    None => {
+                        stack.push(marker);
+                        break;
+                    },
+                }
+            }
+
+            items.push(RawListItem {
+                level: stack.len() - 1,
+                ordered: marker.ordered,
+                number,
+                text: line[marker.content_start..].trim().to_string(),
+            });
+
+            blank_before = false;
+            index += 1;
+            continue;
+        }
+
+        if interrupts_list(line) { break; }
+
+        // After a blank line, only text indented to the item content
+        // continues the item.
+        let (columns, _) = leading_columns(line);
+        let content_indent = stack.last().map_or(0, |top| top.content_indent);
+        if blank_before && columns < content_indent { break; }
+
+        if let Some(item) = items.last_mut() {
+            append_text(&mut item.text, line);
+        }
+
+        blank_before = false;
+        index += 1;
+    }
+
+    // Trailing blank lines are left for the Markdown run which follows.
+    while index > start && is_blank(lines[index - 1]) {
+        index -= 1;
+    }
+
+    (items, index)
+}
+
+/// Parse a Markdown text with `bh_cmark`.
+pub fn parse_markdown(text: &str) -> Result<Vec<AstBlock>, Box<dyn std::error::Error>> {
+	let mut scanner = Scanner::new(text);
+    let tokens = match scanner.scan_tokens() {
+		Ok(tokens) => tokens,
+		Err(err) => {
+			return Err(err.into());
+		}
+	};
+
+    let mut parser = Parser::new(&tokens);
+    let parse_output = parser.parse();
+
+	if parse_output.has_error() {
+		return Err(parse_output.errors().join("\n").into())
+	}
+
+    Ok(parse_output.into_blocks())
+}
+
+/// Parse a single line of inline Markdown: bold and italic spans.
+///
+/// # Returns
+///
+/// * [`Option<InlineContent>`] — `None` when the text is blank.
+///
+/// * [`std::error::Error`] — if `bh_cmark` fails to parse the text.
+///
+pub fn parse_inline(text: &str) -> Result<Option<InlineContent>, Box<dyn std::error::Error>> {
+    for block in parse_markdown(text)? {
+        match block {
+            AstBlock::Paragraph { content } |
+            AstBlock::Header { level: _, content } => return Ok(Some(content)),
+            _ => {}
+        }
+    }
+
+    Ok(None)
+}
+
+/// Parse the inline Markdown of each cell of a table row.
+fn parse_table_row(cells: &[String]) -> Result<Vec<Option<InlineContent>>, Box<dyn std::error::Error>> {
+    cells.iter().map(|cell| parse_inline(cell)).collect()
+}
+
+/// Parse a line holding only an image into a [`Figure`]: `None` when 
+/// `bh_cmark` does not take it as a single image.
+fn parse_figure(image: &str, 
+    label: Option<String>,
+    span: bool
+) -> Result<Option<Figure>, Box<dyn std::error::Error>> {
+    let mut images = parse_markdown(image)?;
+
+    Ok(match (images.len(), images.pop()) {
+        (1, Some(AstBlock::Image { path, alt })) => Some(Figure::new(path, alt, label, span)),
+        _ => None,
+    })
+}
+
+/// Turn the Markdown text into [`Block`]s: `bh_cmark` blocks, interleaved
+/// with the constructs recognised by this module.
+pub fn parse_blocks(text: &str) -> Result<Vec<Block>, Box<dyn std::error::Error>> {
+    let mut blocks: Vec<Block> = Vec::new();
+
+    for raw_block in split_raw_blocks(text) {
+        match raw_block {
+            RawBlock::Markdown(markdown) => {
+                blocks.extend(parse_markdown(&markdown)?.into_iter().map(Block::Ast));
+            },
+            RawBlock::List(raw_items) => {
+                let mut items: Vec<ListItem> = Vec::new();
+                for raw_item in raw_items {
+                    items.push(ListItem::new(raw_item.level, raw_item.ordered,
+                        raw_item.number, parse_inline(&raw_item.text)?));
+                }
+                blocks.push(Block::List(ListBlock::new(items)));
+            },
+            RawBlock::Code(code) => blocks.push(Block::Code(CodeBlock::new(code))),
+            RawBlock::Table(table) => {
+                let header = parse_table_row(&table.header)?;
+                let rows = table.rows.iter()
+                    .map(|row| parse_table_row(row))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                blocks.push(Block::Table(TableBlock::new(table.alignments, header, rows)));
+            },
+            RawBlock::Quote(quote) => {
+                blocks.push(Block::QuoteStart);
+                blocks.extend(parse_blocks(&quote)?);
+                blocks.push(Block::QuoteEnd);
+            },
+            RawBlock::Toc => blocks.push(Block::Toc),
+            RawBlock::Columns(columns) => blocks.push(Block::Columns(columns)),
+            RawBlock::FigureRow(lines) => {
+                let mut figures: Vec<Figure> = Vec::new();
+                for line in &lines {
+                    let Some((image, attributes)) = image_attributes(line) else { break; };
+                    let Some(figure) = parse_figure(image, attributes.label, attributes.span)? 
+                        else { break; };
+                    figures.push(figure);
+                }
+
+                if figures.len() == lines.len() {
+                    blocks.push(Block::FigureRow(FigureRowBlock::new(figures)));
+                } else {
+                    // Not images to `bh_cmark` after all: lay the lines out 
+                    // one after the other.
+                    blocks.extend(parse_markdown(&lines.join("\n\n"))?.into_iter().map(Block::Ast));
+                }
+            },
+            RawBlock::Image(image, attributes) => match parse_figure(&image, attributes.label, 
+                attributes.span)? {
+                Some(figure) => blocks.push(match attributes.float {
+                    Some(side) => Block::FloatImage(FloatImageBlock::new(side, figure)),
+                    None => Block::Image(figure),
+                }),
+                // Not an image to `bh_cmark` after all.
+                None => blocks.extend(parse_markdown(&image)?.into_iter().map(Block::Ast)),
+            },
+        }
+    }
+
+    Ok(blocks)
+}
+
+// To run test for this module only:
+//
+//     * cargo test block_parser::tests
+//
+//     * cargo test block_parser::tests::test_list_marker -- --exact [--nocapture]
+//     * cargo test block_parser::tests::test_split_raw_blocks_list -- --exact [--nocapture]
+//     * cargo test block_parser::tests::test_split_raw_blocks_nested_list -- --exact [--nocapture]
+//     * cargo test block_parser::tests::test_split_raw_blocks_not_list -- --exact [--nocapture]
+//     * cargo test block_parser::tests::test_split_raw_blocks_code -- --exact [--nocapture]
+//     * cargo test block_parser::tests::test_split_raw_blocks_quote -- --exact [--nocapture]
+//     * cargo test block_parser::tests::test_table_cells -- --exact [--nocapture]
+//     * cargo test block_parser::tests::test_split_raw_blocks_table -- --exact [--nocapture]
+//     * cargo test block_parser::tests::test_split_raw_blocks_toc -- --exact [--nocapture]
+//     * cargo test block_parser::tests::test_split_raw_blocks_columns -- --exact [--nocapture]
+//     * cargo test block_parser::tests::test_split_raw_blocks_figure_row -- --exact [--nocapture]
+//     * cargo test block_parser::tests::test_image_attributes -- --exact [--nocapture]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(level: usize, ordered: bool, number: u32, text: &str) -> RawListItem {
+        RawListItem { level, ordered, number, text: text.to_string() }
+    }
+
+    #[test]
+    fn test_list_marker() {
+        let marker = list_marker("- item").expect("bullet");
+        assert!(!marker.ordered, "bullet ordered");
+        assert_eq!(marker.content_indent, 2, "bullet content indent");
+
+        let marker = list_marker("  12) item").expect("ordered");
+        assert!(marker.ordered, "ordered ordered");
+        assert_eq!(marker.number, 12, "ordered number");
+        assert_eq!(marker.marker_indent, 2, "ordered marker indent");
+        assert_eq!(marker.content_indent, 6, "ordered content indent");
+
+        assert!(list_marker("* * *").is_none(), "thematic break");
+        assert!(list_marker("***1. Một là đủ sức***").is_none(), "bold italic");
+        assert!(list_marker("1945. Việt Nam").is_some(), "number at line start");
+        assert!(list_marker("1945 Việt Nam").is_none(), "no marker");
+        assert!(list_marker("-item").is_none(), "no space");
+    }
+
+    #[test]
+    fn test_split_raw_blocks_list() {
+        let text = "Chương trình nghị sự gồm 4 điểm:\n\n\
+            1. Thống nhứt Việt Nam.\n\
+            2. Vấn đề **quan thuế**.\n\
+            3. Ðại diện\n\
+            ngoại giao.\n\
+            7. Văn hóa.\n\n\
+            Ba vấn đề sau.\n";
+
+        let raw_blocks = split_raw_blocks(text);
+
+        assert_eq!(raw_blocks, vec![
+            RawBlock::Markdown("Chương trình nghị sự gồm 4 điểm:\n\n".to_string()),
+            RawBlock::List(vec![
+                item(0, true, 1, "Thống nhứt Việt Nam."),
+                item(0, true, 2, "Vấn đề **quan thuế**."),
+                item(0, true, 3, "Ðại diện ngoại giao."),
+                item(0, true, 4, "Văn hóa."),
+            ]),
+            RawBlock::Markdown("\nBa vấn đề sau.\n".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_split_raw_blocks_nested_list() {
+        let text = "- one\n  - one.one\n    1. one.one.one\n    2. one.one.two\n\
+            - two\n\n  two continued\n* three\n# Header\n";
+
+        let raw_blocks = split_raw_blocks(text);
+
+        assert_eq!(raw_blocks, vec![
+            RawBlock::List(vec![
+                item(0, false, 0, "one"),
+                item(1, false, 0, "one.one"),
+                item(2, true, 1, "one.one.one"),
+                item(2, true, 2, "one.one.two"),
+                item(0, false, 0, "two two continued"),
+                item(0, false, 0, "three"),
+            ]),
+            RawBlock::Markdown("# Header\n".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_split_raw_blocks_not_list() {
+        let test_data: Vec<&str> = vec![
+            "***1. Một là đủ sức cướp chính quyền.***\n",
+            "Sau ngày 02/09/1945, Việt Nam trở thành quốc gia\n1945. độc lập.\n",
+            "Above\n\n* * *\n\nBelow\n",
+        ];
+
+        for (index, text) in test_data.iter().enumerate() {
+            assert_eq!(split_raw_blocks(text), vec![RawBlock::Markdown(text.to_string())],
+                "Text {} is not a list", index);
+        }
+    }
+
+    #[test]
+    fn test_split_raw_blocks_code() {
+        let text = "Before\n```rust\nfn main() {\n\tlet x = 1;\n\n}\n```\nAfter\n\
+            - item\n  ~~~\n  indented\n    more\n  ~~~~\n````\nunclosed ```\n";
+
+        let raw_blocks = split_raw_blocks(text);
+
+        assert_eq!(raw_blocks, vec![
+            RawBlock::Markdown("Before\n".to_string()),
+            RawBlock::Code("fn main() {\n\tlet x = 1;\n\n}".to_string()),
+            RawBlock::Markdown("After\n".to_string()),
+            RawBlock::List(vec![item(0, false, 0, "item")]),
+            RawBlock::Code("indented\n  more".to_string()),
+            RawBlock::Code("unclosed ```".to_string()),
+        ]);
+
+        assert!(code_fence("``` a`b").is_none(), "backtick in info string");
+        assert!(code_fence("    ```").is_none(), "indented code");
+        assert!(code_fence("~~").is_none(), "short fence");
+    }
+
+    #[test]
+    fn test_split_raw_blocks_quote() {
+        let text = "Before\n> Quoted **text**\nlazy line\n>\n> > nested\n> - item\n\n\
+            >no space\n  > indented\n\nAfter\n";
+
+        let raw_blocks = split_raw_blocks(text);
+
+        assert_eq!(raw_blocks, vec![
+            RawBlock::Markdown("Before\n".to_string()),
+            RawBlock::Quote("Quoted **text**\nlazy line\n\n> nested\n- item\n".to_string()),
+            RawBlock::Quote("no space\nindented\n".to_string()),
+            RawBlock::Markdown("\nAfter\n".to_string()),
+        ]);
+
+        // One level of markers is removed at a time.
+        assert_eq!(split_raw_blocks("> nested\n- item\n"), vec![
+            RawBlock::Quote("nested\n".to_string()),
+            RawBlock::List(vec![item(0, false, 0, "item")]),
+        ]);
+    }
+
+    #[test]
+    fn test_table_cells() {
+        let test_data: Vec<(&str, Vec<&str>)> = vec![
+            ("| a | b |", vec!["a", "b"]),
+            ("a | b", vec!["a", "b"]),
+            ("| a \\| b | |", vec!["a | b", ""]),
+            ("|a|", vec!["a"]),
+        ];
+
+        for (line, expected) in test_data {
+            assert_eq!(table_cells(line), expected, "{line}");
+        }
+
+        assert_eq!(table_delimiter("| :--- | :---: | ---: | --- |"), Some(vec![
+            ColumnAlignment::Left, ColumnAlignment::Centre, 
+            ColumnAlignment::Right, ColumnAlignment::Left,
+        ]));
+        assert!(table_delimiter("| --- | text |").is_none(), "not a delimiter");
+        assert!(table_delimiter("---").is_none(), "thematic break");
+    }
+
+    #[test]
+    fn test_split_raw_blocks_table() {
+        let text = "Before\n| Name | Year |\n|------|-----:|\n| Hà Nội | 1010 |\n\
+            | Huế |\n| Sài Gòn | 1698 | extra |\n\nAfter | not a table\n";
+
+        let raw_blocks = split_raw_blocks(text);
+
+        let cells = |row: &[&str]| -> Vec<String> { 
+            row.iter().map(|cell| cell.to_string()).collect() 
+        };
+
+        assert_eq!(raw_blocks, vec![
+            RawBlock::Markdown("Before\n".to_string()),
+            RawBlock::Table(RawTable {
+                alignments: vec![ColumnAlignment::Left, ColumnAlignment::Right],
+                header: cells(&["Name", "Year"]),
+                rows: vec![
+                    cells(&["Hà Nội", "1010"]),
+                    cells(&["Huế", ""]),
+                    cells(&["Sài Gòn", "1698"]),
+                ],
+            }),
+            RawBlock::Markdown("\nAfter | not a table\n".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_split_raw_blocks_toc() {
+        let text = "# Mở đầu\n\n[TOC]\n\nNội dung [toc] trong câu.\n  [toc]  \n";
+
+        let raw_blocks = split_raw_blocks(text);
+
+        assert_eq!(raw_blocks, vec![
+            RawBlock::Markdown("# Mở đầu\n\n".to_string()),
+            RawBlock::Toc,
+            RawBlock::Markdown("\nNội dung [toc] trong câu.\n".to_string()),
+            RawBlock::Toc,
+        ]);
+    }
+
+    #[test]
+    fn test_split_raw_blocks_columns() {
+        let text = "[columns=2]\nHai cột [columns=3] trong câu.\n\n  [COLUMNS=1]  \n\
+            [columns=0]\n[columns]\n";
+
+        let raw_blocks = split_raw_blocks(text);
+
+        assert_eq!(raw_blocks, vec![
+            RawBlock::Columns(2),
+            RawBlock::Markdown("Hai cột [columns=3] trong câu.\n\n".to_string()),
+            RawBlock::Columns(1),
+            RawBlock::Markdown("[columns=0]\n[columns]\n".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_split_raw_blocks_figure_row() {
+        let text = "![Một](a.png)\n  ![Hai](b.png)  \n\n![Ba](c.png)\n\n\
+            ![1](1.png)\n![2](2.png)\n![3](3.png)\n![4](4.png)\n![5](5.png)\n\
+            Chữ ![6](6.png)\n";
+
+        let raw_blocks = split_raw_blocks(text);
+
+        assert_eq!(raw_blocks, vec![
+            RawBlock::FigureRow(vec!["![Một](a.png)".to_string(), "![Hai](b.png)".to_string()]),
+            RawBlock::Markdown("\n![Ba](c.png)\n\n".to_string()),
+            RawBlock::FigureRow(vec!["![1](1.png)".to_string(), "![2](2.png)".to_string(),
+                "![3](3.png)".to_string(), "![4](4.png)".to_string()]),
+            RawBlock::Markdown("![5](5.png)\nChữ ![6](6.png)\n".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_image_attributes() {
+        let attributes = |float: Option<FloatSide>, label: Option<&str>| ImageAttributes {
+            float,
+            label: label.map(str::to_string),
+            ..ImageAttributes::default()
+        };
+
+        assert_eq!(image_attributes("![Hồ Chí Minh](a.png){float=left}"),
+            Some(("![Hồ Chí Minh](a.png)", attributes(Some(FloatSide::Left), None))));
+        assert_eq!(image_attributes("  ![](a.png) {float=right} "), 
+            Some(("![](a.png)", attributes(Some(FloatSide::Right), None))));
+        assert_eq!(image_attributes("![Bản đồ](a.png){#fig:ban-do float=right}"), 
+            Some(("![Bản đồ](a.png)", attributes(Some(FloatSide::Right), Some("ban-do")))));
+        assert_eq!(image_attributes("![](a.png)"), Some(("![](a.png)", attributes(None, None))));
+        assert_eq!(image_attributes("![](a.png){span #fig:a}"), Some(("![](a.png)", 
+            ImageAttributes { float: None, label: Some("a".to_string()), span: true })));
+
+        assert_eq!(image_attributes("![](a.png){float=centre}"), None);
+        assert_eq!(image_attributes("![](a.png){#fig:}"), None);
+        assert_eq!(image_attributes("![](a.png){#tab:a}"), None);
+        assert_eq!(image_attributes("![](a.png){}"), None);
+        assert_eq!(image_attributes("Chữ {float=left}"), None);
+
+        let raw_blocks = split_raw_blocks("Một\n![](a.png){float=right}\nHai\n\n\
+            ![A](a.png){#fig:a}\n![B](b.png)\n![C](c.png){float=left}\n![D](d.png){#fig:d}\n");
+        assert_eq!(raw_blocks, vec![
+            RawBlock::Markdown("Một\n".to_string()),
+            RawBlock::Image("![](a.png)".to_string(), attributes(Some(FloatSide::Right), None)),
+            RawBlock::Markdown("Hai\n\n".to_string()),
+            RawBlock::FigureRow(vec!["![A](a.png){#fig:a}".to_string(), 
+                "![B](b.png)".to_string()]),
+            RawBlock::Image("![C](c.png)".to_string(), attributes(Some(FloatSide::Left), None)),
+            RawBlock::Image("![D](d.png)".to_string(), attributes(None, Some("d"))),
+        ]);
+    }
+}
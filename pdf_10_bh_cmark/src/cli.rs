@@ -0,0 +1,280 @@
+/* 17/10/2026 */
+
+//! Command-line front end.
+//!
+//! ```text
+//! pdf_10_bh_cmark [OPTIONS] <INPUT>...
+//! ```
+//!
+//! Each input Markdown file is rendered to its own PDF. The arguments are
+//! parsed by hand rather than with a crate such as `clap`, to keep the
+//! dependency list short.
+//!
+//! Exit status:
+//!
+//! * [`EXIT_SUCCESS`] — all documents were written, possibly with warnings.
+//! * [`EXIT_DOCUMENT_ERROR`] — one or more documents failed to parse or
+//!   render. With `--strict`, warnings count as failures.
+//! * [`EXIT_USAGE_ERROR`] — invalid command-line arguments.
+//! * [`EXIT_CONFIG_ERROR`] — the configuration file could not be loaded.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::page_geometry::named_page_size;
+
+pub const EXIT_SUCCESS: i32 = 0;
+pub const EXIT_DOCUMENT_ERROR: i32 = 1;
+pub const EXIT_USAGE_ERROR: i32 = 2;
+pub const EXIT_CONFIG_ERROR: i32 = 3;
+
+pub const DEFAULT_CONFIG_FILE: &str = "./config/config.toml";
+
+pub const USAGE: &str = "\
+Usage: pdf_10_bh_cmark [OPTIONS] <INPUT>...
+
+Arguments:
+  <INPUT>...                Markdown file(s) to render.
+
+Options:
+  -c, --config <FILE>       Configuration file [default: ./config/config.toml].
+  -o, --output <PATH>       Output PDF file. With more than one input, a directory
+                            in which each PDF is named after its input file.
+                            [default: the input path with a .pdf extension]
+  -p, --page-size <NAME>    Page size override: A3, A4, A5, Letter or Legal.
+                            Replaces the size set in the [page] configuration.
+      --strict              Treat warnings, such as an image which cannot be
+                            loaded, as errors.
+  -h, --help                Print this help and exit.";
+
+/// The parsed command-line options.
+#[derive(Debug)]
+pub struct CliOptions {
+    inputs: Vec<PathBuf>,
+    config: PathBuf,
+    output: Option<PathBuf>,
+    page_size: Option<String>,
+    strict: bool,
+}
+
+/// What the program has been asked to do.
+#[derive(Debug)]
+pub enum Command {
+    Help,
+    Render(CliOptions),
+}
+
+impl CliOptions {
+    pub fn inputs(&self) -> &[PathBuf] {
+        &self.inputs
+    }
+
+    pub fn config(&self) -> &Path {
+        &self.config
+    }
+
+    pub fn output(&self) -> Option<&Path> {
+        self.output.as_deref()
+    }
+
+    pub fn page_size(&self) -> Option<&str> {
+        self.page_size.as_deref()
+    }
+
+    pub fn strict(&self) -> bool {
+        self.strict
+    }
+
+    /// The PDF file name for `input`.
+    ///
+    /// * No `--output` — `input` with its extension replaced by `.pdf`.
+    /// * A single input — `--output` as given.
+    /// * Multiple inputs — `--output` is a directory, the PDF is named after
+    ///   the input file.
+    pub fn output_for(&self, input: &Path) -> PathBuf {
+        match &self.output {
+            None => input.with_extension("pdf"),
+            Some(output) if self.inputs.len() == 1 => output.clone(),
+            Some(output) => {
+                let mut file_name = input.file_stem()
+                    .unwrap_or(input.as_os_str()).to_os_string();
+                file_name.push(".pdf");
+                output.join(file_name)
+            }
+        }
+    }
+}
+
+/// Split `--name=value` into `("--name", Some("value"))`. Short options and
+/// long options without `=` are returned unchanged.
+fn split_option(arg: &str) -> (&str, Option<&str>) {
+    match arg.split_once('=') {
+        Some((name, value)) if arg.starts_with("--") => (name, Some(value)),
+        _ => (arg, None),
+    }
+}
+
+/// Parse the command-line arguments, excluding the program name.
+///
+/// # Returns
+///
+/// * [`Command`] — either a request for help, or the options for rendering.
+///
+/// * [`std::error::Error`] — on an unknown option, a missing option value,
+///   an unknown page size, when no input file is given, or when two inputs 
+///   would be written to the same PDF file, see [`CliOptions::output_for()`].
+///
+pub fn parse_args<I>(args: I) -> Result<Command, Box<dyn std::error::Error>>
+where
+    I: IntoIterator<Item = String>
+{
+    let mut inputs: Vec<PathBuf> = Vec::new();
+    let mut config: Option<PathBuf> = None;
+    let mut output: Option<PathBuf> = None;
+    let mut page_size: Option<String> = None;
+    let mut strict = false;
+    let mut options_ended = false;
+
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        if options_ended || !arg.starts_with('-') {
+            inputs.push(PathBuf::from(arg));
+            continue;
+        }
+
+        let (name, inline_value) = split_option(&arg);
+
+        let mut value = || -> Result<String, Box<dyn std::error::Error>> {
+            match inline_value {
+                Some(value) => Ok(value.to_string()),
+                None => args.next()
+                    .ok_or_else(|| format!("Option {name} requires a value").into()),
+            }
+        };
+
+        match name {
+            "-h" | "--help" => return Ok(Command::Help),
+            "-c" | "--config" => config = Some(PathBuf::from(value()?)),
+            "-o" | "--output" => output = Some(PathBuf::from(value()?)),
+            "-p" | "--page-size" => {
+                let name = value()?;
+                if named_page_size(&name).is_none() {
+                    return Err(format!("Unknown page size: {name}").into());
+                }
+                page_size = Some(name);
+            },
+            "--strict" => strict = true,
+            "--" => options_ended = true,
+            _ => return Err(format!("Unknown option: {arg}").into()),
+        }
+    }
+
+    if inputs.is_empty() {
+        return Err("No input Markdown file given".into());
+    }
+
+    let options = CliOptions {
+        inputs,
+        config: config.unwrap_or_else(|| PathBuf::from(DEFAULT_CONFIG_FILE)),
+        output,
+        page_size,
+        strict,
+    };
+
+    // Such as `a/notes.md b/notes.md -o out`: the second PDF would overwrite 
+    // the first.
+    let mut outputs: HashMap<PathBuf, &Path> = HashMap::new();
+    for input in options.inputs() {
+        let output = options.output_for(input);
+        if let Some(other) = outputs.insert(output.clone(), input) {
+            return Err(format!("{} and {} would both be written to {}", 
+                other.display(), input.display(), output.display()).into());
+        }
+    }
+
+    Ok(Command::Render(options))
+}
+
+// To run test for this module only:
+//
+//     * cargo test cli::tests
+//
+//     * cargo test cli::tests::test_parse_args_defaults -- --exact [--nocapture]
+//     * cargo test cli::tests::test_parse_args_options -- --exact [--nocapture]
+//     * cargo test cli::tests::test_parse_args_invalid -- --exact [--nocapture]
+//     * cargo test cli::tests::test_output_for -- --exact [--nocapture]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    fn render_options(list: &[&str]) -> CliOptions {
+        match parse_args(args(list)).expect("Expected valid arguments") {
+            Command::Render(options) => options,
+            Command::Help => panic!("Expected Command::Render"),
+        }
+    }
+
+    #[test]
+    fn test_parse_args_defaults() {
+        let options = render_options(&["./text/essay.txt"]);
+
+        assert_eq!(options.inputs(), &[PathBuf::from("./text/essay.txt")]);
+        assert_eq!(options.config(), Path::new(DEFAULT_CONFIG_FILE));
+        assert!(options.output().is_none(), "no output");
+        assert!(options.page_size().is_none(), "no page size");
+        assert!(!options.strict(), "not strict");
+    }
+
+    #[test]
+    fn test_parse_args_options() {
+        let options = render_options(&["-c", "a.toml", "--output=out.pdf",
+            "--page-size", "letter", "--strict", "a.md"]);
+
+        assert_eq!(options.config(), Path::new("a.toml"));
+        assert_eq!(options.output(), Some(Path::new("out.pdf")));
+        assert_eq!(options.page_size(), Some("letter"));
+        assert!(options.strict(), "strict");
+        assert_eq!(options.inputs(), &[PathBuf::from("a.md")]);
+
+        let options = render_options(&["a.md", "--", "-b.md"]);
+        assert_eq!(options.inputs(), &[PathBuf::from("a.md"), PathBuf::from("-b.md")]);
+
+        assert!(matches!(parse_args(args(&["a.md", "--help"])), Ok(Command::Help)));
+    }
+
+    #[test]
+    fn test_parse_args_invalid() {
+        let test_data: Vec<(Vec<String>, &str)> = vec![
+            (args(&[]), "No input"),
+            (args(&["--config"]), "requires a value"),
+            (args(&["--unknown", "a.md"]), "Unknown option"),
+            (args(&["-p", "B5", "a.md"]), "Unknown page size"),
+            (args(&["a/notes.md", "b/notes.md", "-o", "out"]), "would both be written to"),
+            (args(&["a/notes.md", "a/notes.txt"]), "would both be written to"),
+        ];
+
+        for (index, (data, message)) in test_data.into_iter().enumerate() {
+            let err = parse_args(data)
+                .expect_err(&format!("Expected failure for arguments {}", index));
+            assert!(err.to_string().contains(message), "{}",
+                format!("Arguments {} error message", index));
+        }
+    }
+
+    #[test]
+    fn test_output_for() {
+        let options = render_options(&["text/a.md"]);
+        assert_eq!(options.output_for(Path::new("text/a.md")), PathBuf::from("text/a.pdf"));
+
+        let options = render_options(&["-o", "out.pdf", "text/a.md"]);
+        assert_eq!(options.output_for(Path::new("text/a.md")), PathBuf::from("out.pdf"));
+
+        let options = render_options(&["-o", "pdf", "text/a.md", "text/b.md"]);
+        assert_eq!(options.output_for(Path::new("text/b.md")), PathBuf::from("pdf/b.pdf"));
+    }
+}
@@ -0,0 +1,240 @@
+/* 17/10/2026 */
+
+//!
+//! Layout fenced code blocks onto a PDF. Code is set in the monospace
+//! `[fonts] code` font, on a shaded background with padding on all sides.
+//! Unlike paragraphs, code is not justified, and its whitespace is kept as
+//! written: tabs are expanded to spaces so columns line up.
+//!
+//! A code block breaks across pages between lines. Each page fragment is
+//! shaded separately, with its own padding above and below.
+//!
+
+use std::ops::Range;
+
+use cairo::Context;
+use pango::{Layout, WrapMode};
+use pangocairo::functions::create_layout;
+
+use crate::column_layout::FrameCursor;
+use crate::config::Config;
+use crate::document::{CodeBlock, PositionedBlock};
+use crate::page_geometry::PageGeometry;
+use crate::text_layout::{layout_width, show_lines};
+
+/// Replace tabs with spaces, up to the next multiple of `tab_width`
+/// characters on the line.
+pub fn expand_tabs(text: &str, tab_width: usize) -> String {
+    let tab_width = tab_width.max(1);
+    let mut expanded = String::with_capacity(text.len());
+    let mut column = 0;
+
+    for c in text.chars() {
+        match c {
+            '\t' => {
+                let spaces = tab_width - (column % tab_width);
+                expanded.extend(std::iter::repeat_n(' ', spaces));
+                column += spaces;
+            },
+            '\n' => {
+                expanded.push(c);
+                column = 0;
+            },
+            _ => {
+                expanded.push(c);
+                column += 1;
+            },
+        }
+    }
+
+    expanded
+}
+
+/// Create the [`pango::Layout`] for a fenced code block. The layout is
+/// narrower than the content width by the padding on either side, lines
+/// longer than that wrap at any character.
+pub fn create_code_layout(code: &CodeBlock,
+    config: &Config,
+    page_geometry: &PageGeometry,
+    context: &Context
+) -> Layout {
+    let code_config = config.code_block();
+    let width = (page_geometry.content_width() - 2.0 * code_config.padding()).max(1.0);
+
+    let layout: Layout = create_layout(context);
+    layout.set_width(layout_width(width));
+    layout.set_wrap(WrapMode::Char);
+    layout.set_font_description(Some(&config.fonts().code().to_pango_description()));
+    layout.set_text(&expand_tabs(code.text(), code_config.tab_width()));
+
+    layout
+}
+
+/// Paginate a fenced code block into [`PositionedBlock::Code`] fragments.
+///
+/// A line goes into the current frame only if the padding below it also
+/// fits, since it might be the last line of the fragment. Every fragment
+/// starts below a padding. A fragment at the top of a frame takes at least
+/// one line, so a line taller than the frame does not leave it empty.
+///
+/// # Arguments
+///
+/// * `line_heights` — the line heights of the code layout.
+///
+/// * `block_index` — index to the semantic code block.
+///
+/// * `pos_blocks` — the fragments are appended to this vector.
+///
+/// * `cursor` — the pagination state, updated to just after the code block,
+///   including its spacing after.
+///
+/// * `config` — code block padding and spacing.
+///
+/// * `page_geometry` — the page size and margins in effect.
+///
+pub fn measure_code_block(line_heights: &[f64],
+    block_index: usize,
+    pos_blocks: &mut Vec<PositionedBlock>,
+    cursor: &mut FrameCursor,
+    config: &Config,
+    page_geometry: &PageGeometry,
+) {
+    let padding = config.code_block().padding();
+
+    cursor.y += cursor.collapse_spacing(config.block_spacing().code().before(), cursor.y)
+        + padding;
+    cursor.y_offset = cursor.y;
+
+    let mut start_line: usize = 0;
+    for (line_index, line_height) in line_heights.iter().enumerate() {
+        let may_break = line_index > start_line 
+            || cursor.y_offset - padding > cursor.frame().top();
+
+        if may_break && cursor.y + line_height + padding > page_geometry.content_bottom() {
+            if line_index > start_line {
+                pos_blocks.push(PositionedBlock::code(block_index, cursor.frame(),
+                    cursor.y_offset, start_line, line_index));
+            }
+
+            start_line = line_index;
+            cursor.next_frame();
+            cursor.y += padding;
+            cursor.y_offset = cursor.y;
+        }
+
+        cursor.y += line_height;
+    }
+
+    if line_heights.len() > start_line {
+        pos_blocks.push(PositionedBlock::code(block_index, cursor.frame(),
+            cursor.y_offset, start_line, line_heights.len()));
+    }
+
+    cursor.y += padding + config.block_spacing().code().after();
+    cursor.y_offset = cursor.y;
+}
+
+/// Render a [`PositionedBlock::Code`] fragment: its shading, then its lines.
+///
+/// Lines are drawn on their baselines, so the text is moved down by the
+/// ascent of the first line to sit inside the shading.
+///
+/// # Arguments
+///
+/// * `layout`, `line_heights` — the code layout and its line heights.
+///
+/// * `y_offset` — where the first line of the fragment starts.
+///
+/// * `lines` — the lines of the fragment.
+///
+/// * `config` — code block shading and padding.
+///
+/// * `context` — the Cairo PDF [`Context`].
+///
+/// * `page_geometry` — the page size and margins in effect.
+///
+pub fn render_code_block(layout: &Layout,
+    line_heights: &[f64],
+    y_offset: f64,
+    lines: Range<usize>,
+    config: &Config,
+    context: &Context,
+    page_geometry: &PageGeometry,
+) {
+    let code_config = config.code_block();
+    let padding = code_config.padding();
+    let text_height: f64 = line_heights[lines.clone()].iter().sum();
+
+    context.save().expect("Failed to save Cairo context");
+
+    context.rectangle(page_geometry.margins().left, y_offset - padding,
+        page_geometry.content_width(), text_height + 2.0 * padding);
+    context.set_source_rgb(code_config.background().r(),
+        code_config.background().g(),
+        code_config.background().b());
+    let _ = context.fill();
+
+    context.restore().expect("Failed to restore Cairo context");
+
+    let ascent = layout.line(lines.start as i32).map_or(0.0, |line| {
+        let (_ink, logical) = line.extents();
+        -logical.y() as f64 / pango::SCALE as f64
+    });
+
+    show_lines(context, layout, line_heights, page_geometry.margins().left + padding,
+        y_offset + ascent, lines.start, lines.end);
+}
+
+// To run test for this module only:
+//
+//     * cargo test code_layout::tests
+//
+//     * cargo test code_layout::tests::test_expand_tabs -- --exact [--nocapture]
+//     * cargo test code_layout::tests::test_measure_code_block_tall_line -- --exact [--nocapture]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use crate::config::load_config;
+
+    #[test]
+    fn test_expand_tabs() {
+        let test_data: Vec<(&str, usize, &str)> = vec![
+            ("\tx", 4, "    x"),
+            ("ab\tx", 4, "ab  x"),
+            ("abcd\tx", 4, "abcd    x"),
+            ("a\tb\n\tc", 2, "a b\n  c"),
+            ("no tabs", 4, "no tabs"),
+            ("\tx", 0, " x"),
+        ];
+
+        for (text, tab_width, expected) in test_data {
+            assert_eq!(expand_tabs(text, tab_width), expected, "{text:?} with {tab_width}");
+        }
+    }
+
+    #[test]
+    /// A line taller than the frame, at the top of a frame: it stays there 
+    /// rather than leaving the frame empty.
+    fn test_measure_code_block_tall_line() {
+        let config = load_config(Path::new("config/config.toml")).unwrap();
+        let page_geometry = PageGeometry::default();
+
+        let mut cursor = FrameCursor::new(1, &page_geometry);
+        cursor.next_frame();
+        let mut pos_blocks: Vec<PositionedBlock> = Vec::new();
+        measure_code_block(&[800.0], 0, &mut pos_blocks, &mut cursor, &config, &page_geometry);
+
+        assert_eq!(pos_blocks.len(), 1, "fragments");
+        assert_eq!(pos_blocks[0].page(), 2, "fragment page");
+
+        // Part-way down a page, the line still moves to the next one.
+        let mut cursor = FrameCursor::new(1, &page_geometry);
+        cursor.y = 400.0;
+        let mut pos_blocks: Vec<PositionedBlock> = Vec::new();
+        measure_code_block(&[800.0], 0, &mut pos_blocks, &mut cursor, &config, &page_geometry);
+
+        assert_eq!(pos_blocks.len(), 1, "moved fragments");
+        assert_eq!(pos_blocks[0].page(), 2, "moved fragment page");
+    }
+}
@@ -0,0 +1,406 @@
+/* 17/10/2026 */
+
+//!
+//! Multi-column pages. Text flows down a column, then into the next column,
+//! then onto the next page: each column of a page is a [`Frame`], and
+//! measuring keeps a [`FrameCursor`] rather than a page and a y-coordinate.
+//!
+//! `[columns] count` sets the number of columns, `gutter` the space between
+//! two of them. A `[columns=N]` marker changes the number of columns for the
+//! blocks after it, see [`crate::block_parser`]. All the columns of a page
+//! are as wide as each other, and the blocks are laid out to that width.
+//!
+//! An image or a figure row fits inside a column, unless it spans all of
+//! them: with `[columns] span_images`, or `{span}` after the image. A
+//! floating image always fits inside a column.
+//!
+//! When the number of columns changes part-way down a page, as it does
+//! around a spanning image, the new columns start below the lowest point
+//! reached by the columns above them. Columns are not balanced: the last
+//! column before such a change can be shorter than the others, or empty.
+//!
+//! With `[columns] rule`, a vertical rule is drawn in the middle of each
+//! gutter, alongside the columns on each page.
+//!
+
+use bh_cmark::ast::AstBlock;
+use cairo::Context;
+
+use crate::config::{ColumnConfig, Config};
+use crate::document::{Block, Figure, Frame, ImageBlockMeasuredInfo};
+use crate::page_geometry::PageGeometry;
+
+/// Columns side by side on part of a page, for drawing their rules.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ColumnSet {
+    page: usize,
+    columns: usize,
+    /// Where the columns start on the page.
+    top: f64,
+    /// The lowest point they reach, the bottom of the content area when
+    /// they carry on onto the next page.
+    bottom: f64,
+}
+
+/// Where measuring has got to: the frame being filled, and where the next
+/// block goes within it.
+#[derive(Debug)]
+pub struct FrameCursor {
+    page: usize,
+    column: usize,
+    /// The number of columns from `top` down.
+    columns: usize,
+    /// Where the columns start on the current page: the top margin, or
+    /// below the blocks above them when they start part-way down the page.
+    top: f64,
+    /// The top margin, where the columns of a new page start.
+    page_top: f64,
+    /// The limit blocks must fit above.
+    page_bottom: f64,
+    /// The lowest point reached by the columns left behind on the current
+    /// page.
+    lowest: f64,
+    /// The column sets of the pages left behind, for the column rules.
+    sets: Vec<ColumnSet>,
+    /// Where the next block goes.
+    pub y: f64,
+    /// Where the last fragment starts.
+    pub y_offset: f64,
+}
+
+impl FrameCursor {
+    /// The top of the first page, in the first of `columns` columns.
+    pub fn new(columns: usize, page_geometry: &PageGeometry) -> Self {
+        let top = page_geometry.margins().top;
+
+        FrameCursor {
+            page: 1,
+            column: 0,
+            columns: columns.max(1),
+            top,
+            page_top: top,
+            page_bottom: page_geometry.content_bottom(),
+            lowest: top,
+            sets: Vec::new(),
+            y: top,
+            y_offset: top,
+        }
+    }
+
+    pub fn frame(&self) -> Frame {
+        Frame::new(self.page, self.column, self.top)
+    }
+
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    /// The tops of the frames left on the current page after the current
+    /// one. The frames after them start at the top of a new page.
+    pub fn next_tops(&self) -> Vec<f64> {
+        vec![self.top; self.columns - self.column - 1]
+    }
+
+    /// Whether the next frame starts part-way down the current page, so
+    /// that a block which would go onto a new page should be tried in it.
+    pub fn next_frame_partial(&self) -> bool {
+        self.column + 1 < self.columns && self.top > self.page_top
+    }
+
+    /// Whether `y` is the top of a frame reached by a break, with nothing
+    /// placed in it yet. Neither the start of the first page, nor the start
+    /// of columns below other blocks, counts.
+    pub fn at_frame_start(&self, y: f64) -> bool {
+        y <= self.top && (self.column > 0 || (self.page > 1 && self.top <= self.page_top))
+    }
+
+    /// Vertical spacing above a block starting at `y`, collapsed to nothing
+    /// at the start of a frame. The first page keeps the spacing above its
+    /// first block.
+    pub fn collapse_spacing(&self, spacing: f64, y: f64) -> f64 {
+        if self.at_frame_start(y) { 0.0 } else { spacing }
+    }
+
+    /// Move to the top of the next frame: the next column, or the first
+    /// column of the next page.
+    pub fn next_frame(&mut self) {
+        if self.column + 1 < self.columns {
+            self.lowest = self.lowest.max(self.y);
+            self.column += 1;
+            self.y = self.top;
+            self.y_offset = self.y;
+        } else {
+            self.next_page();
+        }
+    }
+
+    /// Move to the top of the next frame with room for `height`. The
+    /// columns left on the current page all start as low as the next one:
+    /// when it is too short, so are they, and the block goes onto the next
+    /// page.
+    pub fn next_frame_for(&mut self, height: f64) {
+        self.next_frame();
+
+        if self.top > self.page_top && self.y + height > self.page_bottom {
+            self.next_page();
+        }
+    }
+
+    /// Move to the top of the first column of the next page.
+    fn next_page(&mut self) {
+        self.close_set(self.page_bottom);
+
+        self.page += 1;
+        self.column = 0;
+        self.top = self.page_top;
+        self.lowest = self.top;
+        self.y = self.top;
+        self.y_offset = self.y;
+    }
+
+    /// Start `columns` columns below everything placed on the current page.
+    pub fn set_columns(&mut self, columns: usize) {
+        let top = self.lowest.max(self.y);
+        self.close_set(top);
+
+        self.columns = columns.max(1);
+        self.column = 0;
+        self.top = top;
+        self.lowest = top;
+        self.y = top;
+        self.y_offset = top;
+    }
+
+    /// Record the columns of the current page, from their top down to
+    /// `bottom`.
+    fn close_set(&mut self, bottom: f64) {
+        if self.columns > 1 && bottom > self.top {
+            self.sets.push(ColumnSet {
+                page: self.page,
+                columns: self.columns,
+                top: self.top,
+                bottom,
+            });
+        }
+    }
+
+    /// The column sets of the document, once its last block has been placed.
+    pub fn finish(mut self) -> Vec<ColumnSet> {
+        let bottom = self.lowest.max(self.y);
+        self.close_set(bottom);
+        self.sets
+    }
+}
+
+/// How far `column` of `columns` is to the right of the first one.
+pub fn column_offset(column: usize,
+    columns: usize,
+    config: &ColumnConfig,
+    page_geometry: &PageGeometry
+) -> f64 {
+    column as f64 * (page_geometry.content_width() + config.gutter()) / columns.max(1) as f64
+}
+
+/// The number of columns each block flows through: `[columns] count`, as
+/// changed by the `[columns=N]` markers. A spanning image gets `1`: the
+/// columns stop above it and start again below it.
+pub fn block_columns(blocks: &[Block], config: &ColumnConfig) -> Vec<usize> {
+    let mut columns = config.count();
+
+    blocks.iter().map(|block| {
+        let span = match block {
+            Block::Ast(AstBlock::Image { .. }) => config.span_images(),
+            Block::Image(figure) => config.span_images() || figure.span(),
+            Block::FigureRow(row) => config.span_images() || row.figures().iter().any(Figure::span),
+            Block::Columns(count) => {
+                columns = *count;
+                false
+            },
+            _ => false,
+        };
+
+        if span { 1 } else { columns }
+    }).collect()
+}
+
+/// Place a block which is kept whole, such as an image block, in the frame
+/// where it fits. `measure` is called with where the block would start and
+/// the spacing above it, as for [`crate::image_layout::measure_image_block()`].
+///
+/// When the block does not fit, it goes onto a new page: the next frame.
+/// If that frame starts part-way down the current page, the block is
+/// measured again in it.
+///
+/// # Returns
+///
+/// The measured block, with the cursor moved to its frame, but not past it.
+///
+pub fn place_whole_block(cursor: &mut FrameCursor,
+    y: f64,
+    spacing_before: f64,
+    mut measure: impl FnMut(f64, f64) -> Result<ImageBlockMeasuredInfo, Box<dyn std::error::Error>>
+) -> Result<ImageBlockMeasuredInfo, Box<dyn std::error::Error>> {
+    let mut measured_info = measure(y, spacing_before)?;
+
+    while measured_info.new_page() && cursor.next_frame_partial() {
+        cursor.next_frame();
+        measured_info = measure(cursor.y, 0.0)?;
+    }
+
+    if measured_info.new_page() {
+        cursor.next_frame();
+    }
+
+    Ok(measured_info)
+}
+
+/// Draw the rules between the columns on a page.
+///
+/// # Arguments
+///
+/// * `sets` — the column sets of the document, see [`FrameCursor::finish()`].
+///
+/// * `page` — the page being drawn.
+///
+/// * `config` — whether to draw the rules, their width and colour, and the
+///   gutter.
+///
+/// * `context` — the Cairo PDF [`Context`].
+///
+/// * `page_geometry` — the page size and margins in effect.
+///
+pub fn render_column_rules(sets: &[ColumnSet],
+    page: usize,
+    config: &Config,
+    context: &Context,
+    page_geometry: &PageGeometry,
+) {
+    let column_config = config.columns();
+    if !column_config.rule() { return; }
+
+    context.save().expect("Failed to save Cairo context");
+
+    context.set_line_width(column_config.rule_width());
+    context.set_source_rgb(column_config.rule_colour().r(),
+        column_config.rule_colour().g(),
+        column_config.rule_colour().b());
+
+    for set in sets.iter().filter(|set| set.page == page) {
+        for column in 1..set.columns {
+            let x = page_geometry.margins().left
+                + column_offset(column, set.columns, column_config, page_geometry)
+                - column_config.gutter() / 2.0;
+
+            context.move_to(x, set.top);
+            context.line_to(x, set.bottom);
+        }
+    }
+    let _ = context.stroke();
+
+    // Restore the original context.
+    context.restore().expect("Failed to restore Cairo context");
+}
+
+// To run test for this module only:
+//
+//     * cargo test column_layout::tests
+//
+//     * cargo test column_layout::tests::test_collapse_spacing -- --exact [--nocapture]
+//     * cargo test column_layout::tests::test_frame_cursor -- --exact [--nocapture]
+//     * cargo test column_layout::tests::test_column_offset -- --exact [--nocapture]
+//     * cargo test column_layout::tests::test_block_columns -- --exact [--nocapture]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collapse_spacing() {
+        let page_geometry = PageGeometry::default();
+        let top = page_geometry.margins().top;
+
+        let mut cursor = FrameCursor::new(1, &page_geometry);
+        assert_eq!(cursor.collapse_spacing(15.0, top), 15.0, "top of the first page");
+
+        cursor.next_frame();
+        assert_eq!(cursor.collapse_spacing(15.0, top), 0.0, "top of a new page");
+        assert_eq!(cursor.collapse_spacing(15.0, top + 1.0), 15.0, "within a page");
+
+        let mut cursor = FrameCursor::new(2, &page_geometry);
+        cursor.next_frame();
+        assert_eq!(cursor.frame().page(), 1, "second column");
+        assert_eq!(cursor.collapse_spacing(15.0, top), 0.0, "top of the second column");
+
+        // Columns starting below other blocks keep the spacing between them.
+        cursor.y = 300.0;
+        cursor.set_columns(3);
+        assert_eq!(cursor.collapse_spacing(15.0, 300.0), 15.0, "columns below other blocks");
+    }
+
+    #[test]
+    fn test_frame_cursor() {
+        let page_geometry = PageGeometry::default();
+        let top = page_geometry.margins().top;
+        let bottom = page_geometry.content_bottom();
+
+        let mut cursor = FrameCursor::new(2, &page_geometry);
+        assert_eq!(cursor.next_tops(), vec![top], "second column left");
+
+        cursor.y = 500.0;
+        cursor.next_frame();
+        assert_eq!(cursor.frame(), Frame::new(1, 1, top), "second column");
+        assert_eq!(cursor.next_tops(), Vec::<f64>::new(), "last column");
+
+        cursor.y = 200.0;
+        cursor.next_frame();
+        assert_eq!(cursor.frame(), Frame::new(2, 0, top), "next page");
+        assert_eq!((cursor.y, cursor.y_offset), (top, top), "top of the next page");
+
+        // One column below a block, such as a spanning image: below the
+        // lowest column, then two columns below the image.
+        cursor.y = 400.0;
+        cursor.next_frame();
+        cursor.y = 300.0;
+        cursor.set_columns(1);
+        assert_eq!(cursor.frame(), Frame::new(2, 0, 400.0), "spanning block");
+
+        cursor.y = 600.0;
+        cursor.set_columns(2);
+        assert_eq!(cursor.frame(), Frame::new(2, 0, 600.0), "columns below the block");
+        assert!(cursor.next_frame_partial(), "second column part-way down");
+
+        // The second column is too short: onto the next page.
+        cursor.next_frame_for(bottom - 550.0);
+        assert_eq!(cursor.frame(), Frame::new(3, 0, top), "too short");
+
+        cursor.y = 100.0;
+        assert_eq!(cursor.finish(), vec![
+            ColumnSet { page: 1, columns: 2, top, bottom },
+            ColumnSet { page: 2, columns: 2, top, bottom: 400.0 },
+            ColumnSet { page: 2, columns: 2, top: 600.0, bottom },
+            ColumnSet { page: 3, columns: 2, top, bottom: 100.0 },
+        ]);
+    }
+
+    #[test]
+    fn test_column_offset() {
+        let config = ColumnConfig::default();
+        let page_geometry = PageGeometry::default();
+        let step = (page_geometry.content_width() + config.gutter()) / 3.0;
+
+        assert_eq!(column_offset(0, 3, &config, &page_geometry), 0.0, "first column");
+        assert_eq!(column_offset(2, 3, &config, &page_geometry), 2.0 * step, "third column");
+
+        let column = page_geometry.first_column(3, config.gutter());
+        let third = column.shifted(column_offset(2, 3, &config, &page_geometry));
+        assert!((third.margins().right - page_geometry.margins().right).abs() < 1e-9,
+            "third column right margin");
+    }
+
+    #[test]
+    fn test_block_columns() {
+        let blocks = vec![Block::Toc, Block::Columns(3), Block::QuoteStart, Block::QuoteEnd,
+            Block::Columns(1), Block::Toc];
+
+        assert_eq!(block_columns(&blocks, &ColumnConfig::default()), vec![1, 3, 3, 3, 1, 1]);
+    }
+}
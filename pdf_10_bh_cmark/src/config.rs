@@ -5,9 +5,10 @@
 
 use serde::Deserialize;
 use std::fs;
+use std::path::Path;
 use toml;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct FontSpec {
     family: String,
     size: i32,
@@ -15,62 +16,463 @@ pub struct FontSpec {
     style: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct FontConfig {
     headers: Vec<FontSpec>,
     paragraph: FontSpec,
     caption: FontSpec,
     page_number: FontSpec,
+    #[serde(default = "FontSpec::default_code")]
+    code: FontSpec,
 }
 
-#[derive(Debug, Deserialize)]
+/// How wide an image is drawn, before `reduction_factor`. An unknown mode 
+/// fails the configuration load.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImageSizing {
+    /// The full content width.
+    FitWidth,
+    /// The physical size from the resolution the image records.
+    Natural,
+    /// The natural size, but no wider than the content width.
+    NaturalMaxWidth,
+}
+
+/// Where the caption of an image goes.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CaptionPosition {
+    Below,
+    Above,
+}
+
+/// How wide the caption of an image is.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CaptionWidth {
+    /// The content width.
+    Content,
+    /// The width of the scaled image.
+    Image,
+}
+
+/// How the lines of an image caption are aligned.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CaptionAlignment {
+    Left,
+    Centre,
+    Right,
+    Justify,
+}
+
+#[derive(Debug, Clone, Deserialize)]
 pub struct ImageBlockConfig {
     reduction_factor: f64,
     centre_aligned: bool,
     step_scale_factor: f64,
     min_allowed_scale: f64,
+    /// How wide an image is drawn: "fit_width", "natural" or
+    /// "natural_max_width".
+    #[serde(default = "ImageBlockConfig::default_sizing")]
+    sizing: ImageSizing,
+    /// The resolution of images which do not record their own, in pixels
+    /// per inch.
+    #[serde(default = "ImageBlockConfig::standard_dpi")]
+    default_dpi: f64,
+    /// Directories searched for images not found next to the Markdown file,
+    /// relative to the Markdown file's directory unless absolute.
+    #[serde(default)]
+    assets_dir: Vec<String>,
+    /// The horizontal gap between the images of a figure row.
+    #[serde(default = "ImageBlockConfig::default_row_gap")]
+    row_gap: f64,
+    /// The width of a floating image, as a fraction of the content width.
+    #[serde(default = "ImageBlockConfig::default_float_width")]
+    float_width: f64,
+    /// The horizontal gap between a floating image and the text beside it.
+    #[serde(default = "ImageBlockConfig::default_row_gap")]
+    float_gap: f64,
+    /// Where the caption of an image goes: "below" or "above" it.
+    #[serde(default = "ImageBlockConfig::default_caption_position")]
+    caption_position: CaptionPosition,
+    /// How wide the caption of an image is: "content", the content width, 
+    /// or "image", the width of the scaled image.
+    #[serde(default = "ImageBlockConfig::default_caption_width")]
+    caption_width: CaptionWidth,
+    /// "left", "centre", "right" or "justify". When absent, the caption is 
+    /// justified, and centred as a block when `centre_aligned`.
+    #[serde(default)]
+    caption_alignment: Option<CaptionAlignment>,
+    /// The vertical gap between an image and its caption.
+    #[serde(default)]
+    caption_gap: f64,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct HeadingSpacing {
     // Array of 6 floats for H1, H2, H3, H4, H5, H6.
     before: [f64; 6],
     after: [f64; 6],
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct ElementSpacing {
     before: f64,
     after: f64,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct BlockSpacingConfig {
     heading: HeadingSpacing,
     paragraph: ElementSpacing,
     image: ElementSpacing,
     thematic: ElementSpacing,
+    #[serde(default = "ElementSpacing::default_list")]
+    list: ElementSpacing,
+    #[serde(default = "ElementSpacing::default_code")]
+    code: ElementSpacing,
+    #[serde(default = "ElementSpacing::default_quote")]
+    quote: ElementSpacing,
+    #[serde(default = "ElementSpacing::default_table")]
+    table: ElementSpacing,
+    #[serde(default = "ElementSpacing::default_toc")]
+    toc: ElementSpacing,
 }
 
-#[derive(Deserialize)]
+#[derive(Clone, Deserialize)]
 pub struct ColourRGB {
     r: f64,
     g: f64,
     b: f64,
 }
 
-#[derive(Deserialize)]
+#[derive(Clone, Deserialize)]
 pub struct HorizontalBreakConfig {
     stroke_width: f64,
     colour: ColourRGB,
 }
 
-#[derive(Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
+pub struct MarginsConfig {
+    top: f64,
+    right: f64,
+    bottom: f64,
+    left: f64,
+}
+
+/// A named page size. The lower case names are accepted too.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub enum PageSizeName {
+    #[serde(alias = "a3")]
+    A3,
+    #[serde(alias = "a4")]
+    A4,
+    #[serde(alias = "a5")]
+    A5,
+    #[serde(alias = "letter")]
+    Letter,
+    #[serde(alias = "legal")]
+    Legal,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Orientation {
+    Portrait,
+    Landscape,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct PageConfig {
+    /// "A3", "A4", "A5", "Letter" or "Legal".
+    size: PageSizeName,
+    /// Custom page width in points. Takes precedence over `size` when 
+    /// `height` is also given.
+    width: Option<f64>,
+    /// Custom page height in points.
+    height: Option<f64>,
+    /// "portrait" or "landscape". Left unset, a named size is portrait and 
+    /// a custom `width` / `height` is used as given.
+    orientation: Option<Orientation>,
+    margins: MarginsConfig,
+}
+
+/// How the items of an ordered list are numbered. An unknown style fails 
+/// the configuration load.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ListNumbering {
+    /// 1, 2, 3.
+    Decimal,
+    /// a, b, c.
+    LowerAlpha,
+    /// A, B, C.
+    UpperAlpha,
+    /// i, ii, iii.
+    LowerRoman,
+    /// I, II, III.
+    UpperRoman,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ListConfig {
+    /// Bullet glyph for each nesting level.
+    bullets: Vec<String>,
+    /// Numbering style for each nesting level: "decimal", "lower_alpha", 
+    /// "upper_alpha", "lower_roman" or "upper_roman".
+    numbering: Vec<ListNumbering>,
+    /// Text after an item number, such as "." or ")".
+    number_suffix: String,
+    /// Horizontal indentation of each nesting level.
+    indent: f64,
+    /// Horizontal gap between a marker and its item text.
+    marker_gap: f64,
+    /// Vertical spacing between items.
+    item_spacing: f64,
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct CodeBlockConfig {
+    /// Shading behind the code.
+    background: ColourRGB,
+    /// Space between the shading edges and the code text.
+    padding: f64,
+    /// Tabs advance to the next multiple of this many characters.
+    tab_width: usize,
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct QuoteConfig {
+    /// Horizontal indentation of each nesting level. The rule is drawn at 
+    /// the start of the indentation.
+    indent: f64,
+    rule_width: f64,
+    rule_colour: ColourRGB,
+    /// Font for quoted text in place of the paragraph font, such as an 
+    /// italic one.
+    font: Option<FontSpec>,
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct TableConfig {
+    /// Space between the cell borders and the cell text.
+    cell_padding: f64,
+    /// `0.0` draws no borders.
+    border_width: f64,
+    border_colour: ColourRGB,
+    /// Shading behind the header row, none when absent.
+    header_background: Option<ColourRGB>,
+    /// Font for the header row, the paragraph font in bold when absent.
+    header_font: Option<FontSpec>,
+    /// Repeat the header row at the top of each page a table continues onto.
+    repeat_header: bool,
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct LinkConfig {
+    colour: ColourRGB,
+    underline: bool,
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct TocConfig {
+    /// Put a table of contents at the start of documents without a `[TOC]` 
+    /// marker.
+    enabled: bool,
+    /// The deepest heading level listed.
+    depth: u8,
+    /// Heading above the entries, none when empty.
+    title: String,
+    /// Horizontal indentation of each heading level below the first.
+    indent: f64,
+    /// Repeated between an entry and its page number, none when empty.
+    leader: String,
+    /// Vertical spacing between entries.
+    entry_spacing: f64,
+    /// Font for the entries, the paragraph font when absent.
+    font: Option<FontSpec>,
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct PaginationConfig {
+    /// The fewest lines of a paragraph or heading left at the bottom of a 
+    /// page before a break.
+    orphans: usize,
+    /// The fewest lines of a paragraph or heading carried onto the top of a 
+    /// page after a break.
+    widows: usize,
+    /// For each heading level, H1 to H6: the fewest lines of the next block 
+    /// kept on the heading's page, `0` for none. An image block is always 
+    /// kept whole.
+    keep_with_next: [usize; 6],
+    /// A thematic break never begins a page. When one does not fit at the 
+    /// bottom of a page: "page_break" ends the page there, "drop" leaves it 
+    /// out and the blocks after it carry on on the same page.
+    thematic_at_page_top: ThematicAtPageTop,
+}
+
+/// What becomes of a thematic break which does not fit at the bottom of a 
+/// page.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThematicAtPageTop {
+    /// The thematic break ends the page.
+    PageBreak,
+    /// The thematic break is left out.
+    Drop,
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct RunningHeadConfig {
+    /// Which heading a page shows when one begins part way down it: 
+    /// "first", "start" or "last".
+    mark: RunningHeadMark,
+}
+
+/// Which heading a page shows when one begins part way down it, see 
+/// [`crate::running_head`].
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunningHeadMark {
+    /// The first heading begun on the page.
+    First,
+    /// The heading in effect at the top of the page.
+    Start,
+    /// The last heading begun on or before the page.
+    Last,
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct FigureConfig {
+    /// Put the figure label, and `separator`, before each image caption. 
+    /// Off by default: captions are shown as written.
+    numbered: bool,
+    /// The figure label, with `{number}` replaced by the figure number.
+    label: String,
+    /// Between the figure label and the caption, left out when the caption 
+    /// is blank.
+    separator: String,
+    /// What a `@fig:name` reference becomes, with `{figure}` replaced by 
+    /// the figure label and `{page}` by its page number.
+    reference: String,
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct ColumnConfig {
+    /// The number of columns text flows through, until a `[columns=N]` 
+    /// marker changes it.
+    count: usize,
+    /// The horizontal space between two columns.
+    gutter: f64,
+    /// Draw a vertical rule in the middle of each gutter.
+    rule: bool,
+    rule_width: f64,
+    rule_colour: ColourRGB,
+    /// Images and figure rows span all the columns rather than fit inside 
+    /// one. A single image can span them with `{span}`.
+    span_images: bool,
+}
+
+#[derive(Clone, Deserialize)]
+pub struct PageTemplateSlot {
+    /// The text, with `{page}`, `{total}`, `{title}`, `{chapter}`, 
+    /// `{section}` and `{date}` replaced.
+    text: String,
+    /// The `[fonts] page_number` font when absent.
+    font: Option<FontSpec>,
+}
+
+#[derive(Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct PageTemplateConfig {
+    /// Aligned against the left margin.
+    left: Option<PageTemplateSlot>,
+    /// Centred between the margins.
+    centre: Option<PageTemplateSlot>,
+    /// Aligned against the right margin.
+    right: Option<PageTemplateSlot>,
+    /// Leave the first page without this header or footer.
+    skip_first_page: bool,
+}
+
+#[derive(Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct MetadataConfig {
+    /// The document title when absent: the first level 1 heading, or the 
+    /// input file name.
+    title: Option<String>,
+    author: Option<String>,
+    subject: Option<String>,
+    keywords: Vec<String>,
+    /// The application which made the document, Cairo's own when absent.
+    creator: Option<String>,
+    /// The language of the text, such as "en" or "vi", which guides Pango's 
+    /// line breaking and shaping. The locale's language when absent.
+    language: Option<String>,
+    /// `YYYY-MM-DD` or `YYYY-MM-DDThh:mm:ss`, with an optional `Z` or 
+    /// `±hh:mm` time zone. When absent, `SOURCE_DATE_EPOCH` or the current 
+    /// time.
+    creation_date: Option<String>,
+}
+
+/// The top-level keys of [`Config`]: its sections.
+pub const CONFIG_SECTIONS: [&str; 18] = [
+    "page", "fonts", "image_block", "block_spacing", "horizontal_break", "list", 
+    "code_block", "quote", "table", "link", "toc", "pagination", "header", 
+    "footer", "running_head", "figure", "columns", "metadata",
+];
+
+#[derive(Clone, Deserialize)]
 pub struct Config {
+    #[serde(default)]
+    page: PageConfig,
     fonts: FontConfig,
     image_block: ImageBlockConfig,
     block_spacing: BlockSpacingConfig,
     horizontal_break: HorizontalBreakConfig,
+    #[serde(default)]
+    list: ListConfig,
+    #[serde(default)]
+    code_block: CodeBlockConfig,
+    #[serde(default)]
+    quote: QuoteConfig,
+    #[serde(default)]
+    table: TableConfig,
+    #[serde(default)]
+    link: LinkConfig,
+    #[serde(default)]
+    toc: TocConfig,
+    #[serde(default)]
+    pagination: PaginationConfig,
+    #[serde(default)]
+    header: PageTemplateConfig,
+    #[serde(default = "PageTemplateConfig::default_footer")]
+    footer: PageTemplateConfig,
+    #[serde(default)]
+    running_head: RunningHeadConfig,
+    #[serde(default)]
+    figure: FigureConfig,
+    #[serde(default)]
+    columns: ColumnConfig,
+    #[serde(default)]
+    metadata: MetadataConfig,
+    /// The configuration file as loaded, which front matter overrides are 
+    /// applied over.
+    #[serde(skip)]
+    source: toml::Table,
 }
 
 impl FontSpec {
@@ -88,6 +490,15 @@ impl FontSpec {
     pub fn style(&self) -> &str {
         &self.style
     }
+
+    fn default_code() -> Self {
+        FontSpec {
+            family: "monospace".to_string(),
+            size: 10,
+            weight: "normal".to_string(),
+            style: "normal".to_string(),
+        }
+    }
 }
 
 impl FontConfig {
@@ -106,6 +517,10 @@ impl FontConfig {
     pub fn page_number(&self) -> &FontSpec {
         &self.page_number
     }
+
+    pub fn code(&self) -> &FontSpec {
+        &self.code
+    }
 }
 
 impl ImageBlockConfig {
@@ -124,6 +539,71 @@ impl ImageBlockConfig {
     pub fn min_allowed_scale(&self) -> f64 {
         self.min_allowed_scale
     }
+
+    pub fn sizing(&self) -> ImageSizing {
+        self.sizing
+    }
+
+    pub fn default_dpi(&self) -> f64 {
+        self.default_dpi
+    }
+
+    pub fn assets_dir(&self) -> &[String] {
+        &self.assets_dir
+    }
+
+    pub fn row_gap(&self) -> f64 {
+        self.row_gap
+    }
+
+    pub fn float_width(&self) -> f64 {
+        self.float_width
+    }
+
+    pub fn float_gap(&self) -> f64 {
+        self.float_gap
+    }
+
+    pub fn caption_position(&self) -> CaptionPosition {
+        self.caption_position
+    }
+
+    pub fn caption_width(&self) -> CaptionWidth {
+        self.caption_width
+    }
+
+    pub fn caption_alignment(&self) -> Option<CaptionAlignment> {
+        self.caption_alignment
+    }
+
+    pub fn caption_gap(&self) -> f64 {
+        self.caption_gap
+    }
+
+    fn default_sizing() -> ImageSizing {
+        ImageSizing::FitWidth
+    }
+
+    /// The CSS reference resolution.
+    fn standard_dpi() -> f64 {
+        96.0
+    }
+
+    fn default_row_gap() -> f64 {
+        12.0
+    }
+
+    fn default_float_width() -> f64 {
+        0.4
+    }
+
+    fn default_caption_position() -> CaptionPosition {
+        CaptionPosition::Below
+    }
+
+    fn default_caption_width() -> CaptionWidth {
+        CaptionWidth::Content
+    }
 }
 
 impl HeadingSpacing {
@@ -144,6 +624,27 @@ impl ElementSpacing {
     pub fn after(&self) -> f64 {
         self.after
     }
+
+    /// Lists are spaced like paragraphs.
+    fn default_list() -> Self {
+        ElementSpacing { before: 15.0, after: 0.0 }
+    }
+
+    fn default_code() -> Self {
+        ElementSpacing { before: 15.0, after: 0.0 }
+    }
+
+    fn default_quote() -> Self {
+        ElementSpacing { before: 10.0, after: 10.0 }
+    }
+
+    fn default_table() -> Self {
+        ElementSpacing { before: 15.0, after: 0.0 }
+    }
+
+    fn default_toc() -> Self {
+        ElementSpacing { before: 15.0, after: 0.0 }
+    }
 }
 
 impl BlockSpacingConfig {
@@ -162,6 +663,26 @@ impl BlockSpacingConfig {
     pub fn thematic(&self) -> &ElementSpacing {
         &self.thematic
     }
+
+    pub fn list(&self) -> &ElementSpacing {
+        &self.list
+    }
+
+    pub fn code(&self) -> &ElementSpacing {
+        &self.code
+    }
+
+    pub fn quote(&self) -> &ElementSpacing {
+        &self.quote
+    }
+
+    pub fn table(&self) -> &ElementSpacing {
+        &self.table
+    }
+
+    pub fn toc(&self) -> &ElementSpacing {
+        &self.toc
+    }
 }
 
 impl ColourRGB {
@@ -188,7 +709,454 @@ impl HorizontalBreakConfig {
     }
 }
 
+impl MarginsConfig {
+    pub fn top(&self) -> f64 {
+        self.top
+    }
+
+    pub fn right(&self) -> f64 {
+        self.right
+    }
+
+    pub fn bottom(&self) -> f64 {
+        self.bottom
+    }
+
+    pub fn left(&self) -> f64 {
+        self.left
+    }
+}
+
+impl Default for MarginsConfig {
+    /// 20mm on all sides.
+    fn default() -> Self {
+        MarginsConfig { top: 57.0, right: 57.0, bottom: 57.0, left: 57.0 }
+    }
+}
+
+impl PageConfig {
+    pub fn size(&self) -> PageSizeName {
+        self.size
+    }
+
+    pub fn width(&self) -> Option<f64> {
+        self.width
+    }
+
+    pub fn height(&self) -> Option<f64> {
+        self.height
+    }
+
+    pub fn orientation(&self) -> Option<Orientation> {
+        self.orientation
+    }
+
+    pub fn margins(&self) -> &MarginsConfig {
+        &self.margins
+    }
+}
+
+impl Default for PageConfig {
+    /// Portrait A4 with 20mm margins: the layout before `[page]` existed.
+    fn default() -> Self {
+        PageConfig {
+            size: PageSizeName::A4,
+            width: None,
+            height: None,
+            orientation: None,
+            margins: MarginsConfig::default(),
+        }
+    }
+}
+
+impl ListConfig {
+    /// The bullet glyph for a nesting level. Deeper levels than configured 
+    /// repeat the last glyph.
+    pub fn bullet(&self, level: usize) -> &str {
+        self.bullets.get(level).or(self.bullets.last()).map_or("•", |s| s.as_str())
+    }
+
+    /// The numbering style for a nesting level. Deeper levels than configured 
+    /// repeat the last style.
+    pub fn numbering(&self, level: usize) -> ListNumbering {
+        self.numbering.get(level).or(self.numbering.last()).copied()
+            .unwrap_or(ListNumbering::Decimal)
+    }
+
+    pub fn number_suffix(&self) -> &str {
+        &self.number_suffix
+    }
+
+    pub fn indent(&self) -> f64 {
+        self.indent
+    }
+
+    pub fn marker_gap(&self) -> f64 {
+        self.marker_gap
+    }
+
+    pub fn item_spacing(&self) -> f64 {
+        self.item_spacing
+    }
+}
+
+impl Default for ListConfig {
+    fn default() -> Self {
+        ListConfig {
+            bullets: vec!["•".to_string(), "◦".to_string(), "▪".to_string()],
+            numbering: vec![ListNumbering::Decimal, ListNumbering::LowerAlpha, 
+                ListNumbering::LowerRoman],
+            number_suffix: ".".to_string(),
+            indent: 18.0,
+            marker_gap: 6.0,
+            item_spacing: 4.0,
+        }
+    }
+}
+
+impl CodeBlockConfig {
+    pub fn background(&self) -> &ColourRGB {
+        &self.background
+    }
+
+    pub fn padding(&self) -> f64 {
+        self.padding
+    }
+
+    pub fn tab_width(&self) -> usize {
+        self.tab_width
+    }
+}
+
+impl Default for CodeBlockConfig {
+    fn default() -> Self {
+        CodeBlockConfig {
+            background: ColourRGB { r: 0.95, g: 0.95, b: 0.95 },
+            padding: 6.0,
+            tab_width: 4,
+        }
+    }
+}
+
+impl QuoteConfig {
+    pub fn indent(&self) -> f64 {
+        self.indent
+    }
+
+    pub fn rule_width(&self) -> f64 {
+        self.rule_width
+    }
+
+    pub fn rule_colour(&self) -> &ColourRGB {
+        &self.rule_colour
+    }
+
+    pub fn font(&self) -> Option<&FontSpec> {
+        self.font.as_ref()
+    }
+}
+
+impl Default for QuoteConfig {
+    fn default() -> Self {
+        QuoteConfig {
+            indent: 18.0,
+            rule_width: 2.0,
+            rule_colour: ColourRGB { r: 0.7, g: 0.7, b: 0.7 },
+            font: None,
+        }
+    }
+}
+
+impl TableConfig {
+    pub fn cell_padding(&self) -> f64 {
+        self.cell_padding
+    }
+
+    pub fn border_width(&self) -> f64 {
+        self.border_width
+    }
+
+    pub fn border_colour(&self) -> &ColourRGB {
+        &self.border_colour
+    }
+
+    pub fn header_background(&self) -> Option<&ColourRGB> {
+        self.header_background.as_ref()
+    }
+
+    pub fn header_font(&self) -> Option<&FontSpec> {
+        self.header_font.as_ref()
+    }
+
+    pub fn repeat_header(&self) -> bool {
+        self.repeat_header
+    }
+}
+
+impl Default for TableConfig {
+    fn default() -> Self {
+        TableConfig {
+            cell_padding: 4.0,
+            border_width: 0.5,
+            border_colour: ColourRGB { r: 0.5, g: 0.5, b: 0.5 },
+            header_background: Some(ColourRGB { r: 0.9, g: 0.9, b: 0.9 }),
+            header_font: None,
+            repeat_header: true,
+        }
+    }
+}
+
+impl LinkConfig {
+    pub fn colour(&self) -> &ColourRGB {
+        &self.colour
+    }
+
+    pub fn underline(&self) -> bool {
+        self.underline
+    }
+}
+
+impl Default for LinkConfig {
+    fn default() -> Self {
+        LinkConfig {
+            colour: ColourRGB { r: 0.0, g: 0.2, b: 0.8 },
+            underline: true,
+        }
+    }
+}
+
+impl TocConfig {
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn depth(&self) -> u8 {
+        self.depth
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn indent(&self) -> f64 {
+        self.indent
+    }
+
+    pub fn leader(&self) -> &str {
+        &self.leader
+    }
+
+    pub fn entry_spacing(&self) -> f64 {
+        self.entry_spacing
+    }
+
+    pub fn font(&self) -> Option<&FontSpec> {
+        self.font.as_ref()
+    }
+}
+
+impl Default for TocConfig {
+    fn default() -> Self {
+        TocConfig {
+            enabled: false,
+            depth: 3,
+            title: "Contents".to_string(),
+            indent: 18.0,
+            leader: ".".to_string(),
+            entry_spacing: 4.0,
+            font: None,
+        }
+    }
+}
+
+impl FigureConfig {
+    pub fn numbered(&self) -> bool {
+        self.numbered
+    }
+
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    pub fn separator(&self) -> &str {
+        &self.separator
+    }
+
+    pub fn reference(&self) -> &str {
+        &self.reference
+    }
+}
+
+impl ColumnConfig {
+    /// At least `1`.
+    pub fn count(&self) -> usize {
+        self.count.max(1)
+    }
+
+    pub fn gutter(&self) -> f64 {
+        self.gutter
+    }
+
+    pub fn rule(&self) -> bool {
+        self.rule
+    }
+
+    pub fn rule_width(&self) -> f64 {
+        self.rule_width
+    }
+
+    pub fn rule_colour(&self) -> &ColourRGB {
+        &self.rule_colour
+    }
+
+    pub fn span_images(&self) -> bool {
+        self.span_images
+    }
+}
+
+impl Default for ColumnConfig {
+    fn default() -> Self {
+        ColumnConfig {
+            count: 1,
+            gutter: 18.0,
+            rule: false,
+            rule_width: 0.5,
+            rule_colour: ColourRGB { r: 0.7, g: 0.7, b: 0.7 },
+            span_images: false,
+        }
+    }
+}
+
+impl Default for FigureConfig {
+    fn default() -> Self {
+        FigureConfig {
+            numbered: false,
+            label: "Figure {number}".to_string(),
+            separator: ": ".to_string(),
+            reference: "{figure} on page {page}".to_string(),
+        }
+    }
+}
+
+impl PaginationConfig {
+    pub fn orphans(&self) -> usize {
+        self.orphans
+    }
+
+    pub fn widows(&self) -> usize {
+        self.widows
+    }
+
+    pub fn keep_with_next(&self, level: u8) -> usize {
+        self.keep_with_next[(level - 1) as usize]
+    }
+
+    pub fn thematic_at_page_top(&self) -> ThematicAtPageTop {
+        self.thematic_at_page_top
+    }
+}
+
+impl Default for PaginationConfig {
+    /// No widow or orphan control, and headings may end a page: the layout 
+    /// before `[pagination]` existed.
+    fn default() -> Self {
+        PaginationConfig {
+            orphans: 1,
+            widows: 1,
+            keep_with_next: [0; 6],
+            thematic_at_page_top: ThematicAtPageTop::PageBreak,
+        }
+    }
+}
+
+impl RunningHeadConfig {
+    pub fn mark(&self) -> RunningHeadMark {
+        self.mark
+    }
+}
+
+impl Default for RunningHeadConfig {
+    fn default() -> Self {
+        RunningHeadConfig {
+            mark: RunningHeadMark::First,
+        }
+    }
+}
+
+impl PageTemplateSlot {
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn font(&self) -> Option<&FontSpec> {
+        self.font.as_ref()
+    }
+}
+
+impl PageTemplateConfig {
+    pub fn left(&self) -> Option<&PageTemplateSlot> {
+        self.left.as_ref()
+    }
+
+    pub fn centre(&self) -> Option<&PageTemplateSlot> {
+        self.centre.as_ref()
+    }
+
+    pub fn right(&self) -> Option<&PageTemplateSlot> {
+        self.right.as_ref()
+    }
+
+    pub fn skip_first_page(&self) -> bool {
+        self.skip_first_page
+    }
+
+    /// "{page} of {total}", centred.
+    fn default_footer() -> Self {
+        PageTemplateConfig {
+            centre: Some(PageTemplateSlot {
+                text: "{page} of {total}".to_string(),
+                font: None,
+            }),
+            ..Default::default()
+        }
+    }
+}
+
+impl MetadataConfig {
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    pub fn author(&self) -> Option<&str> {
+        self.author.as_deref()
+    }
+
+    pub fn subject(&self) -> Option<&str> {
+        self.subject.as_deref()
+    }
+
+    pub fn keywords(&self) -> &[String] {
+        &self.keywords
+    }
+
+    pub fn creator(&self) -> Option<&str> {
+        self.creator.as_deref()
+    }
+
+    pub fn creation_date(&self) -> Option<&str> {
+        self.creation_date.as_deref()
+    }
+
+    pub fn language(&self) -> Option<&str> {
+        self.language.as_deref()
+    }
+}
+
 impl Config {
+    pub fn page(&self) -> &PageConfig {
+        &self.page
+    }
+
     pub fn fonts(&self) -> &FontConfig {
         &self.fonts
     }
@@ -204,9 +1172,100 @@ impl Config {
     pub fn horizontal_break(&self) -> &HorizontalBreakConfig {
         &self.horizontal_break
     }
+
+    pub fn list(&self) -> &ListConfig {
+        &self.list
+    }
+
+    pub fn code_block(&self) -> &CodeBlockConfig {
+        &self.code_block
+    }
+
+    pub fn quote(&self) -> &QuoteConfig {
+        &self.quote
+    }
+
+    pub fn table(&self) -> &TableConfig {
+        &self.table
+    }
+
+    pub fn link(&self) -> &LinkConfig {
+        &self.link
+    }
+
+    pub fn toc(&self) -> &TocConfig {
+        &self.toc
+    }
+
+    pub fn pagination(&self) -> &PaginationConfig {
+        &self.pagination
+    }
+
+    pub fn header(&self) -> &PageTemplateConfig {
+        &self.header
+    }
+
+    pub fn footer(&self) -> &PageTemplateConfig {
+        &self.footer
+    }
+
+    pub fn running_head(&self) -> &RunningHeadConfig {
+        &self.running_head
+    }
+
+    pub fn figure(&self) -> &FigureConfig {
+        &self.figure
+    }
+
+    pub fn columns(&self) -> &ColumnConfig {
+        &self.columns
+    }
+
+    pub fn metadata(&self) -> &MetadataConfig {
+        &self.metadata
+    }
+
+    /// The configuration for one document: `overrides`, from its front 
+    /// matter, replace the same keys of the configuration file. Tables are 
+    /// merged key by key, any other value is replaced whole.
+    pub fn with_overrides(&self, 
+        overrides: &toml::Table
+    ) -> Result<Config, Box<dyn std::error::Error>> {
+        let mut source = self.source.clone();
+        merge_tables(&mut source, overrides);
+
+        let mut config: Config = source.clone().try_into()?;
+        config.source = source;
+        Ok(config)
+    }
+
+    /// The configuration for blocks within a blockquote: the `[quote]` font,
+    /// when given, replaces the paragraph font.
+    pub fn for_quote(&self) -> Config {
+        let mut config = self.clone();
+        if let Some(font) = self.quote.font() {
+            config.fonts.paragraph = font.clone();
+        }
+        config
+    }
 }
 
-pub fn load_config(file_path: &str) -> Result<Config, Box<dyn std::error::Error>> {
-    let config: Config = toml::from_str(&fs::read_to_string(file_path)?)?;
+/// Merge `overrides` into `base`, table by table.
+fn merge_tables(base: &mut toml::Table, overrides: &toml::Table) {
+    for (key, value) in overrides {
+        match (base.get_mut(key), value) {
+            (Some(toml::Value::Table(base_table)), toml::Value::Table(table)) => {
+                merge_tables(base_table, table);
+            },
+            _ => { base.insert(key.clone(), value.clone()); },
+        }
+    }
+}
+
+pub fn load_config(file_path: &Path) -> Result<Config, Box<dyn std::error::Error>> {
+    let source: toml::Table = fs::read_to_string(file_path)?.parse()?;
+
+    let mut config: Config = source.clone().try_into()?;
+    config.source = source;
     Ok(config)
 }
\ No newline at end of file
@@ -0,0 +1,140 @@
+/* 17/10/2026 */
+
+//!
+//! Calendar dates without a date library: the proleptic Gregorian calendar,
+//! in UTC.
+//!
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The `(year, month, day)` of a number of days since 1970-01-01.
+///
+/// Howard Hinnant's `civil_from_days` algorithm: the year is counted from
+/// March, so that the leap day falls at its end.
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524
+        - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month, day)
+}
+
+/// A Unix timestamp, in seconds, as an ISO 8601 date: `YYYY-MM-DD`.
+pub fn iso_date(unix_seconds: i64) -> String {
+    let (year, month, day) = civil_from_days(unix_seconds.div_euclid(86_400));
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// A Unix timestamp, in seconds, as an ISO 8601 UTC date and time:
+/// `YYYY-MM-DDThh:mm:ssZ`.
+pub fn iso_date_time(unix_seconds: i64) -> String {
+    let seconds = unix_seconds.rem_euclid(86_400);
+    format!("{}T{:02}:{:02}:{:02}Z", iso_date(unix_seconds),
+        seconds / 3_600, seconds / 60 % 60, seconds % 60)
+}
+
+/// Check an ISO 8601 date, `YYYY-MM-DD`, or date and time, 
+/// `YYYY-MM-DDThh:mm:ss` with an optional `Z` or `±hh:mm` time zone.
+///
+/// # Returns
+///
+/// The date and time, midnight for a date alone, or `None` when `text` is
+/// not in either form.
+pub fn parse_iso_date_time(text: &str) -> Option<String> {
+    if !text.is_ascii() { return None; }
+
+    // Two digits within `min..=max`.
+    let field = |digits: &str, min: u32, max: u32| -> bool {
+        digits.len() == 2 && digits.bytes().all(|byte| byte.is_ascii_digit())
+            && digits.parse::<u32>().is_ok_and(|value| (min..=max).contains(&value))
+    };
+
+    let (date, time) = text.split_once('T').unwrap_or((text, "00:00:00"));
+
+    let valid_date = date.len() == 10
+        && date[..4].bytes().all(|byte| byte.is_ascii_digit())
+        && &date[4..5] == "-" && field(&date[5..7], 1, 12)
+        && &date[7..8] == "-" && field(&date[8..10], 1, 31);
+
+    let zone = if time.len() > 8 { &time[8..] } else { "" };
+    let valid_zone = zone.is_empty() || zone == "Z"
+        || (zone.len() == 6 && (zone.starts_with('+') || zone.starts_with('-'))
+            && field(&zone[1..3], 0, 23) && &zone[3..4] == ":" && field(&zone[4..6], 0, 59));
+
+    let valid_time = time.len() >= 8
+        && field(&time[..2], 0, 23) && &time[2..3] == ":"
+        && field(&time[3..5], 0, 59) && &time[5..6] == ":" && field(&time[6..8], 0, 59)
+        && valid_zone;
+
+    (valid_date && valid_time).then(|| format!("{date}T{time}"))
+}
+
+/// The current time as a Unix timestamp, in seconds.
+pub fn now() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs() as i64)
+}
+
+// To run test for this module only:
+//
+//     * cargo test date_utils::tests
+//
+//     * cargo test date_utils::tests::test_iso_date -- --exact [--nocapture]
+//     * cargo test date_utils::tests::test_iso_date_time -- --exact [--nocapture]
+//     * cargo test date_utils::tests::test_parse_iso_date_time -- --exact [--nocapture]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_iso_date() {
+        let test_data: Vec<(i64, &str)> = vec![
+            (0, "1970-01-01"),
+            (-1, "1969-12-31"),
+            (951_782_400, "2000-02-29"),
+            (1_709_164_800, "2024-02-29"),
+            (1_792_195_199, "2026-10-16"),
+            (1_792_195_200, "2026-10-17"),
+        ];
+
+        for (unix_seconds, expected) in test_data {
+            assert_eq!(iso_date(unix_seconds), expected, "{unix_seconds}");
+        }
+    }
+
+    #[test]
+    fn test_iso_date_time() {
+        assert_eq!(iso_date_time(0), "1970-01-01T00:00:00Z");
+        assert_eq!(iso_date_time(1_792_241_999), "2026-10-17T12:59:59Z");
+        assert_eq!(iso_date_time(-1), "1969-12-31T23:59:59Z");
+    }
+
+    #[test]
+    fn test_parse_iso_date_time() {
+        let test_data: Vec<(&str, Option<&str>)> = vec![
+            ("2026-10-17", Some("2026-10-17T00:00:00")),
+            ("2026-10-17T08:30:00", Some("2026-10-17T08:30:00")),
+            ("2026-10-17T08:30:00Z", Some("2026-10-17T08:30:00Z")),
+            ("2026-10-17T08:30:00+10:00", Some("2026-10-17T08:30:00+10:00")),
+            ("2026-13-17", None),
+            ("2026-10-17T24:00:00", None),
+            ("2026-10-17T08:30", None),
+            ("2026-10-17T08:30:00+1000", None),
+            ("17/10/2026", None),
+            ("2026-10-1é", None),
+            ("", None),
+        ];
+
+        for (text, expected) in test_data {
+            assert_eq!(parse_iso_date_time(text).as_deref(), expected, "{text}");
+        }
+    }
+}
@@ -3,6 +3,213 @@
 //! Types describe the structure of a document.
 //! The home of layout‑agnostic block metadata.
 
+use bh_cmark::ast::{AstBlock, InlineContent};
+
+/// A semantic block of the document: either produced by `bh_cmark`, or one of 
+/// the constructs recognised by [`crate::block_parser`].
+pub enum Block {
+    Ast(AstBlock),
+    List(ListBlock),
+    Code(CodeBlock),
+    Table(TableBlock),
+    /// Opens a blockquote: the blocks up to the matching `QuoteEnd` are 
+    /// quoted. Quotes nest.
+    QuoteStart,
+    /// Closes the innermost open blockquote.
+    QuoteEnd,
+    /// The table of contents: its entries are only known once the document 
+    /// has been paginated.
+    Toc,
+    /// A single image with attributes, such as a label, `{#fig:name}`. 
+    /// Images without any are `Ast` blocks.
+    Image(Figure),
+    FigureRow(FigureRowBlock),
+    FloatImage(FloatImageBlock),
+    /// A `[columns=N]` marker: the blocks after it flow through this many 
+    /// columns.
+    Columns(usize),
+}
+
+/// A single list item. Nested lists are flattened: each item carries its 
+/// nesting level.
+pub struct ListItem {
+    /// Nesting level, `0` is the outermost list.
+    level: usize,
+    /// Numbered or bulleted.
+    ordered: bool,
+    /// The item number: only meaningful for ordered items.
+    number: u32,
+    /// The item text. `None` for an empty item.
+    content: Option<InlineContent>,
+}
+
+impl ListItem {
+    pub fn new(level: usize, 
+        ordered: bool, 
+        number: u32, 
+        content: Option<InlineContent>
+    ) -> Self {
+        ListItem { level, ordered, number, content }
+    }
+
+    pub fn level(&self) -> usize {
+        self.level
+    }
+
+    pub fn ordered(&self) -> bool {
+        self.ordered
+    }
+
+    pub fn number(&self) -> u32 {
+        self.number
+    }
+
+    pub fn content(&self) -> Option<&InlineContent> {
+        self.content.as_ref()
+    }
+}
+
+/// An ordered or unordered list, including its nested lists.
+pub struct ListBlock {
+    items: Vec<ListItem>,
+}
+
+impl ListBlock {
+    pub fn new(items: Vec<ListItem>) -> Self {
+        ListBlock { items }
+    }
+
+    pub fn items(&self) -> &[ListItem] {
+        &self.items
+    }
+}
+
+/// The text of a fenced code block, exactly as written between the fences.
+pub struct CodeBlock {
+    text: String,
+}
+
+impl CodeBlock {
+    pub fn new(text: String) -> Self {
+        CodeBlock { text }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+/// Horizontal alignment of the cells of a table column.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColumnAlignment {
+    Left,
+    Centre,
+    Right,
+}
+
+/// A table: a header row and body rows, all with one cell per column. An 
+/// empty cell is `None`.
+pub struct TableBlock {
+    alignments: Vec<ColumnAlignment>,
+    header: Vec<Option<InlineContent>>,
+    rows: Vec<Vec<Option<InlineContent>>>,
+}
+
+impl TableBlock {
+    pub fn new(alignments: Vec<ColumnAlignment>, 
+        header: Vec<Option<InlineContent>>, 
+        rows: Vec<Vec<Option<InlineContent>>>
+    ) -> Self {
+        TableBlock { alignments, header, rows }
+    }
+
+    pub fn alignments(&self) -> &[ColumnAlignment] {
+        &self.alignments
+    }
+
+    pub fn header(&self) -> &[Option<InlineContent>] {
+        &self.header
+    }
+
+    pub fn rows(&self) -> &[Vec<Option<InlineContent>>] {
+        &self.rows
+    }
+}
+
+/// An image with its caption.
+pub struct Figure {
+    path: String,
+    caption: InlineContent,
+    /// The name `@fig:` references use, without the `fig:`.
+    label: Option<String>,
+    /// Whether the image spans all the columns, `{span}`.
+    span: bool,
+}
+
+impl Figure {
+    pub fn new(path: String, caption: InlineContent, label: Option<String>, span: bool) -> Self {
+        Figure { path, caption, label, span }
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn caption(&self) -> &InlineContent {
+        &self.caption
+    }
+
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    pub fn span(&self) -> bool {
+        self.span
+    }
+}
+
+/// Two to four images side by side, each with its own caption.
+pub struct FigureRowBlock {
+    figures: Vec<Figure>,
+}
+
+impl FigureRowBlock {
+    pub fn new(figures: Vec<Figure>) -> Self {
+        FigureRowBlock { figures }
+    }
+
+    pub fn figures(&self) -> &[Figure] {
+        &self.figures
+    }
+}
+
+/// The side of the content area a floating image keeps to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FloatSide {
+    Left,
+    Right,
+}
+
+/// An image the paragraphs after it flow around.
+pub struct FloatImageBlock {
+    side: FloatSide,
+    figure: Figure,
+}
+
+impl FloatImageBlock {
+    pub fn new(side: FloatSide, figure: Figure) -> Self {
+        FloatImageBlock { side, figure }
+    }
+
+    pub fn side(&self) -> FloatSide {
+        self.side
+    }
+
+    pub fn figure(&self) -> &Figure {
+        &self.figure
+    }
+}
+
 /// The layout information for the current image and its caption.
 #[derive(Debug, Clone, Copy)]
 pub struct ImageBlockLayoutInfo {
@@ -49,10 +256,16 @@ pub struct ImageBlockMeasuredInfo {
     spacing_after: f64,
     /// The x-coordinate of the image.
     x_coordinate: f64,
+    /// The vertical gap between the image and its caption.
+    caption_gap: f64,
+    /// Whether the caption is above the image rather than below it.
+    caption_above: bool,
 }
 
 #[allow(dead_code)]
 impl ImageBlockMeasuredInfo {
+    /// The caption is directly below the image, see 
+    /// [`ImageBlockMeasuredInfo::with_caption_placement()`] otherwise.
     pub fn new(layout_info: ImageBlockLayoutInfo,
         caption_height: f64,
         image_height: f64,
@@ -60,7 +273,22 @@ impl ImageBlockMeasuredInfo {
         x_coordinate: f64,        
     ) -> Self {
         Self { layout_info, caption_height, image_height, 
-            spacing_after, x_coordinate }
+            spacing_after, x_coordinate, caption_gap: 0.0, caption_above: false }
+    }
+
+    /// The same block moved right by `dx`: measured in the first column, 
+    /// placed in another one.
+    pub fn shifted(mut self, dx: f64) -> Self {
+        self.x_coordinate += dx;
+        self
+    }
+
+    /// Put the caption above the image when `caption_above`, `caption_gap` 
+    /// away from it.
+    pub fn with_caption_placement(mut self, caption_above: bool, caption_gap: f64) -> Self {
+        self.caption_above = caption_above;
+        self.caption_gap = caption_gap;
+        self
     }
 
     pub fn layout_info(&self) -> &ImageBlockLayoutInfo {
@@ -95,8 +323,62 @@ impl ImageBlockMeasuredInfo {
         self.x_coordinate
     }
 
+    pub fn caption_gap(&self) -> f64 {
+        self.caption_gap
+    }
+
+    pub fn caption_above(&self) -> bool {
+        self.caption_above
+    }
+
+    /// Where the image starts: below the caption and the gap when the 
+    /// caption is above it, at the top of the block otherwise.
+    pub fn image_top_y(&self) -> f64 {
+        if self.caption_above {
+            self.block_top_y() + self.caption_height + self.caption_gap
+        } else { self.block_top_y() }
+    }
+
+    /// Where the caption starts.
+    pub fn caption_top_y(&self) -> f64 {
+        if self.caption_above {
+            self.block_top_y()
+        } else { self.block_top_y() + self.image_height + self.caption_gap }
+    }
+
     pub fn block_height(&self) -> f64 {
-        self.image_height + self.caption_height + self.spacing_after
+        self.image_height + self.caption_gap + self.caption_height + self.spacing_after
+    }
+}
+
+/// Where a fragment is: a column of a page. Blocks flow down a column, then 
+/// into the next column, then onto the next page.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Frame {
+    /// Counted from 1.
+    page: usize,
+    /// Counted from 0, left to right.
+    column: usize,
+    /// Where the column starts on the page: the top margin, or below the 
+    /// blocks above it when the columns start part-way down the page.
+    top: f64,
+}
+
+impl Frame {
+    pub fn new(page: usize, column: usize, top: f64) -> Self {
+        Frame { page, column, top }
+    }
+
+    pub fn page(&self) -> usize {
+        self.page
+    }
+
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    pub fn top(&self) -> f64 {
+        self.top
     }
 }
 
@@ -109,8 +391,8 @@ pub enum PositionedBlock {
     Header {
         /// Index to the original semantic [`bh_cmark::ast::AstBlock`].
         block_index: usize,
-        /// Which page this fragment belongs to.
-        page: usize,
+        /// Which page and column this fragment belongs to.
+        frame: Frame,
         /// Where on the page it starts.
         y_offset: f64,
         /// First line of this fragment (layout header can break 
@@ -122,8 +404,8 @@ pub enum PositionedBlock {
     Paragraph {
         /// Index to the original semantic [`bh_cmark::ast::AstBlock`].
         block_index: usize,
-        /// Which page this fragment belongs to.
-        page: usize,
+        /// Which page and column this fragment belongs to.
+        frame: Frame,
         /// Where on the page it starts.
         y_offset: f64,
         /// First line of this fragment.
@@ -134,31 +416,114 @@ pub enum PositionedBlock {
     Image {
         /// Index to the original semantic [`bh_cmark::ast::AstBlock`].
         block_index: usize,
-        /// Which page this fragment belongs to.
-        page: usize,
+        /// Which page and column this fragment belongs to.
+        frame: Frame,
         /// Image block pagination and rendering information.
         measured_info: ImageBlockMeasuredInfo,
     },
     Thematic {
         /// Index to the original semantic [`bh_cmark::ast::AstBlock`].
         block_index: usize,
-        /// Which page this fragment belongs to.
-        page: usize,
+        /// Which page and column this fragment belongs to.
+        frame: Frame,
         /// Where on the page it starts.
         y_offset: f64,
-    }
+    },
+    ListItem {
+        /// Index to the original semantic [`Block::List`].
+        block_index: usize,
+        /// Index to the item within the list.
+        item_index: usize,
+        /// Which page and column this fragment belongs to.
+        frame: Frame,
+        /// Where on the page it starts.
+        y_offset: f64,
+        /// First line of this fragment. The marker is drawn with line `0`.
+        line_start: usize,
+        /// Last line of this fragment (exclusive)
+        line_end: usize,
+    },
+    Code {
+        /// Index to the original semantic [`Block::Code`].
+        block_index: usize,
+        /// Which page and column this fragment belongs to.
+        frame: Frame,
+        /// Where on the page its first line starts, the shading starts 
+        /// above it by the padding.
+        y_offset: f64,
+        /// First line of this fragment.
+        line_start: usize,
+        /// Last line of this fragment (exclusive)
+        line_end: usize,
+    },
+    TableRow {
+        /// Index to the original semantic [`Block::Table`].
+        block_index: usize,
+        /// Index to the row: `0` is the header row, which is repeated at 
+        /// the top of each page the table continues onto.
+        row_index: usize,
+        /// Which page and column this fragment belongs to.
+        frame: Frame,
+        /// Where on the page the top border of the row is.
+        y_offset: f64,
+    },
+    QuoteRule {
+        /// Index to the [`Block::QuoteEnd`] closing the blockquote.
+        block_index: usize,
+        /// Which page and column this fragment belongs to.
+        frame: Frame,
+        /// Where on the page the rule starts.
+        y_start: f64,
+        /// Where on the page the rule ends.
+        y_end: f64,
+    },
+    TocTitle {
+        /// Index to the original semantic [`Block::Toc`].
+        block_index: usize,
+        /// Which page and column this fragment belongs to.
+        frame: Frame,
+        /// Where on the page it starts.
+        y_offset: f64,
+    },
+    TocEntry {
+        /// Index to the original semantic [`Block::Toc`].
+        block_index: usize,
+        /// Index to the entry within the table of contents.
+        entry_index: usize,
+        /// Which page and column this fragment belongs to.
+        frame: Frame,
+        /// Where on the page it starts. An entry never breaks across pages.
+        y_offset: f64,
+    },
+    FigureRow {
+        /// Index to the original semantic [`Block::FigureRow`].
+        block_index: usize,
+        /// Which page and column this fragment belongs to.
+        frame: Frame,
+        /// The whole row is measured as one image block: the scale factor 
+        /// applies to the row height, the image height is the row's.
+        measured_info: ImageBlockMeasuredInfo,
+    },
+    FloatImage {
+        /// Index to the original semantic [`Block::FloatImage`].
+        block_index: usize,
+        /// Which page and column this fragment belongs to.
+        frame: Frame,
+        /// The float pagination and positioning. It is never step scaled.
+        measured_info: ImageBlockMeasuredInfo,
+    },
 }
 
 impl PositionedBlock {
     pub fn header(block_index: usize, 
-        page: usize,
+        frame: Frame,
         y_offset: f64,
         line_start: usize,
         line_end: usize,
     ) -> Self {
         PositionedBlock::Header { 
             block_index,
-            page,
+            frame,
             y_offset,
             line_start, 
             line_end
@@ -166,14 +531,14 @@ impl PositionedBlock {
     }
 
     pub fn paragraph(block_index: usize, 
-        page: usize,
+        frame: Frame,
         y_offset: f64,
         line_start: usize,
         line_end: usize,
     ) -> Self {
         PositionedBlock::Paragraph { 
             block_index,
-            page,
+            frame,
             y_offset,
             line_start, 
             line_end
@@ -181,17 +546,94 @@ impl PositionedBlock {
     }
 
     pub fn image(block_index: usize, 
-        page: usize, 
+        frame: Frame, 
         measured_info: ImageBlockMeasuredInfo
     ) -> Self {
-        PositionedBlock::Image { block_index, page, measured_info }
+        PositionedBlock::Image { block_index, frame, measured_info }
     }
 
     pub fn thematic(block_index: usize,
-        page: usize,
+        frame: Frame,
+        y_offset: f64
+    ) -> Self {
+        PositionedBlock::Thematic { block_index, frame, y_offset }
+    }
+
+    pub fn list_item(block_index: usize,
+        item_index: usize,
+        frame: Frame,
+        y_offset: f64,
+        line_start: usize,
+        line_end: usize,
+    ) -> Self {
+        PositionedBlock::ListItem {
+            block_index,
+            item_index,
+            frame,
+            y_offset,
+            line_start,
+            line_end
+        }
+    }
+
+    pub fn code(block_index: usize,
+        frame: Frame,
+        y_offset: f64,
+        line_start: usize,
+        line_end: usize,
+    ) -> Self {
+        PositionedBlock::Code {
+            block_index,
+            frame,
+            y_offset,
+            line_start,
+            line_end
+        }
+    }
+
+    pub fn table_row(block_index: usize,
+        row_index: usize,
+        frame: Frame,
+        y_offset: f64
+    ) -> Self {
+        PositionedBlock::TableRow { block_index, row_index, frame, y_offset }
+    }
+
+    pub fn quote_rule(block_index: usize,
+        frame: Frame,
+        y_start: f64,
+        y_end: f64
+    ) -> Self {
+        PositionedBlock::QuoteRule { block_index, frame, y_start, y_end }
+    }
+
+    pub fn toc_title(block_index: usize,
+        frame: Frame,
+        y_offset: f64
+    ) -> Self {
+        PositionedBlock::TocTitle { block_index, frame, y_offset }
+    }
+
+    pub fn toc_entry(block_index: usize,
+        entry_index: usize,
+        frame: Frame,
         y_offset: f64
     ) -> Self {
-        PositionedBlock::Thematic { block_index, page, y_offset }
+        PositionedBlock::TocEntry { block_index, entry_index, frame, y_offset }
+    }
+
+    pub fn figure_row(block_index: usize,
+        frame: Frame,
+        measured_info: ImageBlockMeasuredInfo
+    ) -> Self {
+        PositionedBlock::FigureRow { block_index, frame, measured_info }
+    }
+
+    pub fn float_image(block_index: usize,
+        frame: Frame,
+        measured_info: ImageBlockMeasuredInfo
+    ) -> Self {
+        PositionedBlock::FloatImage { block_index, frame, measured_info }
     }
 
     /// Index to the original semantic block.
@@ -200,17 +642,38 @@ impl PositionedBlock {
             PositionedBlock::Header { block_index, .. } | 
             PositionedBlock::Paragraph { block_index, .. } | 
             PositionedBlock::Image { block_index, .. } | 
-            PositionedBlock::Thematic { block_index, .. } => *block_index,
+            PositionedBlock::Thematic { block_index, .. } | 
+            PositionedBlock::ListItem { block_index, .. } | 
+            PositionedBlock::Code { block_index, .. } | 
+            PositionedBlock::TableRow { block_index, .. } | 
+            PositionedBlock::QuoteRule { block_index, .. } | 
+            PositionedBlock::TocTitle { block_index, .. } | 
+            PositionedBlock::TocEntry { block_index, .. } | 
+            PositionedBlock::FigureRow { block_index, .. } | 
+            PositionedBlock::FloatImage { block_index, .. } => *block_index,
         }
     }
 
-    /// Which page this fragment belongs to.
-    pub fn page(&self) -> usize {
+    /// Which page and column this fragment belongs to.
+    pub fn frame(&self) -> Frame {
         match self {
-            PositionedBlock::Header { page, .. } | 
-            PositionedBlock::Paragraph { page, .. } | 
-            PositionedBlock::Image { page, .. } | 
-            PositionedBlock::Thematic { page, .. } => *page,
+            PositionedBlock::Header { frame, .. } | 
+            PositionedBlock::Paragraph { frame, .. } | 
+            PositionedBlock::Image { frame, .. } | 
+            PositionedBlock::Thematic { frame, .. } | 
+            PositionedBlock::ListItem { frame, .. } | 
+            PositionedBlock::Code { frame, .. } | 
+            PositionedBlock::TableRow { frame, .. } | 
+            PositionedBlock::QuoteRule { frame, .. } | 
+            PositionedBlock::TocTitle { frame, .. } | 
+            PositionedBlock::TocEntry { frame, .. } | 
+            PositionedBlock::FigureRow { frame, .. } | 
+            PositionedBlock::FloatImage { frame, .. } => *frame,
         }
     }
-}
\ No newline at end of file
+
+    /// Which page this fragment belongs to.
+    pub fn page(&self) -> usize {
+        self.frame().page()
+    }
+}
@@ -0,0 +1,281 @@
+/* 17/10/2026 */
+
+//!
+//! Layout figure rows onto a PDF: two to four images side by side, each with
+//! its own caption below it. See [`crate::block_parser`] for the syntax.
+//!
+//! The images are scaled to a shared height, so that together with the
+//! `[image_block] row_gap` between them they fill the content width. Each
+//! image heads a column as wide as itself, in which its caption is centred
+//! and wraps. When `[image_block] sizing` is "natural" or
+//! "natural_max_width", the row is no taller than the natural height of its
+//! shortest image: the images are then centred in their columns.
+//!
+//! A row is paginated as a single image block, by [`step_scale_image()`]:
+//! `reduction_factor`, `step_scale_factor` and `min_allowed_scale` apply to
+//! the row height above, as a factor of `1.0`. The columns, and so the
+//! captions, keep their widths as the images shrink.
+//!
+
+use cairo::{Context, ImageSurface};
+use pango::{Alignment, Layout, WrapMode};
+use pangocairo::functions::{create_layout, show_layout};
+
+use crate::config::Config;
+use crate::document::{FigureRowBlock, ImageBlockMeasuredInfo};
+use crate::image_layout::{render_placeholder, sizing_scale_factor, step_scale_image};
+use crate::image_loader::load_image_or_placeholder;
+use crate::link_layout::set_prefixed_content;
+use crate::page_geometry::PageGeometry;
+use crate::prepare_context::PrepareContext;
+use crate::text_layout::{layout_block_height, layout_width, set_layout_language};
+
+/// An image of a figure row, loaded, with its caption laid out.
+#[derive(Debug)]
+pub struct PreparedFigure {
+    /// The decoded PNG, or a blank surface carrying the original JPEG.
+    image_surface: ImageSurface,
+    /// The path and the error of an image which could not be loaded.
+    placeholder: Option<String>,
+    caption_layout: Layout,
+    /// The width of the column the image heads.
+    column_width: f64,
+}
+
+/// The cached images and caption layouts of a figure row, reused for both
+/// measurement and rendering.
+#[derive(Debug)]
+pub struct PreparedFigureRow {
+    figures: Vec<PreparedFigure>,
+    /// The height of the images, before `reduction_factor` and step scaling.
+    image_height: f64,
+    /// The tallest caption.
+    caption_height: f64,
+}
+
+/// The shared height of the images of a row, and the width of their
+/// columns.
+///
+/// # Arguments
+///
+/// * `image_sizes` — the `(width, height)` of each image, in pixels.
+///
+/// * `max_height` — the tallest the images may be, `f64::INFINITY` for no
+///   limit.
+///
+/// * `content_width` — the width the columns and the gaps between them
+///   fill.
+///
+/// * `gap` — the gap between two columns.
+///
+fn row_layout(image_sizes: &[(f64, f64)],
+    max_height: f64,
+    content_width: f64,
+    gap: f64
+) -> (f64, Vec<f64>) {
+    let gaps = gap * image_sizes.len().saturating_sub(1) as f64;
+    let aspect_ratios: Vec<f64> = image_sizes.iter()
+        .map(|(width, height)| width / height)
+        .collect();
+
+    let fit_height = (content_width - gaps).max(0.0) / aspect_ratios.iter().sum::<f64>();
+    let column_widths = aspect_ratios.iter()
+        .map(|aspect_ratio| aspect_ratio * fit_height)
+        .collect();
+
+    (fit_height.min(max_height), column_widths)
+}
+
+/// Load the images of a figure row and lay out their captions. Images which
+/// cannot be loaded are replaced by a placeholder, and the problems added to
+/// `warnings`.
+///
+/// # Arguments
+///
+/// * `row` — the semantic figure row.
+///
+/// * `block_index` — index to the row block.
+///
+/// * `config` — the caption font, `[image_block] row_gap`, `sizing` and
+///   `default_dpi`.
+///
+/// * `page_geometry` — the page geometry of the row.
+///
+/// * `prepare` — the figure numbers, for the labels and the figure 
+///   references in the captions, the Cairo PDF [`Context`], and the 
+///   directories image paths are resolved against.
+///
+/// * `warnings` — the problems found so far.
+///
+pub fn prepare_figure_row(row: &FigureRowBlock,
+    block_index: usize,
+    config: &Config,
+    page_geometry: &PageGeometry,
+    prepare: &PrepareContext,
+    warnings: &mut Vec<String>
+) -> PreparedFigureRow {
+    let image_block = config.image_block();
+    let figures = prepare.figures();
+
+    let images: Vec<(ImageSurface, Option<f64>, Option<String>)> = row.figures().iter()
+        .map(|figure| load_image_or_placeholder(figure.path(), prepare.image_dirs(), warnings))
+        .collect();
+
+    let image_sizes: Vec<(f64, f64)> = images.iter()
+        .map(|(image_surface, _, _)| (image_surface.width() as f64, image_surface.height() as f64))
+        .collect();
+
+    // No image is drawn larger than it would be on its own. Filling the 
+    // content width never limits a row, the natural sizes can.
+    let max_height = images.iter()
+        .map(|(image_surface, dpi, _)| image_surface.height() as f64 
+            * sizing_scale_factor(image_surface.width() as f64, 
+                dpi.unwrap_or(image_block.default_dpi()), 
+                page_geometry.content_width(), image_block.sizing()))
+        .fold(f64::INFINITY, f64::min);
+
+    let (image_height, column_widths) = row_layout(&image_sizes, max_height,
+        page_geometry.content_width(), image_block.row_gap());
+
+    let prepared_figures: Vec<PreparedFigure> = images.into_iter()
+        .zip(row.figures())
+        .zip(column_widths)
+        .enumerate()
+        .map(|(position, (((image_surface, _, placeholder), figure), column_width))| {
+            let caption_layout: Layout = create_layout(prepare.context());
+
+            caption_layout.set_width(layout_width(column_width));
+            caption_layout.set_wrap(WrapMode::WordChar);
+            caption_layout.set_alignment(Alignment::Center);
+            caption_layout.set_font_description(
+                Some(&config.fonts().caption().to_pango_description()));
+            set_layout_language(&caption_layout, config.metadata().language());
+            // As with single images, caption links are not tagged.
+            let prefix = figures.caption_prefix(block_index, position, figure.caption().text());
+            set_prefixed_content(&caption_layout, &prefix, figure.caption(), config.link(), 
+                figures);
+
+            PreparedFigure { image_surface, placeholder, caption_layout, column_width }
+        })
+        .collect();
+
+    let caption_height = prepared_figures.iter()
+        .map(|figure| layout_block_height(&figure.caption_layout))
+        .fold(0.0, f64::max);
+
+    PreparedFigureRow { figures: prepared_figures, image_height, caption_height }
+}
+
+/// Fit a figure row in the remaining page height, on this page or the next,
+/// as [`crate::image_layout::measure_image_block()`] does for a single
+/// image.
+///
+/// # Arguments
+///
+/// * `row` — the prepared figure row.
+///
+/// * `top_y` — the y‑coordinate at which to place the top of the row.
+///
+/// * `spacing_before` — the vertical gap above the row if it is placed on
+///   the current page.
+///
+/// * `config` — `[image_block] reduction_factor`, `step_scale_factor` and
+///   `min_allowed_scale`.
+///
+/// * `page_geometry` — the page geometry of the row.
+///
+/// # Returns
+///
+/// * [`ImageBlockMeasuredInfo`] — the row pagination and positioning, with
+///   the scale factor applying to the row height.
+///
+/// * [`std::error::Error`] — if the row cannot be fitted even after
+///   progressive reduction and a page break.
+///
+pub fn measure_figure_row(row: &PreparedFigureRow,
+    top_y: f64,
+    spacing_before: f64,
+    config: &Config,
+    page_geometry: &PageGeometry,
+) -> Result<ImageBlockMeasuredInfo, Box<dyn std::error::Error>> {
+    let image_block = config.image_block();
+
+    let layout_info = step_scale_image(top_y, &|_| row.caption_height, row.image_height,
+        image_block.reduction_factor(), image_block, spacing_before, page_geometry)?;
+
+    Ok(ImageBlockMeasuredInfo::new(layout_info,
+        row.caption_height,
+        row.image_height * layout_info.scale_factor(),
+        config.block_spacing().image().after(),
+        page_geometry.margins().left))
+}
+
+/// Render a measured figure row: each image centred at the top of its
+/// column, its caption below it.
+pub fn render_figure_row(row: &PreparedFigureRow,
+    measured_info: &ImageBlockMeasuredInfo,
+    context: &Context,
+    config: &Config,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let image_height = measured_info.image_height();
+    let top_y = measured_info.block_top_y();
+    let mut x = measured_info.x_coordinate();
+
+    for figure in &row.figures {
+        let scale = image_height / figure.image_surface.height() as f64;
+        let width = figure.image_surface.width() as f64 * scale;
+        let image_x = x + (figure.column_width - width) / 2.0;
+
+        match &figure.placeholder {
+            Some(message) => render_placeholder(message, image_x, top_y, width, image_height,
+                context, config),
+            None => {
+                context.save().expect("Failed to save Cairo context");
+                context.translate(image_x, top_y);
+                context.scale(scale, scale);
+                context.set_source_surface(&figure.image_surface, 0.0, 0.0)?;
+                context.paint()?;
+                context.restore().expect("Failed to restore Cairo context");
+            },
+        }
+
+        context.move_to(x, top_y + image_height);
+        show_layout(context, &figure.caption_layout);
+
+        x += figure.column_width + config.image_block().row_gap();
+    }
+
+    Ok(())
+}
+
+// To run test for this module only:
+//
+//     * cargo test figure_layout::tests
+//
+//     * cargo test figure_layout::tests::test_row_layout -- --exact [--nocapture]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_row_layout() {
+        // A 4:3 and a 3:4 image, 12 apart, in 480: the columns share 468 in
+        // proportion to the aspect ratios, 4/3 + 3/4 = 25/12.
+        let sizes = [(800.0, 600.0), (300.0, 400.0)];
+
+        let (height, columns) = row_layout(&sizes, f64::INFINITY, 480.0, 12.0);
+        assert!((height - 224.64).abs() < 1e-9, "{height}");
+        assert!((columns[0] - 299.52).abs() < 1e-9, "{}", columns[0]);
+        assert!((columns[1] - 168.48).abs() < 1e-9, "{}", columns[1]);
+
+        // Capped: the columns stay as they are.
+        let (height, capped_columns) = row_layout(&sizes, 150.0, 480.0, 12.0);
+        assert_eq!(height, 150.0);
+        assert_eq!(capped_columns, columns);
+
+        // A single image fills the width.
+        let (height, columns) = row_layout(&[(964.0, 1600.0)], f64::INFINITY, 482.0, 12.0);
+        assert_eq!(columns, vec![482.0]);
+        assert_eq!(height, 800.0);
+    }
+}
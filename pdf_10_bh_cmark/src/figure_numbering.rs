@@ -0,0 +1,283 @@
+/* 17/10/2026 */
+
+//!
+//! Figure numbers and `@fig:name` cross-references.
+//!
+//! Every image is a figure: a plain image, each image of a figure row and a
+//! floating image. Figures are numbered from 1 in document order. With
+//! `[figure] numbered`, each caption starts with the figure label,
+//! `[figure] label` with `{number}` replaced, followed by `[figure]
+//! separator`.
+//!
+//! An image line ending in `{#fig:name}` names its image, see
+//! [`crate::block_parser`]. A name is made of letters, digits, `-` and `_`.
+//! A `@fig:name` reference in a paragraph, a heading, a list item, a table
+//! cell or a caption is replaced by `[figure] reference`, with `{figure}`
+//! the label of the image and `{page}` its page number.
+//!
+//! Page numbers are only known once the document has been paginated, and
+//! the text replacing a reference can move the blocks after it. References
+//! are first resolved with `?` for their pages, then the document is
+//! paginated again with the pages filled in until they settle, as for the
+//! table of contents.
+//!
+//! A reference to a name no image has, and a name given to more than one
+//! image, are reported as warnings. Such a reference is left as it is.
+//!
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use bh_cmark::ast::AstBlock;
+
+use crate::config::FigureConfig;
+use crate::document::{Block, Figure, PositionedBlock};
+
+/// A numbered image.
+#[derive(Debug, Clone, PartialEq)]
+struct NumberedFigure {
+    block_index: usize,
+    /// The position of the image within a figure row, `0` otherwise.
+    position: usize,
+    number: usize,
+    label: Option<String>,
+}
+
+/// The figures of a document, their pages once paginated, and the blocks
+/// referring to them.
+pub struct Figures {
+    figures: Vec<NumberedFigure>,
+    /// The page of each figure block, by block index.
+    pages: HashMap<usize, usize>,
+    /// The indices of the blocks holding references, in order.
+    referring_blocks: Vec<usize>,
+    /// The problems found with the labels and references.
+    warnings: Vec<String>,
+    config: FigureConfig,
+}
+
+/// Whether `name` can name a figure: letters, digits, `-` and `_`.
+pub fn is_label(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(is_label_char)
+}
+
+fn is_label_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '-' || c == '_'
+}
+
+/// Find the `@fig:name` references in a text, in order: the byte range of
+/// each, and its name. A `@` straight after a letter or a digit, as in an
+/// email address, does not start a reference.
+pub fn find_references(text: &str) -> Vec<(Range<usize>, &str)> {
+    text.match_indices("@fig:")
+        .filter(|(start, _)| !text[..*start].chars().next_back().is_some_and(char::is_alphanumeric))
+        .filter_map(|(start, marker)| {
+            let name_start = start + marker.len();
+            let name_len = text[name_start..].find(|c| !is_label_char(c))
+                .unwrap_or(text.len() - name_start);
+
+            (name_len > 0).then(|| (start..name_start + name_len, &text[name_start..name_start + name_len]))
+        })
+        .collect()
+}
+
+/// The texts of a block which may hold references.
+fn block_texts(block: &Block) -> Vec<&str> {
+    match block {
+        Block::Ast(AstBlock::Header { content, .. }) |
+        Block::Ast(AstBlock::Paragraph { content }) => vec![content.text()],
+        Block::Ast(AstBlock::Image { alt, .. }) => vec![alt.text()],
+        Block::List(list) => list.items().iter()
+            .filter_map(|item| item.content())
+            .map(|content| content.text())
+            .collect(),
+        Block::Table(table) => table.header().iter()
+            .chain(table.rows().iter().flatten())
+            .flatten()
+            .map(|content| content.text())
+            .collect(),
+        Block::Image(figure) => vec![figure.caption().text()],
+        Block::FigureRow(row) => row.figures().iter()
+            .map(|figure| figure.caption().text())
+            .collect(),
+        Block::FloatImage(float) => vec![float.figure().caption().text()],
+        _ => Vec::new(),
+    }
+}
+
+impl Figures {
+    /// Number the images of a document, and find its references.
+    pub fn new(blocks: &[Block], config: &FigureConfig) -> Self {
+        let mut figures: Vec<NumberedFigure> = Vec::new();
+
+        for (block_index, block) in blocks.iter().enumerate() {
+            let labels: Vec<Option<&str>> = match block {
+                Block::Ast(AstBlock::Image { .. }) => vec![None],
+                Block::Image(figure) => vec![figure.label()],
+                Block::FigureRow(row) => row.figures().iter().map(Figure::label).collect(),
+                Block::FloatImage(float) => vec![float.figure().label()],
+                _ => continue,
+            };
+
+            for (position, label) in labels.into_iter().enumerate() {
+                figures.push(NumberedFigure {
+                    block_index,
+                    position,
+                    number: figures.len() + 1,
+                    label: label.map(str::to_string),
+                });
+            }
+        }
+
+        let mut warnings: Vec<String> = Vec::new();
+
+        for (index, figure) in figures.iter().enumerate() {
+            if let Some(label) = &figure.label
+                && figures[..index].iter().any(|other| other.label.as_ref() == Some(label)) {
+                warnings.push(format!("Figure name #fig:{label} is used more than once"));
+            }
+        }
+
+        let mut referring_blocks: Vec<usize> = Vec::new();
+
+        for (block_index, block) in blocks.iter().enumerate() {
+            let references: Vec<(Range<usize>, &str)> = block_texts(block).into_iter()
+                .flat_map(find_references)
+                .collect();
+
+            if references.is_empty() { continue; }
+            referring_blocks.push(block_index);
+
+            for (_, name) in references {
+                let warning = format!("Unknown figure reference @fig:{name}");
+                if !figures.iter().any(|figure| figure.label.as_deref() == Some(name))
+                    && !warnings.contains(&warning) {
+                    warnings.push(warning);
+                }
+            }
+        }
+
+        Figures {
+            figures,
+            pages: HashMap::new(),
+            referring_blocks,
+            warnings,
+            config: config.clone(),
+        }
+    }
+
+    fn figure_label(&self, figure: &NumberedFigure) -> String {
+        self.config.label().replace("{number}", &figure.number.to_string())
+    }
+
+    /// The text put before the caption of an image: the figure label and
+    /// the separator, or only the label when the caption is blank. Empty
+    /// when figures are not numbered.
+    ///
+    /// # Arguments
+    ///
+    /// * `block_index` — the image block.
+    ///
+    /// * `position` — the position of the image within a figure row, `0`
+    ///   otherwise.
+    ///
+    /// * `caption` — the caption text.
+    ///
+    pub fn caption_prefix(&self, block_index: usize, position: usize, caption: &str) -> String {
+        let figure = self.figures.iter()
+            .find(|figure| figure.block_index == block_index && figure.position == position);
+
+        match figure {
+            Some(figure) if self.config.numbered() => {
+                let label = self.figure_label(figure);
+                if caption.trim().is_empty() { label } else { label + self.config.separator() }
+            },
+            _ => String::new(),
+        }
+    }
+
+    /// The text replacing a `@fig:name` reference, `None` when no image has
+    /// the name.
+    pub fn resolve(&self, name: &str) -> Option<String> {
+        let figure = self.figures.iter()
+            .find(|figure| figure.label.as_deref() == Some(name))?;
+
+        let page = self.pages.get(&figure.block_index)
+            .map_or("?".to_string(), |page| page.to_string());
+
+        Some(self.config.reference()
+            .replace("{figure}", &self.figure_label(figure))
+            .replace("{page}", &page))
+    }
+
+    /// The references within a text and what each resolves to, skipping
+    /// those which do not resolve.
+    pub fn resolve_references(&self, text: &str) -> Vec<(Range<usize>, String)> {
+        find_references(text).into_iter()
+            .filter_map(|(range, name)| Some((range, self.resolve(name)?)))
+            .collect()
+    }
+
+    /// The indices of the blocks holding references, in order.
+    pub fn referring_blocks(&self) -> &[usize] {
+        &self.referring_blocks
+    }
+
+    /// The problems found with the labels and references.
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    /// Record the page of each figure: that of the first fragment of its
+    /// block.
+    ///
+    /// # Returns
+    ///
+    /// Whether any page differs from those recorded before.
+    pub fn place(&mut self, pos_blocks: &[PositionedBlock]) -> bool {
+        let mut pages: HashMap<usize, usize> = HashMap::new();
+
+        for figure in &self.figures {
+            let page = pos_blocks.iter()
+                .filter(|pos_block| pos_block.block_index() == figure.block_index)
+                .map(|pos_block| pos_block.page())
+                .min();
+
+            if let Some(page) = page {
+                pages.insert(figure.block_index, page);
+            }
+        }
+
+        let changed = pages != self.pages;
+        self.pages = pages;
+        changed
+    }
+}
+
+// To run test for this module only:
+//
+//     * cargo test figure_numbering::tests
+//
+//     * cargo test figure_numbering::tests::test_find_references -- --exact [--nocapture]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_references() {
+        let text = "Xem @fig:ban-do, @fig:hai_2. Không: a@fig:b, @fig: hay @tab:c.";
+
+        assert_eq!(find_references(text), vec![
+            (4..15, "ban-do"),
+            (17..27, "hai_2"),
+        ]);
+
+        assert_eq!(find_references("@fig:một"), vec![(0..10, "một")]);
+        assert_eq!(find_references("Không có."), vec![]);
+
+        assert!(is_label("ban-do_2"));
+        assert!(!is_label(""));
+        assert!(!is_label("ban do"));
+        assert!(!is_label("fig:a"));
+    }
+}
@@ -0,0 +1,423 @@
+/* 17/10/2026 */
+
+//!
+//! Floating images: an image kept to the left or the right of the content
+//! area, with the paragraphs after it flowing around it. See
+//! [`crate::block_parser`] for the syntax.
+//!
+//! A float is `[image_block] float_width` of the content width, its caption
+//! below it, and `[image_block] float_gap` away from the text beside it. It
+//! is never step scaled: when it does not fit in the rest of the page, it
+//! moves to the next one, together with the text after it. A float taller
+//! than a page is scaled down to fit one.
+//!
+//! Pango lays out every line of a [`pango::Layout`] to the same width, so a
+//! paragraph beside a float is split into two layouts when it is prepared:
+//!
+//! * The lines beside the float, from a copy of the paragraph layout
+//!   narrowed by the float width and gap: a [`FloatWrap`].
+//!
+//! * The lines below the float, from a full width layout of the rest of
+//!   the text, which replaces the paragraph layout.
+//!
+//! How many lines go beside the float depends only on the heights of the
+//! float and of the lines, since the first paragraph starts level with the
+//! float. Paragraphs which fit beside the float in full leave the rest of
+//! its height to the next paragraph. Any other block clears the float: it
+//! starts below it.
+//!
+//! # Limitations
+//!
+//! 1. Only paragraphs flow around a float: a heading, a list or another
+//!    image after a float starts below it.
+//!
+//! 2. The lines beside a float are not moved to the next page with it when
+//!    widow and orphan control breaks a paragraph within them.
+//!
+
+use std::ops::Range;
+
+use cairo::{Context, ImageSurface};
+use pango::{AttrList, Alignment, Layout, WrapMode};
+use pangocairo::functions::{create_layout, show_layout};
+
+use crate::config::{Config, ImageSizing};
+use crate::document::{FloatImageBlock, FloatSide, ImageBlockLayoutInfo, ImageBlockMeasuredInfo};
+use crate::image_layout::{render_placeholder, sizing_scale_factor};
+use crate::image_loader::load_image_or_placeholder;
+use crate::link_layout::{LayoutLink, links_from, set_prefixed_content, tag_line_links};
+use crate::page_geometry::PageGeometry;
+use crate::prepare_context::PrepareContext;
+use crate::text_layout::{
+    layout_block_height,
+    layout_width,
+    measure_line_height,
+    set_layout_language,
+    show_lines,
+};
+
+/// A floating image, loaded and scaled, with its caption laid out.
+#[derive(Debug)]
+pub struct PreparedFloat {
+    side: FloatSide,
+    /// The decoded PNG, or a blank surface carrying the original JPEG.
+    image_surface: ImageSurface,
+    /// The path and the error of an image which could not be loaded.
+    placeholder: Option<String>,
+    caption_layout: Layout,
+    scale_factor: f64,
+    /// The width of the float, which the image is centred in.
+    width: f64,
+    /// The height of the scaled image.
+    image_height: f64,
+    caption_height: f64,
+}
+
+impl PreparedFloat {
+    /// The height of the image and its caption.
+    pub fn height(&self) -> f64 {
+        self.image_height + self.caption_height
+    }
+}
+
+/// The lines of a paragraph beside a floating image.
+#[derive(Debug)]
+pub struct FloatWrap {
+    /// The whole paragraph, laid out to the width beside the float. Only
+    /// its first `lines` lines are drawn.
+    layout: Layout,
+    /// The links within the `layout` text.
+    links: Vec<LayoutLink>,
+    lines: usize,
+    /// How far right of the content area's left edge the lines start.
+    x_offset: f64,
+}
+
+impl FloatWrap {
+    pub fn lines(&self) -> usize {
+        self.lines
+    }
+}
+
+/// The scale factor of a floating image.
+///
+/// # Arguments
+///
+/// * `image_size` — the `(width, height)` of the image, in pixels.
+///
+/// * `dpi` — the resolution of the image, in pixels per inch.
+///
+/// * `float_width` — the width of the float.
+///
+/// * `max_height` — the tallest the image may be: a page less the caption.
+///
+/// * `sizing` — `[image_block] sizing`: with "natural" or
+///   "natural_max_width", an image is no larger than its natural size. It
+///   is never wider than the float.
+///
+fn float_scale(image_size: (f64, f64),
+    dpi: f64,
+    float_width: f64,
+    max_height: f64,
+    sizing: ImageSizing
+) -> f64 {
+    let (image_width, image_height) = image_size;
+
+    sizing_scale_factor(image_width, dpi, float_width, sizing)
+        .min(float_width / image_width)
+        .min(max_height.max(0.0) / image_height)
+}
+
+/// Load and scale a floating image, and lay out its caption. An image which
+/// cannot be loaded is replaced by a placeholder, and the problem added to
+/// `warnings`.
+///
+/// # Arguments
+///
+/// * `float` — the semantic floating image.
+///
+/// * `block_index` — index to the float block.
+///
+/// * `config` — the caption font, `[image_block] float_width`, `sizing`
+///   and `default_dpi`.
+///
+/// * `page_geometry` — the page geometry of the float.
+///
+/// * `prepare` — the figure numbers, for the figure references in the 
+///   caption, the Cairo PDF [`Context`], and the directories image paths are 
+///   resolved against.
+///
+/// * `warnings` — the problems found so far.
+///
+pub fn prepare_float(float: &FloatImageBlock,
+    block_index: usize,
+    config: &Config,
+    page_geometry: &PageGeometry,
+    prepare: &PrepareContext,
+    warnings: &mut Vec<String>
+) -> PreparedFloat {
+    let image_block = config.image_block();
+    let figure = float.figure();
+    let figures = prepare.figures();
+
+    let (image_surface, dpi, placeholder) =
+        load_image_or_placeholder(figure.path(), prepare.image_dirs(), warnings);
+
+    let width = page_geometry.content_width() * image_block.float_width();
+
+    let caption_layout: Layout = create_layout(prepare.context());
+    caption_layout.set_width(layout_width(width));
+    caption_layout.set_wrap(WrapMode::WordChar);
+    caption_layout.set_alignment(Alignment::Center);
+    caption_layout.set_font_description(Some(&config.fonts().caption().to_pango_description()));
+    set_layout_language(&caption_layout, config.metadata().language());
+    // As with other images, caption links are not tagged.
+    let prefix = figures.caption_prefix(block_index, 0, figure.caption().text());
+    set_prefixed_content(&caption_layout, &prefix, figure.caption(), config.link(), figures);
+
+    let caption_height = layout_block_height(&caption_layout);
+    let page_height = page_geometry.content_height();
+
+    let image_size = (image_surface.width() as f64, image_surface.height() as f64);
+    let scale_factor = float_scale(image_size, dpi.unwrap_or(image_block.default_dpi()),
+        width, page_height - caption_height, image_block.sizing());
+
+    PreparedFloat {
+        side: float.side(),
+        image_surface,
+        placeholder,
+        caption_layout,
+        scale_factor,
+        width,
+        image_height: image_size.1 * scale_factor,
+        caption_height,
+    }
+}
+
+/// Where the lines beside a float start, as an offset from the left of the
+/// content area, and how wide they are.
+pub fn wrap_geometry(float: &PreparedFloat,
+    config: &Config,
+    page_geometry: &PageGeometry
+) -> (f64, f64) {
+    let gap = config.image_block().float_gap();
+    let width = page_geometry.content_width() - float.width - gap;
+
+    match float.side {
+        FloatSide::Left => (float.width + gap, width),
+        FloatSide::Right => (0.0, width),
+    }
+}
+
+/// A copy of a [`pango::Layout`] holding its text from byte `at` on, with
+/// the attributes moved back by `at`.
+fn layout_tail(layout: &Layout, at: usize) -> Layout {
+    let tail = layout.copy();
+    let attrs = AttrList::new();
+
+    if let Some(attr_list) = layout.attributes().and_then(|attr_list| attr_list.copy()) {
+        // Collects a copy of every attribute, removing none.
+        let _ = attr_list.filter(|attr| {
+            if attr.end_index() as usize > at {
+                let mut attr = attr.clone();
+                attr.set_start_index(attr.start_index().saturating_sub(at as u32));
+                // The maximum index stands for the end of the text.
+                if attr.end_index() != u32::MAX {
+                    attr.set_end_index(attr.end_index() - at as u32);
+                }
+                attrs.insert(attr);
+            }
+            false
+        });
+    }
+
+    tail.set_text(&layout.text()[at..]);
+    tail.set_attributes(Some(&attrs));
+
+    tail
+}
+
+/// Split a paragraph around a float: the lines whose tops are within
+/// `available_height` go beside it, see the module documentation.
+///
+/// # Arguments
+///
+/// * `layout`, `line_heights`, `links` — the paragraph. When some lines are
+///   left below the float, they are replaced by the full width layout of
+///   the rest of the text, the line heights of the lines beside the float
+///   followed by the rest's, and the links within the rest.
+///
+/// * `available_height` — the height of the float left beside the
+///   paragraph.
+///
+/// * `x_offset`, `width` — from [`wrap_geometry()`].
+///
+/// # Returns
+///
+/// The lines beside the float, `None` when there is no height left.
+///
+pub fn wrap_paragraph(layout: &mut Layout,
+    line_heights: &mut Vec<f64>,
+    links: &mut Vec<LayoutLink>,
+    available_height: f64,
+    x_offset: f64,
+    width: f64
+) -> Option<FloatWrap> {
+    let narrow = layout.copy();
+    narrow.set_width(layout_width(width));
+
+    let narrow_heights: Vec<f64> = (0..narrow.line_count())
+        .map(|i| measure_line_height(i, &narrow))
+        .collect();
+
+    let mut top = 0.0;
+    let lines = narrow_heights.iter()
+        .take_while(|line_height| {
+            let beside = top < available_height;
+            top += *line_height;
+            beside
+        })
+        .count();
+
+    if lines == 0 { return None; }
+
+    let wrap = FloatWrap { layout: narrow, links: links.clone(), lines, x_offset };
+
+    if lines == narrow_heights.len() {
+        *line_heights = narrow_heights;
+        return Some(wrap);
+    }
+
+    let at = wrap.layout.line(lines as i32)
+        .map_or(0, |line| line.start_index() as usize);
+    let tail = layout_tail(layout, at);
+
+    *line_heights = narrow_heights[..lines].iter().copied()
+        .chain((0..tail.line_count()).map(|i| measure_line_height(i, &tail)))
+        .collect();
+    *links = links_from(links, at);
+    *layout = tail;
+
+    Some(wrap)
+}
+
+/// Draw lines `lines` of a paragraph split by [`wrap_paragraph()`], and tag
+/// their links: those beside the float from the [`FloatWrap`], the rest
+/// from the paragraph layout. `position` is the `(x, y_offset)` of the 
+/// first of them.
+pub fn show_wrapped_lines(context: &Context,
+    wrap: &FloatWrap,
+    layout: &Layout,
+    line_heights: &[f64],
+    links: &[LayoutLink],
+    position: (f64, f64),
+    lines: Range<usize>,
+) {
+    let (x, y_offset) = position;
+    let beside = lines.start..lines.end.min(wrap.lines).max(lines.start);
+
+    show_lines(context, &wrap.layout, line_heights, x + wrap.x_offset, y_offset,
+        beside.start, beside.end);
+    tag_line_links(context, &wrap.layout, &wrap.links, line_heights, x + wrap.x_offset,
+        y_offset, beside.clone());
+
+    // The rest is indexed from the first line below the float.
+    let y = y_offset + line_heights[beside.clone()].iter().sum::<f64>();
+    let below = lines.start.max(wrap.lines) - wrap.lines..lines.end.max(wrap.lines) - wrap.lines;
+    let below_heights = &line_heights[wrap.lines.min(line_heights.len())..];
+
+    show_lines(context, layout, below_heights, x, y, below.start, below.end);
+    tag_line_links(context, layout, links, below_heights, x, y, below);
+}
+
+/// Place a floating image at `top_y` on the current page, or at the top of
+/// the next one when it does not fit.
+///
+/// # Arguments
+///
+/// * `float` — the prepared float.
+///
+/// * `top_y` — the y‑coordinate at which to place the top of the float.
+///
+/// * `spacing_before` — the vertical gap above the float if it is placed on
+///   the current page.
+///
+/// * `config` — `[block_spacing.image] after`.
+///
+/// * `page_geometry` — the page geometry of the float.
+///
+pub fn measure_float(float: &PreparedFloat,
+    top_y: f64,
+    spacing_before: f64,
+    config: &Config,
+    page_geometry: &PageGeometry,
+) -> ImageBlockMeasuredInfo {
+    let new_page = top_y + spacing_before + float.height() > page_geometry.content_bottom();
+    let y = if new_page { page_geometry.margins().top } else { top_y + spacing_before };
+
+    let x = match float.side {
+        FloatSide::Left => page_geometry.margins().left,
+        FloatSide::Right => page_geometry.margins().left + page_geometry.content_width()
+            - float.width,
+    };
+
+    ImageBlockMeasuredInfo::new(ImageBlockLayoutInfo::new(float.scale_factor, new_page, y),
+        float.caption_height,
+        float.image_height,
+        config.block_spacing().image().after(),
+        x)
+}
+
+/// Render a measured floating image: the image centred in the float, its
+/// caption below it.
+pub fn render_float(float: &PreparedFloat,
+    measured_info: &ImageBlockMeasuredInfo,
+    context: &Context,
+    config: &Config,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let x = measured_info.x_coordinate();
+    let top_y = measured_info.block_top_y();
+    let scale = measured_info.scale_factor();
+    let width = float.image_surface.width() as f64 * scale;
+    let image_x = x + (float.width - width) / 2.0;
+
+    match &float.placeholder {
+        Some(message) => render_placeholder(message, image_x, top_y, width,
+            measured_info.image_height(), context, config),
+        None => {
+            context.save().expect("Failed to save Cairo context");
+            context.translate(image_x, top_y);
+            context.scale(scale, scale);
+            context.set_source_surface(&float.image_surface, 0.0, 0.0)?;
+            context.paint()?;
+            context.restore().expect("Failed to restore Cairo context");
+        },
+    }
+
+    context.move_to(x, top_y + measured_info.image_height());
+    show_layout(context, &float.caption_layout);
+
+    Ok(())
+}
+
+// To run test for this module only:
+//
+//     * cargo test float_layout::tests
+//
+//     * cargo test float_layout::tests::test_float_scale -- --exact [--nocapture]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_float_scale() {
+        // 800px into 200: a quarter.
+        assert_eq!(float_scale((800.0, 600.0), 96.0, 200.0, 700.0, ImageSizing::FitWidth), 0.25);
+        // Natural, 96 dpi: three quarters, but no wider than the float.
+        assert_eq!(float_scale((800.0, 600.0), 96.0, 200.0, 700.0, ImageSizing::Natural), 0.25);
+        assert_eq!(float_scale((160.0, 120.0), 96.0, 200.0, 700.0, 
+            ImageSizing::NaturalMaxWidth), 0.75);
+        // Fit width, but the image would be taller than the page.
+        assert_eq!(float_scale((100.0, 1000.0), 96.0, 200.0, 500.0, ImageSizing::FitWidth), 0.5);
+    }
+}
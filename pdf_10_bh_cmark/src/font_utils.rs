@@ -2,10 +2,10 @@
 
 //! A bridge between font configuration and Pango.
 
-use pango::{FontDescription, Attribute, AttrInt, Weight, Style};
+use pango::{FontDescription, Attribute, AttrColor, AttrInt, Weight, Style, Underline};
 
-use crate::config::FontSpec;
-use bh_cmark::ast::{Span, SpanStyle};
+use crate::config::{FontSpec, LinkConfig};
+use bh_cmark::ast::SpanStyle;
 
 impl FontSpec {
     pub fn to_pango_description(&self) -> FontDescription {
@@ -35,24 +35,51 @@ impl FontSpec {
     }
 }
 
-pub fn create_font_attrs(span: &Span) -> Vec<Attribute> {
+/// The Pango attributes for a styled span of text, `start` and `end` are
+/// byte indices.
+pub fn create_span_attrs(style: &SpanStyle, start: usize, end: usize) -> Vec<Attribute> {
     let mut attrs: Vec<Attribute> = Vec::new();
 
-    match *span.style() {
+    match *style {
         SpanStyle::Normal => {}
         SpanStyle::Bold => {
             let mut bold = AttrInt::new_weight(Weight::Bold); 
-            bold.set_start_index(span.start() as u32); 
-            bold.set_end_index(span.end() as u32); 
+            bold.set_start_index(start as u32); 
+            bold.set_end_index(end as u32); 
             attrs.push(bold.into());
         }
         SpanStyle::Italic => {
             let mut italic = AttrInt::new_style(Style::Italic);
-            italic.set_start_index(span.start() as u32);
-            italic.set_end_index(span.end() as u32);
+            italic.set_start_index(start as u32);
+            italic.set_end_index(end as u32);
             attrs.push(italic.into());
         }        
     }
 
     attrs
-}
\ No newline at end of file
+}
+
+/// The Pango attributes for link text: the `[link]` colour, and an
+/// underline when configured.
+pub fn create_link_attrs(start: usize, end: usize, link_config: &LinkConfig) -> Vec<Attribute> {
+    let mut attrs: Vec<Attribute> = Vec::new();
+
+    // Pango colours are 16-bit.
+    let channel = |value: f64| (value.clamp(0.0, 1.0) * u16::MAX as f64) as u16;
+    let colour = link_config.colour();
+
+    let mut foreground = AttrColor::new_foreground(channel(colour.r()),
+        channel(colour.g()), channel(colour.b()));
+    foreground.set_start_index(start as u32);
+    foreground.set_end_index(end as u32);
+    attrs.push(foreground.into());
+
+    if link_config.underline() {
+        let mut underline = AttrInt::new_underline(Underline::Single);
+        underline.set_start_index(start as u32);
+        underline.set_end_index(end as u32);
+        attrs.push(underline.into());
+    }
+
+    attrs
+}
@@ -0,0 +1,394 @@
+/* 17/10/2026 */
+
+//!
+//! Front matter: a block at the very top of a Markdown file, between two
+//! `+++` lines for TOML, or two `---` lines for YAML, which is taken off the
+//! text before it is parsed.
+//!
+//! ```text
+//! +++
+//! title = "Rust: PDFs"
+//! author = "Be Hai Nguyen"
+//! date = 2026-10-17
+//! language = "en"
+//!
+//! [fonts]
+//! paragraph = { family = "Be Vietnam Pro", size = 11, weight = "normal", style = "normal" }
+//! +++
+//! ```
+//!
+//! `title`, `author`, `subject`, `keywords` and `language` go into the
+//! `[metadata]` section, `date` into `[metadata] creation_date`. All other
+//! keys override the same configuration keys for this document only, over
+//! the configuration file.
+//!
+//! A `---` line is also a thematic break: a text starting with one, with
+//! another `---` line further down, only has YAML front matter when the
+//! lines between them parse as YAML, with at least one key, and all of its
+//! top-level keys are the document keys above or configuration sections.
+//! Otherwise they are left in the text.
+//!
+//! # Limitations
+//!
+//! YAML front matter is a subset of YAML: `key: value` pairs, nested by
+//! indentation, with string, number and boolean values, `[a, b]` lists and
+//! `- item` lists of such values. Anchors, multi-line strings and lists of
+//! maps are not supported.
+//!
+
+use toml::{Table, Value};
+
+use crate::config::CONFIG_SECTIONS;
+
+/// Front matter keys which go into the `[metadata]` section, and their
+/// `[metadata]` names.
+const METADATA_KEYS: [(&str, &str); 6] = [
+    ("title", "title"),
+    ("author", "author"),
+    ("subject", "subject"),
+    ("keywords", "keywords"),
+    ("language", "language"),
+    ("date", "creation_date"),
+];
+
+/// Split the front matter off a Markdown text.
+///
+/// # Returns
+///
+/// The front matter as configuration overrides, `None` when the text does
+/// not start with front matter, and the rest of the text. Lines between
+/// `---` lines are only front matter when [`is_yaml_front_matter()`].
+///
+/// * An error message — if `+++` front matter is not valid TOML.
+///
+pub fn split_front_matter(text: &str) -> Result<(Option<Table>, &str), String> {
+    let text_start = text.strip_prefix('\u{feff}').unwrap_or(text);
+
+    let Some((first_line, after_first)) = text_start.split_once('\n') else {
+        return Ok((None, text));
+    };
+
+    let delimiter = first_line.trim_end();
+    if delimiter != "+++" && delimiter != "---" {
+        return Ok((None, text));
+    }
+
+    // The closing delimiter line.
+    let mut offset = 0;
+    let mut closing: Option<(usize, usize)> = None;
+    for line in after_first.split_inclusive('\n') {
+        if line.trim_end() == delimiter {
+            closing = Some((offset, offset + line.len()));
+            break;
+        }
+        offset += line.len();
+    }
+
+    // Without a closing line, `---` is a thematic break.
+    let Some((end, rest_start)) = closing else { return Ok((None, text)); };
+
+    let front_matter = &after_first[..end];
+    let table = if delimiter == "+++" {
+        front_matter.parse::<Table>()
+            .map_err(|err| format!("front matter: {err}"))?
+    } else {
+        // Otherwise the `---` lines are thematic breaks.
+        match parse_yaml(front_matter) {
+            Ok(table) if is_yaml_front_matter(&table) => table,
+            _ => return Ok((None, text)),
+        }
+    };
+
+    Ok((Some(config_overrides(table)), &after_first[rest_start..]))
+}
+
+/// Whether YAML between two `---` lines is front matter: a non-empty table 
+/// of document keys and configuration sections. Markdown such as a heading, 
+/// which YAML takes as a comment, or `Note: read first` is not.
+fn is_yaml_front_matter(table: &Table) -> bool {
+    !table.is_empty() && table.keys().all(|key| CONFIG_SECTIONS.contains(&key.as_str())
+        || METADATA_KEYS.iter().any(|(document_key, _)| document_key == key))
+}
+
+/// Move the document keys of the front matter into a `[metadata]` table.
+fn config_overrides(mut table: Table) -> Table {
+    let mut metadata = match table.remove("metadata") {
+        Some(Value::Table(metadata)) => metadata,
+        _ => Table::new(),
+    };
+
+    for (key, metadata_key) in METADATA_KEYS {
+        let Some(value) = table.remove(key) else { continue; };
+
+        let value = match (metadata_key, value) {
+            // A TOML date, or a YAML date read as a string.
+            (_, Value::Datetime(datetime)) => Value::String(datetime.to_string()),
+            ("keywords", Value::String(keywords)) => Value::Array(keywords.split(',')
+                .map(|keyword| Value::String(keyword.trim().to_string()))
+                .filter(|keyword| keyword.as_str() != Some(""))
+                .collect()),
+            (_, value) => value,
+        };
+        metadata.insert(metadata_key.to_string(), value);
+    }
+
+    if !metadata.is_empty() {
+        table.insert("metadata".to_string(), Value::Table(metadata));
+    }
+
+    table
+}
+
+/// A YAML scalar or `[a, b]` list, as a TOML value.
+fn yaml_value(text: &str) -> Value {
+    let text = text.trim();
+
+    if let Some(items) = text.strip_prefix('[').and_then(|items| items.strip_suffix(']')) {
+        if items.trim().is_empty() { return Value::Array(Vec::new()); }
+        return Value::Array(split_yaml_list(items).into_iter().map(yaml_value).collect());
+    }
+
+    if let Some(quoted) = text.strip_prefix('"').and_then(|quoted| quoted.strip_suffix('"')) {
+        return Value::String(quoted.replace("\\\"", "\"").replace("\\\\", "\\"));
+    }
+    if let Some(quoted) = text.strip_prefix('\'').and_then(|quoted| quoted.strip_suffix('\'')) {
+        return Value::String(quoted.replace("''", "'"));
+    }
+
+    // An unquoted value ends at a comment.
+    let text = text.split(" #").next().unwrap_or(text).trim_end();
+
+    match text {
+        "true" => Value::Boolean(true),
+        "false" => Value::Boolean(false),
+        _ => text.parse::<i64>().map(Value::Integer)
+            .or_else(|_| text.parse::<f64>().map(Value::Float))
+            .unwrap_or_else(|_| Value::String(text.to_string())),
+    }
+}
+
+/// Split the items of a `[a, b]` list at commas outside quotes.
+fn split_yaml_list(items: &str) -> Vec<&str> {
+    let mut parts: Vec<&str> = Vec::new();
+    let mut quote: Option<char> = None;
+    let mut start = 0;
+
+    for (index, ch) in items.char_indices() {
+        match (quote, ch) {
+            (None, '"' | '\'') => quote = Some(ch),
+            (Some(open), _) if ch == open => quote = None,
+            (None, ',') => {
+                parts.push(&items[start..index]);
+                start = index + 1;
+            },
+            _ => {},
+        }
+    }
+    parts.push(&items[start..]);
+
+    parts
+}
+
+/// Parse the lines of a YAML block at one indentation, from `lines[*index]`
+/// on, into a map or, for `- item` lines, a list.
+///
+/// # Arguments
+///
+/// * `lines` — the line number, indentation and text of each line, blank
+///   and comment lines left out.
+///
+fn yaml_block(lines: &[(usize, usize, &str)],
+    index: &mut usize,
+    indent: usize
+) -> Result<Value, String> {
+    let is_item = |text: &str| text == "-" || text.starts_with("- ");
+
+    if lines.get(*index).is_some_and(|(_, _, text)| is_item(text)) {
+        let mut items: Vec<Value> = Vec::new();
+
+        while let Some((_, line_indent, text)) = lines.get(*index).copied()
+            && line_indent == indent && is_item(text) {
+            items.push(yaml_value(&text[1..]));
+            *index += 1;
+        }
+
+        return Ok(Value::Array(items));
+    }
+
+    let mut table = Table::new();
+
+    while let Some((number, line_indent, text)) = lines.get(*index).copied() {
+        if line_indent < indent { break; }
+        if line_indent > indent {
+            return Err(format!("line {number}: unexpected indentation"));
+        }
+
+        let Some((key, value)) = text.split_once(':') else {
+            return Err(format!("line {number}: expected 'key: value'"));
+        };
+        let key = key.trim().trim_matches(|ch| ch == '"' || ch == '\'');
+        *index += 1;
+
+        if !value.trim().is_empty() {
+            table.insert(key.to_string(), yaml_value(value));
+            continue;
+        }
+
+        // A nested block, or an empty value, which is left out.
+        match lines.get(*index) {
+            Some((_, nested_indent, _)) if *nested_indent > indent => {
+                let nested = yaml_block(lines, index, *nested_indent)?;
+                table.insert(key.to_string(), nested);
+            },
+            // `- item` lines may sit at the same indentation as their key.
+            Some((_, nested_indent, text)) if *nested_indent == indent && is_item(text) => {
+                let nested = yaml_block(lines, index, indent)?;
+                table.insert(key.to_string(), nested);
+            },
+            _ => {},
+        }
+    }
+
+    Ok(Value::Table(table))
+}
+
+/// Parse YAML front matter into a TOML table. See the module documentation
+/// for the YAML supported.
+fn parse_yaml(text: &str) -> Result<Table, String> {
+    let lines: Vec<(usize, usize, &str)> = text.lines().enumerate()
+        .map(|(number, line)| (number + 2, line.len() - line.trim_start().len(), line.trim()))
+        .filter(|(_, _, text)| !text.is_empty() && !text.starts_with('#'))
+        .collect();
+
+    let mut index = 0;
+    match yaml_block(&lines, &mut index, 0)? {
+        Value::Table(table) => Ok(table),
+        _ => Err("expected 'key: value' pairs".to_string()),
+    }
+}
+
+// To run test for this module only:
+//
+//     * cargo test front_matter::tests
+//
+//     * cargo test front_matter::tests::test_split_front_matter_toml -- --exact [--nocapture]
+//     * cargo test front_matter::tests::test_split_front_matter_yaml -- --exact [--nocapture]
+//     * cargo test front_matter::tests::test_split_front_matter_none -- --exact [--nocapture]
+//     * cargo test front_matter::tests::test_config_sections -- --exact [--nocapture]
+//     * cargo test front_matter::tests::test_with_overrides -- --exact [--nocapture]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    use crate::config::load_config;
+
+    #[test]
+    fn test_split_front_matter_toml() {
+        let text = "+++\ntitle = \"Essay\"\ndate = 2026-10-17\nkeywords = \"rust, pdf\"\n\n\
+            [toc]\nenabled = true\n+++\n# Heading\n";
+
+        let (overrides, rest) = split_front_matter(text).unwrap();
+        let overrides = overrides.unwrap();
+
+        assert_eq!(rest, "# Heading\n");
+        assert_eq!(overrides["metadata"]["title"].as_str(), Some("Essay"));
+        assert_eq!(overrides["metadata"]["creation_date"].as_str(), Some("2026-10-17"));
+        assert_eq!(overrides["metadata"]["keywords"], Value::Array(vec![
+            Value::String("rust".to_string()), Value::String("pdf".to_string())]));
+        assert_eq!(overrides["toc"]["enabled"].as_bool(), Some(true));
+        assert!(overrides.get("title").is_none(), "title moved into [metadata]");
+    }
+
+    #[test]
+    fn test_split_front_matter_yaml() {
+        let text = "---\r\n\
+            title: \"Rust: PDFs\"\r\n\
+            author: Be Hai Nguyen # the author\r\n\
+            keywords: [rust, 'pdf, cairo']\r\n\
+            date: 2026-10-17\r\n\
+            pagination:\r\n\
+            \x20 orphans: 3\r\n\
+            \x20 keep_with_next:\r\n\
+            \x20 - 1\r\n\
+            \x20 - 0\r\n\
+            link:\r\n\
+            \x20 underline: false\r\n\
+            ---\r\nText\r\n";
+
+        let (overrides, rest) = split_front_matter(text).unwrap();
+        let overrides = overrides.unwrap();
+
+        assert_eq!(rest, "Text\r\n");
+
+        let metadata = &overrides["metadata"];
+        assert_eq!(metadata["title"].as_str(), Some("Rust: PDFs"));
+        assert_eq!(metadata["author"].as_str(), Some("Be Hai Nguyen"));
+        assert_eq!(metadata["keywords"], Value::Array(vec![
+            Value::String("rust".to_string()), Value::String("pdf, cairo".to_string())]));
+        assert_eq!(metadata["creation_date"].as_str(), Some("2026-10-17"));
+
+        assert_eq!(overrides["pagination"]["orphans"].as_integer(), Some(3));
+        assert_eq!(overrides["pagination"]["keep_with_next"], Value::Array(vec![
+            Value::Integer(1), Value::Integer(0)]));
+        assert_eq!(overrides["link"]["underline"].as_bool(), Some(false));
+
+        assert!(split_front_matter("+++\ntitle = \n+++\n").is_err());
+    }
+
+    #[test]
+    fn test_split_front_matter_none() {
+        let test_data: Vec<&str> = vec![
+            "# Heading\n",
+            "",
+            // A thematic break.
+            "---\nText\n",
+            // Thematic breaks around text which is not YAML.
+            "---\nText\n---\n",
+            "---\ntitle: a\n  b: c\n---\n",
+            "---\n# Chapter 1\n---\n",
+            "---\nNote: read first\n---\n",
+            "Text\n---\n",
+            " +++\ntitle = \"a\"\n+++\n",
+        ];
+
+        for text in test_data {
+            let (overrides, rest) = split_front_matter(text).unwrap();
+            assert!(overrides.is_none(), "{text:?}");
+            assert_eq!(rest, text);
+        }
+    }
+
+    #[test]
+    /// Every section of the configuration file can be overridden by YAML 
+    /// front matter.
+    fn test_config_sections() {
+        let source: Table = std::fs::read_to_string("config/config.toml").unwrap()
+            .parse().unwrap();
+
+        for key in source.keys() {
+            assert!(CONFIG_SECTIONS.contains(&key.as_str()), "{key}");
+        }
+    }
+
+    #[test]
+    fn test_with_overrides() {
+        let config = load_config(Path::new("config/config.toml")).unwrap();
+        let text = "+++\ntitle = \"Essay\"\nlanguage = \"vi\"\n\
+            [pagination]\norphans = 3\n+++\n";
+
+        let (overrides, _) = split_front_matter(text).unwrap();
+        let document_config = config.with_overrides(&overrides.unwrap()).unwrap();
+
+        assert_eq!(document_config.metadata().title(), Some("Essay"));
+        assert_eq!(document_config.metadata().language(), Some("vi"));
+        assert_eq!(document_config.pagination().orphans(), 3);
+        // Keys not overridden keep their configured values.
+        assert_eq!(document_config.pagination().widows(), config.pagination().widows());
+        assert_eq!(document_config.toc().depth(), config.toc().depth());
+
+        let mut overrides = Table::new();
+        overrides.insert("pagination".to_string(), Value::String("none".to_string()));
+        assert!(config.with_overrides(&overrides).is_err());
+    }
+}
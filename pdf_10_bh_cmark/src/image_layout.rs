@@ -4,46 +4,92 @@
 //! Layout an image and its associated caption onto a PDF. The layout algorithm is 
 //! represented in detail in the function [`measure_image_block()`] documentation.
 //! 
+//! The caption goes below or above the image, `[image_block] caption_position`, 
+//! `caption_gap` away from it. It is as wide as the content area, or as the 
+//! scaled image, `caption_width`, and aligned by `caption_alignment`. 
+//! 
 
 use cairo::{Context, ImageSurface};
-use pango::Layout;
+use pango::{Alignment, Layout};
 use pangocairo::functions::show_layout;
 
-use crate::page_geometry::{
-    a4_default_content_width,
-    a4_default_content_height,
-    A4_DEFAULT_MARGINS,
-};
+use crate::page_geometry::PageGeometry;
 
-use crate::config::Config;
+use crate::config::{
+    CaptionAlignment, CaptionPosition, CaptionWidth, Config, ImageBlockConfig, ImageSizing
+};
 
 use crate::document;
 use document::{ImageBlockLayoutInfo, ImageBlockMeasuredInfo};
 
 use crate::text_layout::{
+    layout_width,
+    create_text_layout,
     layout_block_height,
     center_layout_block,
 };
 
-/// Scale an already loaded image to fit [`a4_default_content_width()`].
+/// Scale an already loaded image to fit [`PageGeometry::content_width()`].
 /// 
-/// If original image width is less than [`a4_default_content_width()`], the returned 
+/// If original image width is less than the content width, the returned 
 /// factor is greater than 1.0, representing a scale up, the quality of the rendered 
 /// image might not be as good as the original image.
 /// 
-/// If the original image width is greater than [`a4_default_content_width()`], the 
+/// If the original image width is greater than the content width, the 
 /// returned factor is less than 1.0, representing a scale down.
 /// 
 /// # Arguments
 /// 
 /// * `original_image_width` — physical width of the image.
 /// 
+/// * `content_width` — the width of the page content area.
+/// 
 /// # Return
 /// 
 /// [`f64`] — the image scaling factor.
 /// 
-fn get_scaling_factor(original_image_width: f64) -> f64 {
-    a4_default_content_width() / original_image_width
+fn get_scaling_factor(original_image_width: f64, content_width: f64) -> f64 {
+    content_width / original_image_width
+}
+
+/// The scale factor of an image under a `[image_block] sizing` mode, before
+/// `reduction_factor` is applied.
+///
+/// * "fit_width" — the image fills [`PageGeometry::content_width()`], see
+///   [`get_scaling_factor()`].
+///
+/// * "natural" — the image keeps its physical size: a pixel is `1 / dpi`
+///   of an inch, 72 points.
+///
+/// * "natural_max_width" — the natural size, scaled down to the content
+///   width if wider.
+///
+/// Figure rows and floating images scale by this too, with their own 
+/// widths for `content_width`.
+///
+/// # Arguments
+///
+/// * `original_image_width` — the width of the image in pixels.
+///
+/// * `dpi` — the resolution of the image, in pixels per inch.
+///
+/// * `content_width` — the width of the page content area.
+///
+/// * `sizing` — the sizing mode.
+///
+pub fn sizing_scale_factor(original_image_width: f64,
+    dpi: f64,
+    content_width: f64,
+    sizing: ImageSizing
+) -> f64 {
+    let fit_width = get_scaling_factor(original_image_width, content_width);
+    let natural = 72.0 / dpi;
+
+    match sizing {
+        ImageSizing::FitWidth => fit_width,
+        ImageSizing::Natural => natural,
+        ImageSizing::NaturalMaxWidth => natural.min(fit_width),
+    }
 }
 
 /// This function is used by [`render_image_block()`] to determine an appropriate
@@ -51,7 +97,7 @@ fn get_scaling_factor(original_image_width: f64) -> f64 {
 /// (spacing before + image + caption).
 ///
 /// The caller first computes `original_scale_factor`, which scales the image to
-/// fit within [`a4_default_content_width()`]. This guarantees that the image
+/// fit within [`PageGeometry::content_width()`]. This guarantees that the image
 /// fits horizontally on the page, but does *not* guarantee that the image block
 /// (spacing before + image + caption) fits vertically in the remaining space.
 ///
@@ -73,7 +119,7 @@ fn get_scaling_factor(original_image_width: f64) -> f64 {
 ///       proceed to step 3.
 ///
 /// 3. Attempt to place the block at the top of a new page
-///    (`A4_DEFAULT_MARGINS.top`):
+///    (`page_geometry.margins().top`):
 ///
 ///    a. Repeat the same progressive‑reduction loop described in step 2a.
 ///
@@ -89,22 +135,26 @@ fn get_scaling_factor(original_image_width: f64) -> f64 {
 /// * `current_top_y` — the y‑coordinate on the current page where the block
 ///   would be placed.
 /// 
-/// * `caption_height` — the height of the caption block (from a [`pango::Layout`]).
+/// * `caption_height` — the height of the caption block (from a [`pango::Layout`]) 
+///   and of the gap between it and the image, given the scale factor: a caption 
+///   as wide as the image wraps onto more lines as the image shrinks.
 /// 
 /// * `original_image_height` — the unscaled image height in pixels.
 /// 
 /// * `original_scale_factor` — the scale factor that fits the image within
-///   [`a4_default_content_width()`].
-/// 
-/// * `step_scale_factor` — the amount by which the scale factor is reduced on
-///   each iteration when attempting to make the block fit.
+///   [`PageGeometry::content_width()`].
 /// 
-/// * `min_allowed_scale` — the minimum acceptable scale factor. If the scale
-///   falls below this value, layout is considered impossible.
+/// * `image_block` — the `[image_block]` section: `step_scale_factor`, the 
+///   amount by which the scale factor is reduced on each iteration when 
+///   attempting to make the block fit, and `min_allowed_scale`, the minimum 
+///   acceptable scale factor. If the scale falls below it, layout is 
+///   considered impossible.
 /// 
 /// * `spacing_before` — the vertical gap above this image block if it is to 
 ///   placed on the current page. 
 ///
+/// * `page_geometry` — the page size and margins in effect.
+///
 /// # Returns
 ///
 /// * [`ImageBlockLayoutInfo`] — on success, containing the chosen scale factor,
@@ -113,19 +163,21 @@ fn get_scaling_factor(original_image_width: f64) -> f64 {
 /// * `std::error::Error` — if the block cannot be fitted even after progressive
 ///   reduction and a page break.
 ///
-fn step_scale_image(
+pub fn step_scale_image(
     current_top_y: f64,
-    caption_height: f64,
+    caption_height: &dyn Fn(f64) -> f64,
     original_image_height: f64,
     original_scale_factor: f64,
-    step_scale_factor: f64,
-    min_allowed_scale: f64,
+    image_block: &ImageBlockConfig,
     spacing_before: f64,
+    page_geometry: &PageGeometry,
 ) -> Result<ImageBlockLayoutInfo, Box<dyn std::error::Error>> 
 {
     // There are only two possible y coordinates: the current y and a
-    // new page A4_DEFAULT_MARGINS.top.
-    let y_positions = [current_top_y, A4_DEFAULT_MARGINS.top];
+    // new page top margin.
+    let y_positions = [current_top_y, page_geometry.margins().top];
+    let step_scale_factor = image_block.step_scale_factor();
+    let min_allowed_scale = image_block.min_allowed_scale();
 
     for (i, &top_y) in y_positions.iter().enumerate() {
         let mut scale = original_scale_factor;
@@ -140,9 +192,9 @@ fn step_scale_image(
             // `ImageBlockMeasuredInfo::block_height()` includes `.after()`, so
             // if there is not enough vertical space for the next block, it flows 
             // to the next page naturally.
-            let block_height = image_height + caption_height;
+            let block_height = image_height + caption_height(scale);
 
-            if top_y + spacing_before + block_height <= a4_default_content_height() {
+            if top_y + spacing_before + block_height <= page_geometry.content_bottom() {
                 let new_page = i == 1;
                 // The image block happens to be on a new page, do not apply 
                 // `config.block_spacing().image().before()` since there is no
@@ -172,8 +224,9 @@ fn step_scale_image(
 ///
 /// The process works as follows:
 ///
-/// * Compute the scale factor required to fit the image within
-///   [`a4_default_content_width()`].
+/// * Compute the scale factor of the `[image_block] sizing` mode: by
+///   default, the one required to fit the image within
+///   [`PageGeometry::content_width()`]. See [`sizing_scale_factor()`].
 ///
 /// * Apply `reduction_factor` to obtain the initial final scale factor.
 ///   (The image is not scaled yet; this value is only used for layout calculations.)
@@ -202,20 +255,25 @@ fn step_scale_image(
 ///
 /// # Arguments
 ///
-/// * `image_width` — the original width of the image.
+/// * `image_size` — the original width and height of the image.
 /// 
-/// * `image_height` — the original height of the image.
+/// * `dpi` — the resolution recorded in the image file, in pixels per inch.
+///   `config.image_block().default_dpi()` when absent.
 /// 
 /// * `caption_layout` — the fully prepared caption text [`Layout`] associated 
 ///   with the image.
 /// 
 /// * `top_y` — the y‑coordinate at which to place the top of the image.
 /// 
-/// * `context` — the Cairo PDF [`Context`].
+/// * `spacing_before` — the vertical gap above this image block if it is 
+///   placed on the current page: `config.block_spacing().image().before()`, 
+///   or nothing at the top of a page.
 /// 
 /// * `config` — configuration parameters such as the caption font,
 ///   `reduction_factor`, whether to horizontally center the image and caption,
 ///   `step_scale_factor`, and `min_allowed_scale`.
+/// 
+/// * `page_geometry` — the page size and margins in effect.
 ///
 /// # Returns
 ///
@@ -225,37 +283,129 @@ fn step_scale_image(
 /// * [`std::error::Error`] — if the block cannot be fitted even after progressive
 ///   reduction and a page break.
 /// 
-pub fn measure_image_block(image_width: f64,
-    image_height: f64,
+pub fn measure_image_block(image_size: (f64, f64),
+    dpi: Option<f64>,
     caption_layout: &Layout,
     top_y: f64, 
+    spacing_before: f64,
     config: &Config,
+    page_geometry: &PageGeometry,
 ) -> Result<ImageBlockMeasuredInfo, Box<dyn std::error::Error>> {
+    let (image_width, image_height) = image_size;
     let reduction_factor = config.image_block().reduction_factor();
     let centre_aligned = config.image_block().centre_aligned();
-    let step_scale_factor= config.image_block().step_scale_factor();
-    let min_allowed_scale= config.image_block().min_allowed_scale();
-    let spacing_before = config.block_spacing().image().before();
 
-    let caption_height = layout_block_height(caption_layout);
+    let caption_gap = if layout_block_height(caption_layout) > 0.0 {
+        config.image_block().caption_gap()
+    } else { 0.0 };
 
-    let scale_factor: f64 = get_scaling_factor(image_width) * reduction_factor;
+    let caption_height_at = |scale: f64| 
+        caption_height(caption_layout, image_width * scale, config, page_geometry) + caption_gap;
+
+    let dpi = dpi.unwrap_or(config.image_block().default_dpi());
+    let scale_factor: f64 = sizing_scale_factor(image_width, dpi,
+        page_geometry.content_width(), config.image_block().sizing()) * reduction_factor;
 
     let scaled_res = step_scale_image(top_y, 
-        caption_height, image_height, scale_factor, step_scale_factor, 
-        min_allowed_scale, spacing_before)?;
+        &caption_height_at, image_height, scale_factor, config.image_block(), 
+        spacing_before, page_geometry)?;
+
+    let caption_height = caption_height(caption_layout, 
+        image_width * scaled_res.scale_factor(), config, page_geometry);
 
     let x: f64 = if centre_aligned {
         let width: f64 = image_width * scaled_res.scale_factor();
-        ( (a4_default_content_width() - width) / 2.0 ) + A4_DEFAULT_MARGINS.left
-    } else { A4_DEFAULT_MARGINS.left };
+        ( (page_geometry.content_width() - width) / 2.0 ) + page_geometry.margins().left
+    } else { page_geometry.margins().left };
 
     let scaled_image_height: f64 = image_height * scaled_res.scale_factor();
 
+    let caption_above = config.image_block().caption_position() == CaptionPosition::Above;
+
     Ok(ImageBlockMeasuredInfo::new(scaled_res, 
         caption_height,
         scaled_image_height,
-        config.block_spacing().image().after(), x))
+        config.block_spacing().image().after(), x)
+        .with_caption_placement(caption_above, caption_gap))
+}
+
+/// The width of the caption of an image block: that of the scaled image 
+/// when `[image_block] caption_width` is "image", of the content area 
+/// otherwise.
+fn caption_width(scaled_image_width: f64, config: &Config, page_geometry: &PageGeometry) -> f64 {
+    match config.image_block().caption_width() {
+        CaptionWidth::Content => page_geometry.content_width(),
+        CaptionWidth::Image => scaled_image_width,
+    }
+}
+
+/// The height of the caption of an image block once laid out to its 
+/// width, see [`caption_width()`]. The prepared layout is left as it is.
+fn caption_height(caption_layout: &Layout, 
+    scaled_image_width: f64,
+    config: &Config,
+    page_geometry: &PageGeometry,
+) -> f64 {
+    let layout = caption_layout.copy();
+    layout.set_width(layout_width(caption_width(scaled_image_width, config, page_geometry)));
+
+    layout_block_height(&layout)
+}
+
+/// Align the caption of an image block by `[image_block] caption_alignment`: 
+/// "left", "centre", "right" or "justify". When absent, the caption keeps 
+/// its justified layout, see [`render_image_block()`].
+pub fn align_caption(caption_layout: &Layout, config: &Config) {
+    let (alignment, justify) = match config.image_block().caption_alignment() {
+        Some(CaptionAlignment::Left) => (Alignment::Left, false),
+        Some(CaptionAlignment::Centre) => (Alignment::Center, false),
+        Some(CaptionAlignment::Right) => (Alignment::Right, false),
+        Some(CaptionAlignment::Justify) => (Alignment::Left, true),
+        None => return,
+    };
+
+    caption_layout.set_alignment(alignment);
+    caption_layout.set_justify(justify);
+}
+
+/// Draw a frame the size of the scaled image, with its top left corner at 
+/// `(x, y)` and `message` centred in it, in place of an image which could 
+/// not be loaded.
+pub fn render_placeholder(message: &str,
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+    context: &Context,
+    config: &Config,
+) {
+    let stroke_width = config.horizontal_break().stroke_width();
+    let colour = config.horizontal_break().colour();
+
+    context.save().expect("Failed to save Cairo context");
+
+    context.set_line_width(stroke_width);
+    context.set_source_rgb(colour.r(), colour.g(), colour.b());
+    context.rectangle(x, y, width, height);
+    let _ = context.stroke();
+
+    context.restore().expect("Failed to restore Cairo context");
+
+    let padding = config.fonts().caption().size() as f64;
+    let layout = create_text_layout(layout_width((width - 2.0 * padding).max(0.0)), message, 
+        config.fonts().caption(), false, context);
+    layout.set_alignment(pango::Alignment::Center);
+
+    // Clip long messages to the frame.
+    context.save().expect("Failed to save Cairo context");
+    context.rectangle(x, y, width, height);
+    context.clip();
+
+    let text_height = layout_block_height(&layout);
+    context.move_to(x + padding, y + ((height - text_height) / 2.0).max(0.0));
+    show_layout(context, &layout);
+
+    context.restore().expect("Failed to restore Cairo context");
 }
 
 /// Attempt to render an image block, whose PNG has been loaded, and whose scaled factor 
@@ -266,6 +416,10 @@ pub fn measure_image_block(image_width: f64,
 /// 
 /// * `image_surface` — the decoded PNG ready for rendering.
 /// 
+/// * `placeholder` — the message to show in a frame in place of the image, 
+///   when it could not be loaded. `image_surface` is then a blank stand-in 
+///   giving the size of the frame.
+/// 
 /// * `caption_layout` — the fully prepared caption text [`Layout`] associated 
 ///   with the image.
 /// 
@@ -276,19 +430,34 @@ pub fn measure_image_block(image_width: f64,
 /// 
 /// * `config` — configuration parameter [`crate::config::Config`]'s  
 ///   `image_block().centre_aligned()`: whether to horizontally center the image and 
-///   its caption. Only meaningful when the scaled image is narrower than the page width.
+///   its caption. Only meaningful when the scaled image is narrower than the page width. 
+///   A caption aligned by `image_block().caption_alignment()` is not centred as a block.
+/// 
+/// * `page_geometry` — the page size and margins in effect.
 ///  
 pub fn render_image_block(image_surface: &ImageSurface, 
+    placeholder: Option<&str>,
     caption_layout: &Layout, 
     measured_info: &ImageBlockMeasuredInfo,
     context: &Context,
     config: &Config,
+    page_geometry: &PageGeometry,
 ) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(message) = placeholder {
+        render_placeholder(message, 
+            measured_info.x_coordinate(), measured_info.image_top_y(),
+            image_surface.width() as f64 * measured_info.scale_factor(), 
+            measured_info.image_height(), context, config);
+
+        return render_caption(image_surface, caption_layout, measured_info, context, config, 
+            page_geometry);
+    }
+
     // Reserve the entire context. Painting an image will alter some context information.
     context.save().expect("Failed to save Cairo context");
 
     // Move to the top-left content area (unscaled)    
-    context.translate(measured_info.x_coordinate(), measured_info.block_top_y());
+    context.translate(measured_info.x_coordinate(), measured_info.image_top_y());
     
     // Apply scale transformation
     context.scale(measured_info.scale_factor(), measured_info.scale_factor());
@@ -304,15 +473,36 @@ pub fn render_image_block(image_surface: &ImageSurface,
     // Restore the original context.
     context.restore().expect("Failed to restore Cairo context");
     
-    let caption_top_y =
-        measured_info.block_top_y() + measured_info.image_height();
+    render_caption(image_surface, caption_layout, measured_info, context, config, page_geometry)
+}
+
+/// Draw the caption of an image block below or above the image, laid out to 
+/// its width, see [`caption_width()`].
+fn render_caption(image_surface: &ImageSurface, 
+    caption_layout: &Layout, 
+    measured_info: &ImageBlockMeasuredInfo,
+    context: &Context,
+    config: &Config,
+    page_geometry: &PageGeometry,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let scaled_image_width = image_surface.width() as f64 * measured_info.scale_factor();
+    let width = caption_width(scaled_image_width, config, page_geometry);
+    let x = match config.image_block().caption_width() {
+        CaptionWidth::Content => page_geometry.margins().left,
+        CaptionWidth::Image => measured_info.x_coordinate(),
+    };
 
-    context.move_to(A4_DEFAULT_MARGINS.left, caption_top_y);
+    // The prepared layout is shared: measuring and rendering it again must 
+    // not see this width. See `caption_height()`.
+    let layout = caption_layout.copy();
+    layout.set_width(layout_width(width));
+    context.move_to(x, measured_info.caption_top_y());
 
-    if config.image_block().centre_aligned() {
-        center_layout_block(&context, &caption_layout, a4_default_content_width());
+    // A caption aligned by `caption_alignment` is not centred as a block.
+    if config.image_block().centre_aligned() && config.image_block().caption_alignment().is_none() {
+        center_layout_block(context, &layout, width);
     }
-    show_layout(&context, &caption_layout);
+    show_layout(context, &layout);
 
     Ok(()) 
 }
@@ -321,12 +511,15 @@ pub fn render_image_block(image_surface: &ImageSurface,
 // 
 //     * cargo test image_layout::tests
 //
+//     * cargo test image_layout::tests::test_sizing_scale_factor -- --exact [--nocapture]
 //     * cargo test image_layout::tests::test_step_scale_image_unscalable -- --exact [--nocapture]
 //     * cargo test image_layout::tests::test_step_scale_image_current_page -- --exact [--nocapture]
 //     * cargo test image_layout::tests::test_step_scale_image_new_page -- --exact [--nocapture]
+//     * cargo test image_layout::tests::test_step_scale_image_caption_grows -- --exact [--nocapture]
 //     * cargo test image_layout::tests::test_measure_image_block_unscalable -- --exact [--nocapture]
 //     * cargo test image_layout::tests::test_measure_image_block_current_page -- --exact [--nocapture]
 //     * cargo test image_layout::tests::test_measure_image_block_new_page -- --exact [--nocapture]
+//     * cargo test image_layout::tests::test_measure_image_block_caption_above -- --exact [--nocapture]
 //
 #[cfg(test)]
 /// Note: the image used in the test is ./img/unscalable.png, although a physical image is 
@@ -341,13 +534,13 @@ pub fn render_image_block(image_surface: &ImageSurface,
 /// 
 mod tests {
     use crate::text_layout::{
-        a4_layout_width, 
+        layout_width, 
         create_text_layout,
     };
 
     use cairo::{Context, PdfSurface};
     use super::*;
-    use crate::page_geometry::A4;
+    use crate::page_geometry::{A4, A4_DEFAULT_MARGINS};
 
     const IMAGE_WIDTH: f64 = 964.0;
     const IMAGE_HEIGHT: f64 = 1600.0;
@@ -356,6 +549,12 @@ mod tests {
     const CAPTION_HEIGHT: f64 = 40.48046875;
         
     fn create_config(step_scale_factor: &str, min_allowed_scale: &str) -> Config {
+        create_config_with(step_scale_factor, min_allowed_scale, "")
+    }
+
+    /// `image_block` — further `[image_block]` settings, one per line.
+    fn create_config_with(step_scale_factor: &str, min_allowed_scale: &str, 
+        image_block: &str) -> Config {
         // There is a risk that this config_str will fail to load in the future when 
         // the configuration Rust code change.
         let config_str = 
@@ -376,6 +575,7 @@ mod tests {
             centre_aligned = true\n \
             step_scale_factor = {step_scale_factor}\n \
             min_allowed_scale = {min_allowed_scale}\n \
+            {image_block}\n \
             [block_spacing.heading]\n \
             before = [120.0, 40.0, 30.0, 20.0, 20.0, 20.0]\n \
             after = [15.0, 13.0, 0.0, 9.0, 8.0, 6.0]\n \
@@ -392,7 +592,8 @@ mod tests {
             stroke_width = 0.8\n \
             colour = { r = 0.7, g = 0.7, b = 0.7 }"
             .replace("{step_scale_factor}", step_scale_factor)
-            .replace("{min_allowed_scale}", min_allowed_scale);
+            .replace("{min_allowed_scale}", min_allowed_scale)
+            .replace("{image_block}", image_block);
 
         let config: Config = toml::from_str(&config_str)
             .expect("Failed to load test config string");
@@ -400,12 +601,34 @@ mod tests {
         config
     }
 
+    #[test]
+    fn test_sizing_scale_factor() {
+        let content_width = PageGeometry::default().content_width();
+
+        // Fills the content width.
+        assert_eq!(sizing_scale_factor(IMAGE_WIDTH, 96.0, content_width, ImageSizing::FitWidth),
+            content_width / IMAGE_WIDTH);
+
+        // 964px at 96 dpi is 723pt, wider than the content.
+        assert_eq!(sizing_scale_factor(IMAGE_WIDTH, 96.0, content_width, ImageSizing::Natural), 0.75);
+        assert_eq!(sizing_scale_factor(IMAGE_WIDTH, 96.0, content_width, 
+            ImageSizing::NaturalMaxWidth),
+            content_width / IMAGE_WIDTH);
+
+        // 964px at 300 dpi is 231.36pt, narrower than the content.
+        assert_eq!(sizing_scale_factor(IMAGE_WIDTH, 300.0, content_width, 
+            ImageSizing::NaturalMaxWidth),
+            0.24);
+    }
+
     #[test]
     /// Using `./img/unscalable.png` documented at the top of the test mod.
     /// 
     fn test_step_scale_image_unscalable() {
         let current_top_y = A4_DEFAULT_MARGINS.top;
-        let original_scale_factor = get_scaling_factor(IMAGE_WIDTH);
+        let page_geometry = PageGeometry::default();
+        let original_scale_factor = get_scaling_factor(IMAGE_WIDTH, 
+            page_geometry.content_width());
         let step_scale_factor = 0.0;
         // min_allowed_scale of 1.0 implies only accept the fit-page-width scaled image 
         // size. Note that `step_scale_image()` is not responsible for `reduction_factor` -- 
@@ -419,12 +642,12 @@ mod tests {
         let spacing_before = config.block_spacing().image().before();
 
         let res = step_scale_image(current_top_y, 
-            CAPTION_HEIGHT, 
+            &|_| CAPTION_HEIGHT, 
             IMAGE_HEIGHT, 
             original_scale_factor, 
-            step_scale_factor, 
-            min_allowed_scale,
-            spacing_before);
+            config.image_block(), 
+            spacing_before,
+            &page_geometry);
 
         let err = res.expect_err("Expected failure for unscalable image");
         assert!(err.to_string().contains("cannot fit"), "Unexpected error message");
@@ -435,7 +658,9 @@ mod tests {
     /// 
     fn test_step_scale_image_current_page() {
         let current_top_y = A4_DEFAULT_MARGINS.top;
-        let original_scale_factor = get_scaling_factor(IMAGE_WIDTH);
+        let page_geometry = PageGeometry::default();
+        let original_scale_factor = get_scaling_factor(IMAGE_WIDTH, 
+            page_geometry.content_width());
         // Ensure the image is scaled down enough to fit the page.
         let step_scale_factor = 0.1;
         let min_allowed_scale = 0.2;
@@ -446,12 +671,12 @@ mod tests {
         let spacing_before = config.block_spacing().image().before();
 
         let res = step_scale_image(current_top_y, 
-            CAPTION_HEIGHT, 
+            &|_| CAPTION_HEIGHT, 
             IMAGE_HEIGHT, 
             original_scale_factor, 
-            step_scale_factor, 
-            min_allowed_scale,
-            spacing_before);
+            config.image_block(), 
+            spacing_before,
+            &page_geometry);
 
         assert!(res.is_ok(), "Expected success for scalable image");
         let image_block = res.unwrap();
@@ -466,14 +691,16 @@ mod tests {
         assert!(image_block.scale_factor() >= min_allowed_scale, "step scale factor vs min allowed scale");
 
         let scaled_height = IMAGE_HEIGHT * image_block.scale_factor();
-        assert!(image_block.block_top_y() + scaled_height + CAPTION_HEIGHT <= a4_default_content_height());
+        assert!(image_block.block_top_y() + scaled_height + CAPTION_HEIGHT <= page_geometry.content_bottom());
     }
 
     #[test]
     fn test_step_scale_image_new_page() {
         // At the 600.00 y-coordinate, the image block should be on a new page.
         let current_top_y = 600.0;
-        let original_scale_factor = get_scaling_factor(IMAGE_WIDTH);
+        let page_geometry = PageGeometry::default();
+        let original_scale_factor = get_scaling_factor(IMAGE_WIDTH, 
+            page_geometry.content_width());
         // Ensure the image is scaled down enough to fit the page.
         let step_scale_factor = 0.1;
         let min_allowed_scale = 0.2;
@@ -484,12 +711,12 @@ mod tests {
         let spacing_before = config.block_spacing().image().before();
 
         let res = step_scale_image(current_top_y, 
-            CAPTION_HEIGHT, 
+            &|_| CAPTION_HEIGHT, 
             IMAGE_HEIGHT, 
             original_scale_factor, 
-            step_scale_factor, 
-            min_allowed_scale, 
-            spacing_before);
+            config.image_block(), 
+            spacing_before,
+            &page_geometry);
 
         assert!(res.is_ok(), "Expected success for scalable image");
         let image_block = res.unwrap();
@@ -504,7 +731,45 @@ mod tests {
         assert!(image_block.scale_factor() >= min_allowed_scale, "step scale factor vs min allowed scale");
 
         let scaled_height = IMAGE_HEIGHT * image_block.scale_factor();
-        assert!(image_block.block_top_y() + scaled_height + CAPTION_HEIGHT <= a4_default_content_height());
+        assert!(image_block.block_top_y() + scaled_height + CAPTION_HEIGHT <= page_geometry.content_bottom());
+    }
+
+    #[test]
+    /// A caption as wide as the image wraps onto more lines as the image 
+    /// shrinks: here its height is inversely proportional to the scale.
+    /// 
+    /// With a fixed caption the block fits one step below the original scale, 
+    /// see the previous tests. The taller caption takes another step.
+    fn test_step_scale_image_caption_grows() {
+        let current_top_y = A4_DEFAULT_MARGINS.top;
+        let page_geometry = PageGeometry::default();
+        let original_scale_factor = get_scaling_factor(IMAGE_WIDTH, 
+            page_geometry.content_width());
+        let step_scale_factor = 0.1;
+        let min_allowed_scale = 0.2;
+        let spacing_before = 30.0;
+
+        let config = create_config(&step_scale_factor.to_string(), 
+            &min_allowed_scale.to_string());
+
+        let caption_height = |scale: f64| 40.0 / scale;
+
+        let image_block = step_scale_image(current_top_y, 
+            &caption_height, 
+            IMAGE_HEIGHT, 
+            original_scale_factor, 
+            config.image_block(), 
+            spacing_before,
+            &page_geometry)
+            .expect("Expected success for scalable image");
+
+        assert!(!image_block.new_page(), "current page");
+        assert!((image_block.scale_factor() - (original_scale_factor - 0.2)).abs() < 1e-9, 
+            "scale factor {}", image_block.scale_factor());
+
+        let scale = image_block.scale_factor();
+        assert!(image_block.block_top_y() + IMAGE_HEIGHT * scale + caption_height(scale) 
+            <= page_geometry.content_bottom());
     }
 
     #[test]
@@ -528,11 +793,13 @@ mod tests {
         let context = Context::new(&surface)
             .expect("Failed to create context");
         
-        let layout = create_text_layout(a4_layout_width(), CAPTION, 
+        let page_geometry = PageGeometry::default();
+        let layout = create_text_layout(layout_width(page_geometry.content_width()), CAPTION, 
             config.fonts().caption(), true, &context);
 
-        let res= measure_image_block(IMAGE_WIDTH, 
-            IMAGE_HEIGHT, &layout, A4_DEFAULT_MARGINS.top, &config);
+        let res= measure_image_block((IMAGE_WIDTH, 
+            IMAGE_HEIGHT), None, &layout, A4_DEFAULT_MARGINS.top, 
+            config.block_spacing().image().before(), &config, &page_geometry);
 
         let err = res.expect_err("Expected failure for unscalable image");
         assert!(err.to_string().contains("cannot fit"), "Unexpected error message");
@@ -560,11 +827,13 @@ mod tests {
         let context = Context::new(&surface)
             .expect("Failed to create context");
 
-        let layout = create_text_layout(a4_layout_width(), CAPTION, 
+        let page_geometry = PageGeometry::default();
+        let layout = create_text_layout(layout_width(page_geometry.content_width()), CAPTION, 
             config.fonts().caption(), true, &context);
 
-        let res= measure_image_block(IMAGE_WIDTH, 
-            IMAGE_HEIGHT, &layout, A4_DEFAULT_MARGINS.top, &config);
+        let res= measure_image_block((IMAGE_WIDTH, 
+            IMAGE_HEIGHT), None, &layout, A4_DEFAULT_MARGINS.top, 
+            config.block_spacing().image().before(), &config, &page_geometry);
 
         assert!(res.is_ok(), "Expected success for scalable image");
 
@@ -605,11 +874,13 @@ mod tests {
         let context = Context::new(&surface)
             .expect("Failed to create context");
 
-        let layout = create_text_layout(a4_layout_width(), CAPTION, 
+        let page_geometry = PageGeometry::default();
+        let layout = create_text_layout(layout_width(page_geometry.content_width()), CAPTION, 
             config.fonts().caption(), true, &context);
 
-        let res= measure_image_block(IMAGE_WIDTH, 
-            IMAGE_HEIGHT, &layout, TOP_Y, &config);
+        let res= measure_image_block((IMAGE_WIDTH, 
+            IMAGE_HEIGHT), None, &layout, TOP_Y, 
+            config.block_spacing().image().before(), &config, &page_geometry);
 
         assert!(res.is_ok(), "Expected success for scalable image");
 
@@ -623,4 +894,46 @@ mod tests {
         assert_eq!(measured_info.block_top_y(), A4_DEFAULT_MARGINS.top, "top y");
     }
 
+    #[test]
+    /// Using `./img/unscalable.png` documented at the top of the test mod.
+    /// 
+    /// The caption is above the image, 6.0 away from it, and as wide as the 
+    /// scaled image: narrower than the content area, so it takes more lines.
+    fn test_measure_image_block_caption_above() {
+        const CAPTION_GAP: f64 = 6.0;
+
+        let config = create_config_with("0.1", "0.2", 
+            "caption_position = \"above\"\n caption_width = \"image\"\n caption_gap = 6.0");
+
+        let pdf_file_name = "test_measure_image_block_caption_above.pdf";
+        let surface = PdfSurface::new(A4.width, A4.height, pdf_file_name)
+            .expect("Failed to create PDF surface");
+
+        let context = Context::new(&surface)
+            .expect("Failed to create context");
+
+        let page_geometry = PageGeometry::default();
+        let layout = create_text_layout(layout_width(page_geometry.content_width()), CAPTION, 
+            config.fonts().caption(), true, &context);
+
+        let measured_info = measure_image_block((IMAGE_WIDTH, 
+            IMAGE_HEIGHT), None, &layout, A4_DEFAULT_MARGINS.top, 
+            config.block_spacing().image().before(), &config, &page_geometry)
+            .expect("Expected success for scalable image");
+
+        assert!(measured_info.caption_above(), "caption above");
+        assert_eq!(measured_info.caption_gap(), CAPTION_GAP, "caption gap");
+        assert!(measured_info.caption_height() > layout_block_height(&layout), 
+            "caption as wide as the image");
+
+        assert_eq!(measured_info.caption_top_y(), measured_info.block_top_y(), "caption top y");
+        assert_eq!(measured_info.image_top_y(), measured_info.block_top_y() 
+            + measured_info.caption_height() + CAPTION_GAP, "image top y");
+
+        let image_bottom_y = measured_info.image_top_y() + measured_info.image_height();
+        assert!(image_bottom_y <= page_geometry.content_bottom(), "fits the page");
+        assert_eq!(measured_info.block_height(), measured_info.image_height() + CAPTION_GAP 
+            + measured_info.caption_height() + measured_info.spacing_after(), "block height");
+    }
+
 }
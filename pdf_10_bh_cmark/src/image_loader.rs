@@ -0,0 +1,371 @@
+/* 17/10/2026 */
+
+//!
+//! Load the image files of image blocks. The format is recognised from the
+//! leading bytes of the file, not its extension.
+//!
+//! * PNG — decoded by Cairo.
+//!
+//! * JPEG — not decoded: only its dimensions are read from its frame
+//!   header. The image surface stays blank, and the original JPEG bytes are
+//!   attached to it as [`cairo::MIME_TYPE_JPEG`] mime data. The PDF backend
+//!   then embeds the JPEG stream unchanged, rather than re-encoding the
+//!   pixels, so photos do not bloat the PDF.
+//!
+//! A relative image path is looked for, in order:
+//!
+//! 1. in the directory of the Markdown file;
+//! 2. in each `[image_block] assets_dir` directory, themselves relative to
+//!    the directory of the Markdown file unless absolute;
+//! 3. in the current working directory, as before image paths were resolved.
+//!
+//! An image which cannot be loaded is not fatal: [`placeholder_image()`]
+//! stands in for it, and a frame showing the path and the error is drawn in
+//! its place.
+//!
+//! The resolution an image records, the PNG `pHYs` chunk or the JPEG JFIF
+//! header, gives its natural physical size. Only the horizontal resolution
+//! is used: images with non-square pixels are not supported.
+//!
+
+use std::fs;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+use cairo::{Format, ImageSurface, MIME_TYPE_JPEG};
+
+/// An image file format.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+}
+
+/// Recognise an image file format from its leading bytes.
+pub fn image_format(bytes: &[u8]) -> Option<ImageFormat> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some(ImageFormat::Png)
+    } else if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some(ImageFormat::Jpeg)
+    } else {
+        None
+    }
+}
+
+/// The `(width, height)` of a JPEG image, in pixels, from its first start
+/// of frame segment. `None` when there is none, or the data is truncated.
+pub fn jpeg_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    let be_u16 = |at: usize| -> Option<usize> {
+        Some(u16::from_be_bytes([*bytes.get(at)?, *bytes.get(at + 1)?]) as usize)
+    };
+
+    // After the start of image marker.
+    let mut index = 2;
+
+    loop {
+        // Markers start with 0xFF, which may be repeated as padding.
+        if *bytes.get(index)? != 0xFF { return None; }
+        while *bytes.get(index)? == 0xFF { index += 1; }
+
+        let marker = *bytes.get(index)?;
+        index += 1;
+
+        match marker {
+            // Start of frame: all of 0xC0 to 0xCF, bar DHT, JPG and DAC.
+            0xC0..=0xCF if !matches!(marker, 0xC4 | 0xC8 | 0xCC) => {
+                // Length, precision, then height and width.
+                let height = be_u16(index + 3)?;
+                let width = be_u16(index + 5)?;
+                return Some((width as u32, height as u32));
+            },
+            // Markers without a segment.
+            0x01 | 0xD0..=0xD7 => {},
+            // End of image, or start of scan before any frame.
+            0xD9 | 0xDA => return None,
+            // The segment length includes the length itself.
+            _ => index += be_u16(index)?,
+        }
+    }
+}
+
+/// The horizontal resolution of a PNG image, in pixels per inch, from its
+/// `pHYs` chunk. `None` when there is none, or it gives the pixel aspect
+/// ratio only.
+pub fn png_dpi(bytes: &[u8]) -> Option<f64> {
+    // After the signature, chunks of a length, a type, the data and a CRC.
+    let mut index = 8;
+
+    while let Some(header) = bytes.get(index..index + 8) {
+        let length = u32::from_be_bytes(header[..4].try_into().ok()?) as usize;
+        let data = bytes.get(index + 8..index + 8 + length)?;
+
+        match &header[4..8] {
+            // Pixels per unit on the x axis, on the y axis, then the unit,
+            // 1 for the metre.
+            b"pHYs" if length == 9 && data[8] == 1 => {
+                let pixels_per_metre = u32::from_be_bytes(data[..4].try_into().ok()?);
+                return (pixels_per_metre > 0).then_some(pixels_per_metre as f64 * 0.0254);
+            },
+            // `pHYs` comes before the image data.
+            b"IDAT" | b"IEND" => return None,
+            _ => index += 12 + length,
+        }
+    }
+
+    None
+}
+
+/// The horizontal resolution of a JPEG image, in pixels per inch, from its
+/// JFIF header. `None` when there is none, or it gives the pixel aspect
+/// ratio only.
+pub fn jfif_dpi(bytes: &[u8]) -> Option<f64> {
+    // The JFIF APP0 segment comes straight after the start of image.
+    let app0 = bytes.get(2..18)?;
+    if app0[..2] != [0xFF, 0xE0] || &app0[4..9] != b"JFIF\0" { return None; }
+
+    // After the version: the units, then the horizontal density.
+    let density = u16::from_be_bytes([app0[12], app0[13]]) as f64;
+
+    match app0[11] {
+        1 if density > 0.0 => Some(density),
+        2 if density > 0.0 => Some(density * 2.54),
+        _ => None,
+    }
+}
+
+/// Load an image file onto an [`ImageSurface`], see the module
+/// documentation.
+///
+/// # Returns
+///
+/// * [`ImageSurface`] — the decoded PNG, or a blank surface the size of the
+///   JPEG with the JPEG attached, and the resolution recorded in the file, in
+///   pixels per inch.
+///
+/// * [`std::error::Error`] — if the file cannot be read, is neither a PNG
+///   nor a JPEG, or cannot be decoded.
+///
+pub fn load_image(
+    path: &Path
+) -> Result<(ImageSurface, Option<f64>), Box<dyn std::error::Error>> {
+    let bytes = fs::read(path)?;
+
+    match image_format(&bytes) {
+        Some(ImageFormat::Png) => {
+            let dpi = png_dpi(&bytes);
+            let image_surface = ImageSurface::create_from_png(&mut Cursor::new(bytes))
+                .map_err(|err| format!("Failed to decode PNG image: {err}"))?;
+
+            Ok((image_surface, dpi))
+        },
+        Some(ImageFormat::Jpeg) => {
+            let (width, height) = jpeg_dimensions(&bytes)
+                .ok_or("Failed to read JPEG image dimensions")?;
+            let dpi = jfif_dpi(&bytes);
+
+            let image_surface = ImageSurface::create(Format::Rgb24, width as i32, height as i32)?;
+            image_surface.set_mime_data(MIME_TYPE_JPEG, bytes)?;
+
+            Ok((image_surface, dpi))
+        },
+        None => Err("Unsupported image format: expected PNG or JPEG".into()),
+    }
+}
+
+/// Load the image of an image block or a figure row, with its path 
+/// resolved against `image_dirs`, see [`resolve_image_path()`]. An image 
+/// which cannot be loaded is replaced by [`placeholder_image()`], and the 
+/// problem added to `warnings`.
+///
+/// # Returns
+///
+/// The image surface, the resolution recorded in the file, and for an image
+/// which could not be loaded, the path and the error to show in its place.
+///
+pub fn load_image_or_placeholder(path: &str,
+    image_dirs: &[PathBuf],
+    warnings: &mut Vec<String>
+) -> (ImageSurface, Option<f64>, Option<String>) {
+    let path = resolve_image_path(path, image_dirs);
+
+    match load_image(&path) {
+        Ok((image_surface, dpi)) => (image_surface, dpi, None),
+        Err(err) => {
+            warnings.push(format!("Failed to load image {}: {}", path.display(), err));
+
+            let image_surface = placeholder_image()
+                .expect("Failed to create image placeholder");
+            (image_surface, None, Some(format!("{}\n{}", path.display(), err)))
+        },
+    }
+}
+
+/// The directories searched for the images of a Markdown file, in order,
+/// bar the current working directory. See the module documentation.
+pub fn image_search_dirs(markdown_file: &Path, assets_dir: &[String]) -> Vec<PathBuf> {
+    let markdown_dir = markdown_file.parent().unwrap_or(Path::new(""));
+
+    std::iter::once(markdown_dir.to_path_buf())
+        .chain(assets_dir.iter().map(|dir| markdown_dir.join(dir)))
+        .collect()
+}
+
+/// Resolve an image path from the Markdown against the directories from
+/// [`image_search_dirs()`], then the current working directory. The first
+/// existing file wins; when there is none, the path within the Markdown
+/// file's directory is returned, for the error to name it.
+pub fn resolve_image_path(path: &str, search_dirs: &[PathBuf]) -> PathBuf {
+    let path = Path::new(path);
+    if path.is_absolute() { return path.to_path_buf(); }
+
+    search_dirs.iter()
+        .map(|dir| dir.join(path))
+        .chain(std::iter::once(path.to_path_buf()))
+        .find(|candidate| candidate.is_file())
+        .unwrap_or_else(|| search_dirs.first().map_or(path.to_path_buf(), |dir| dir.join(path)))
+}
+
+/// The size of [`placeholder_image()`] in pixels: 480 by 120 points at 96
+/// pixels per inch.
+const PLACEHOLDER_SIZE: (i32, i32) = (640, 160);
+
+/// A blank, transparent surface standing in for an image which cannot be
+/// loaded, so that its placeholder frame is laid out as an image would be.
+pub fn placeholder_image() -> Result<ImageSurface, Box<dyn std::error::Error>> {
+    Ok(ImageSurface::create(Format::ARgb32, PLACEHOLDER_SIZE.0, PLACEHOLDER_SIZE.1)?)
+}
+
+// To run test for this module only:
+//
+//     * cargo test image_loader::tests
+//
+//     * cargo test image_loader::tests::test_image_format -- --exact [--nocapture]
+//     * cargo test image_loader::tests::test_jpeg_dimensions -- --exact [--nocapture]
+//     * cargo test image_loader::tests::test_png_dpi -- --exact [--nocapture]
+//     * cargo test image_loader::tests::test_jfif_dpi -- --exact [--nocapture]
+//     * cargo test image_loader::tests::test_load_image_placeholder -- --exact [--nocapture]
+//     * cargo test image_loader::tests::test_resolve_image_path -- --exact [--nocapture]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Start of image, a JFIF APP0 segment, a padded baseline start of frame
+    /// of 600 x 400 pixels, then the end of image.
+    const JPEG_HEADER: [u8; 41] = [
+        0xFF, 0xD8,
+        0xFF, 0xE0, 0x00, 0x10, b'J', b'F', b'I', b'F', 0x00,
+        0x01, 0x01, 0x01, 0x00, 0x48, 0x00, 0x48, 0x00, 0x00,
+        0xFF, 0xFF, 0xC0, 0x00, 0x11, 0x08, 0x01, 0x90, 0x02, 0x58,
+        0x03, 0x01, 0x22, 0x00, 0x02, 0x11, 0x01, 0x03, 0x11, 0x01,
+        0xFF,
+    ];
+
+    #[test]
+    fn test_image_format() {
+        assert_eq!(image_format(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"), Some(ImageFormat::Png));
+        assert_eq!(image_format(&JPEG_HEADER), Some(ImageFormat::Jpeg));
+        assert_eq!(image_format(b"GIF89a"), None);
+        assert_eq!(image_format(b""), None);
+    }
+
+    #[test]
+    fn test_jpeg_dimensions() {
+        assert_eq!(jpeg_dimensions(&JPEG_HEADER), Some((600, 400)));
+
+        // A progressive start of frame.
+        let mut progressive = JPEG_HEADER;
+        progressive[22] = 0xC2;
+        assert_eq!(jpeg_dimensions(&progressive), Some((600, 400)));
+
+        // Truncated within the frame header.
+        assert_eq!(jpeg_dimensions(&JPEG_HEADER[..27]), None);
+        // Start of scan before any frame.
+        assert_eq!(jpeg_dimensions(&[0xFF, 0xD8, 0xFF, 0xDA, 0x00, 0x02]), None);
+        assert_eq!(jpeg_dimensions(&[0xFF, 0xD8]), None);
+    }
+
+    /// A PNG signature and chunks, with zeroed CRCs.
+    fn png(chunks: &[(&[u8; 4], &[u8])]) -> Vec<u8> {
+        let mut bytes = b"\x89PNG\r\n\x1a\n".to_vec();
+        for (chunk_type, data) in chunks {
+            bytes.extend((data.len() as u32).to_be_bytes());
+            bytes.extend(*chunk_type);
+            bytes.extend(*data);
+            bytes.extend([0; 4]);
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_png_dpi() {
+        let ihdr: (&[u8; 4], &[u8]) = (b"IHDR", &[0; 13]);
+        let idat: (&[u8; 4], &[u8]) = (b"IDAT", &[0; 4]);
+        // 3780 pixels per metre, 96.012 pixels per inch.
+        let phys: (&[u8; 4], &[u8]) = (b"pHYs", &[0, 0, 0x0E, 0xC4, 0, 0, 0x0E, 0xC4, 1]);
+        let aspect_only: (&[u8; 4], &[u8]) = (b"pHYs", &[0, 0, 0, 1, 0, 0, 0, 1, 0]);
+
+        let dpi = png_dpi(&png(&[ihdr, phys, idat])).unwrap();
+        assert!((dpi - 96.012).abs() < 1e-9, "{dpi}");
+
+        assert_eq!(png_dpi(&png(&[ihdr, idat])), None);
+        assert_eq!(png_dpi(&png(&[ihdr, aspect_only, idat])), None);
+        // After the image data.
+        assert_eq!(png_dpi(&png(&[ihdr, idat, phys])), None);
+        // Truncated.
+        assert_eq!(png_dpi(&png(&[ihdr, phys])[..40]), None);
+    }
+
+    #[test]
+    fn test_jfif_dpi() {
+        // 72 dots per inch.
+        assert_eq!(jfif_dpi(&JPEG_HEADER), Some(72.0));
+
+        // 72 dots per centimetre.
+        let mut per_cm = JPEG_HEADER;
+        per_cm[13] = 2;
+        assert_eq!(jfif_dpi(&per_cm), Some(72.0 * 2.54));
+
+        // Aspect ratio only.
+        let mut aspect_only = JPEG_HEADER;
+        aspect_only[13] = 0;
+        assert_eq!(jfif_dpi(&aspect_only), None);
+
+        assert_eq!(jfif_dpi(&[0xFF, 0xD8, 0xFF, 0xDB, 0x00, 0x43]), None);
+    }
+
+    #[test]
+    fn test_load_image_placeholder() {
+        let err = load_image(Path::new("./img/no_such_image.png"))
+            .expect_err("Expected failure for a missing image");
+        assert!(!err.to_string().is_empty(), "error message");
+
+        let image_surface = placeholder_image().expect("Failed to create placeholder");
+        assert_eq!((image_surface.width(), image_surface.height()), PLACEHOLDER_SIZE);
+    }
+
+    #[test]
+    fn test_resolve_image_path() {
+        let search_dirs = image_search_dirs(Path::new("text/essay.txt"), &["../config".to_string()]);
+        assert_eq!(search_dirs, vec![PathBuf::from("text"), PathBuf::from("text/../config")]);
+
+        let test_data: Vec<(&str, PathBuf)> = vec![
+            // Next to the Markdown file.
+            ("img/ho-chi-minh-fontainebleau-19460914.png",
+                PathBuf::from("text/img/ho-chi-minh-fontainebleau-19460914.png")),
+            // In an assets directory.
+            ("config.toml", PathBuf::from("text/../config/config.toml")),
+            // In the current working directory.
+            ("img/unscalable.png", PathBuf::from("img/unscalable.png")),
+            // Nowhere.
+            ("img/no_such_image.png", PathBuf::from("text/img/no_such_image.png")),
+        ];
+
+        for (path, expected) in test_data {
+            assert_eq!(resolve_image_path(path, &search_dirs), expected, "{path}");
+        }
+
+        // Absolute paths are left as they are.
+        let absolute = std::env::current_dir().unwrap().join("img/no_such_image.png");
+        assert_eq!(resolve_image_path(absolute.to_str().unwrap(), &search_dirs), absolute);
+    }
+}
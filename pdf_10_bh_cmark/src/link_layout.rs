@@ -0,0 +1,424 @@
+/* 17/10/2026 */
+
+//!
+//! Clickable links. `bh_cmark` does not parse inline links: `[label](url)`
+//! comes through as plain text within an [`InlineContent`]. Before the text
+//! goes into a [`pango::Layout`], each link is reduced to its label, styled
+//! with the `[link]` colour and underline, and its byte range within the
+//! layout text is recorded.
+//!
+//! Figure references, `@fig:name`, are replaced in the text with the links
+//! removed, and so are not recognised within a link's URL. See
+//! [`crate::figure_numbering`].
+//!
+//! When a layout is rendered, each link becomes a Cairo `Link` tag. The
+//! tag's rectangles cover the link's glyphs, worked out from the Pango
+//! layout, one rectangle per line for a link which wraps.
+//!
+//! # Limitations
+//!
+//! 1. Link labels cannot contain `]`, URLs cannot contain `)` or whitespace,
+//!    and link titles are not supported.
+//!
+//! 2. `*` and `_` within a URL may be taken as emphasis by `bh_cmark`.
+//!
+
+use std::ops::Range;
+
+use cairo::{Context, CAIRO_TAG_LINK};
+use pango::{AttrList, Layout};
+
+use bh_cmark::ast::InlineContent;
+
+use crate::config::LinkConfig;
+use crate::figure_numbering::Figures;
+use crate::font_utils::{create_link_attrs, create_span_attrs};
+
+/// A link within the text of a [`pango::Layout`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayoutLink {
+    /// Byte range of the label within the layout text.
+    range: Range<usize>,
+    url: String,
+}
+
+/// A `[label](url)` link found in Markdown text. All offsets are bytes.
+#[derive(Debug, PartialEq)]
+struct MarkdownLink {
+    /// The `[`.
+    start: usize,
+    /// The `]`.
+    label_end: usize,
+    /// Just after the `)`.
+    end: usize,
+    url: String,
+}
+
+impl MarkdownLink {
+    /// The byte ranges which are not part of the label: `[` and `](url)`.
+    fn markup(&self) -> [Range<usize>; 2] {
+        [self.start..self.start + 1, self.label_end..self.end]
+    }
+}
+
+/// Find the `[label](url)` links in a text, in order. Images, `![alt](path)`,
+/// are not links.
+fn markdown_links(text: &str) -> Vec<MarkdownLink> {
+    let mut links: Vec<MarkdownLink> = Vec::new();
+
+    let mut index = 0;
+    while let Some(offset) = text[index..].find('[') {
+        let start = index + offset;
+        index = start + 1;
+
+        if text[..start].ends_with('!') { continue; }
+
+        let Some(label_len) = text[start + 1..].find(']') else { break; };
+        let label_end = start + 1 + label_len;
+
+        let Some(destination) = text[label_end + 1..].strip_prefix('(') else { continue; };
+        let Some(url_len) = destination.find(')') else { continue; };
+        let url = destination[..url_len].trim();
+
+        if label_len == 0 || url.is_empty() || url.contains(char::is_whitespace) { continue; }
+
+        let end = label_end + 2 + url_len + 1;
+        links.push(MarkdownLink { start, label_end, end, url: url.to_string() });
+        index = end;
+    }
+
+    links
+}
+
+/// Map a byte offset within the Markdown text onto the text with the link
+/// markup removed. Offsets within the markup map to where it was.
+fn map_offset(offset: usize, links: &[MarkdownLink]) -> usize {
+    let removed: usize = links.iter()
+        .flat_map(|link| link.markup())
+        .map(|markup| offset.min(markup.end).saturating_sub(markup.start))
+        .sum();
+
+    offset - removed
+}
+
+/// Remove the link markup from a text, leaving the labels.
+fn strip_links(text: &str, links: &[MarkdownLink]) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut index = 0;
+
+    for markup in links.iter().flat_map(|link| link.markup()) {
+        stripped.push_str(&text[index..markup.start]);
+        index = markup.end;
+    }
+    stripped.push_str(&text[index..]);
+
+    stripped
+}
+
+/// Map a byte offset within a text onto the text with `edits`, byte ranges 
+/// and their replacements in order, applied. Offsets within a replaced 
+/// range map to the start of its replacement.
+fn edit_offset(offset: usize, edits: &[(Range<usize>, String)]) -> usize {
+    edits.iter().fold(offset, |mapped, (range, replacement)| {
+        if range.end <= offset {
+            mapped + replacement.len() - range.len()
+        } else if range.start < offset {
+            mapped - (offset - range.start)
+        } else {
+            mapped
+        }
+    })
+}
+
+/// Replace byte ranges of a text, `edits` in order.
+fn apply_edits(text: &str, edits: &[(Range<usize>, String)]) -> String {
+    let mut edited = String::with_capacity(text.len());
+    let mut index = 0;
+
+    for (range, replacement) in edits {
+        edited.push_str(&text[index..range.start]);
+        edited.push_str(replacement);
+        index = range.end;
+    }
+    edited.push_str(&text[index..]);
+
+    edited
+}
+
+/// Set the text of a [`pango::Layout`] from an [`InlineContent`], with its
+/// bold and italic spans, its links reduced to styled labels, and its 
+/// figure references resolved.
+///
+/// # Returns
+///
+/// The links within the layout text.
+pub fn set_inline_content(layout: &Layout,
+    inline_content: &InlineContent,
+    link_config: &LinkConfig,
+    figures: &Figures
+) -> Vec<LayoutLink> {
+    set_prefixed_content(layout, "", inline_content, link_config, figures)
+}
+
+/// As [`set_inline_content()`], with `prefix`, plain text such as a figure 
+/// label, put before the text.
+pub fn set_prefixed_content(layout: &Layout,
+    prefix: &str,
+    inline_content: &InlineContent,
+    link_config: &LinkConfig,
+    figures: &Figures
+) -> Vec<LayoutLink> {
+    let text = inline_content.text();
+    let markdown_links = markdown_links(text);
+    let stripped = strip_links(text, &markdown_links);
+
+    let mut edits: Vec<(Range<usize>, String)> = Vec::new();
+    if !prefix.is_empty() {
+        edits.push((0..0, prefix.to_string()));
+    }
+    edits.extend(figures.resolve_references(&stripped));
+
+    let map = |offset: usize| edit_offset(map_offset(offset, &markdown_links), &edits);
+
+    let attrs = AttrList::new();
+    for span in inline_content.spans() {
+        for attr in create_span_attrs(span.style(), map(span.start()), map(span.end())) {
+            attrs.insert(attr);
+        }
+    }
+
+    let links: Vec<LayoutLink> = markdown_links.iter().map(|link| LayoutLink {
+        range: map(link.start + 1)..map(link.label_end),
+        url: link.url.clone(),
+    }).collect();
+
+    for link in &links {
+        for attr in create_link_attrs(link.range.start, link.range.end, link_config) {
+            attrs.insert(attr);
+        }
+    }
+
+    layout.set_attributes(Some(&attrs));
+    layout.set_text(&apply_edits(&stripped, &edits));
+
+    links
+}
+
+/// The byte index of the last character before `index`.
+fn last_char_index(text: &str, index: usize) -> usize {
+    text[..index].char_indices().last().map_or(0, |(i, _)| i)
+}
+
+/// Emit a Cairo `Link` tag covering the given rectangles, `(x, y, width,
+/// height)` in page coordinates.
+///
+/// # Arguments
+///
+/// * `destination` — the link tag attributes naming where the link goes:
+///   `uri='...'` for an external link, `page=... pos=[...]` for an internal
+///   one.
+///
+fn tag_link(context: &Context, destination: &str, rects: &[(f64, f64, f64, f64)]) {
+    if rects.is_empty() { return; }
+
+    let rects: Vec<String> = rects.iter()
+        .map(|(x, y, width, height)| format!("{x} {y} {width} {height}"))
+        .collect();
+
+    context.tag_begin(CAIRO_TAG_LINK, &format!("{destination} rect=[{}]", rects.join(" ")));
+    context.tag_end(CAIRO_TAG_LINK);
+}
+
+/// Emit a Cairo `Link` tag to a URL.
+fn tag_uri_link(context: &Context, url: &str, rects: &[(f64, f64, f64, f64)]) {
+    let url = url.replace('\\', "\\\\").replace('\'', "\\'");
+    tag_link(context, &format!("uri='{url}'"), rects);
+}
+
+/// Emit a Cairo `Link` tag to a position within the document: `page` is
+/// 1-based, `(x, y)` is in page coordinates.
+pub fn tag_page_link(context: &Context,
+    page: usize,
+    x: f64,
+    y: f64,
+    rect: (f64, f64, f64, f64)
+) {
+    tag_link(context, &format!("page={page} pos=[{x} {y}]"), &[rect]);
+}
+
+/// Tag the links within lines `lines` of a [`pango::Layout`] drawn line by
+/// line, as [`crate::text_layout::show_lines()`] does: the first line's
+/// baseline at `(x, y_offset)`, each following line advanced by its
+/// precomputed height.
+pub fn tag_line_links(context: &Context,
+    layout: &Layout,
+    links: &[LayoutLink],
+    line_heights: &[f64],
+    x: f64,
+    y_offset: f64,
+    lines: Range<usize>,
+) {
+    if links.is_empty() { return; }
+
+    let text = layout.text();
+    let scale = pango::SCALE as f64;
+
+    for link in links {
+        let mut rects: Vec<(f64, f64, f64, f64)> = Vec::new();
+        let mut baseline = y_offset;
+
+        for line_index in lines.clone() {
+            let Some(line) = layout.line(line_index as i32) else { continue; };
+
+            let line_start = line.start_index() as usize;
+            let line_end = line_start + line.length() as usize;
+            let start = link.range.start.max(line_start);
+            let end = link.range.end.min(line_end);
+
+            if start < end {
+                let (_ink, logical) = line.extents();
+                let x_start = line.index_to_x(start as i32, false) as f64 / scale;
+                let x_end = line.index_to_x(last_char_index(&text, end) as i32, true) as f64 / scale;
+
+                rects.push((x + x_start, baseline + logical.y() as f64 / scale,
+                    x_end - x_start, logical.height() as f64 / scale));
+            }
+
+            baseline += line_heights[line_index];
+        }
+
+        tag_uri_link(context, &link.url, &rects);
+    }
+}
+
+/// The links within the text from byte `at` on, with their ranges moved 
+/// back by `at`, for a layout holding the end of the text. A link across 
+/// `at` keeps its part after it.
+pub fn links_from(links: &[LayoutLink], at: usize) -> Vec<LayoutLink> {
+    links.iter()
+        .filter(|link| link.range.end > at)
+        .map(|link| LayoutLink {
+            range: link.range.start.saturating_sub(at)..link.range.end - at,
+            url: link.url.clone(),
+        })
+        .collect()
+}
+
+/// Tag the links within a whole [`pango::Layout`] drawn with its top left
+/// corner at `(x, y)`, as [`pangocairo::functions::show_layout()`] does.
+pub fn tag_layout_links(context: &Context,
+    layout: &Layout,
+    links: &[LayoutLink],
+    x: f64,
+    y: f64,
+) {
+    if links.is_empty() { return; }
+
+    let text = layout.text();
+    let scale = pango::SCALE as f64;
+
+    for link in links {
+        let mut rects: Vec<(f64, f64, f64, f64)> = Vec::new();
+
+        for line in layout.lines_readonly() {
+            let line_start = line.start_index() as usize;
+            let line_end = line_start + line.length() as usize;
+            let start = link.range.start.max(line_start);
+            let end = link.range.end.min(line_end);
+
+            if start < end {
+                let first = layout.index_to_pos(start as i32);
+                let last = layout.index_to_pos(last_char_index(&text, end) as i32);
+
+                rects.push((x + first.x() as f64 / scale, y + first.y() as f64 / scale,
+                    (last.x() + last.width() - first.x()) as f64 / scale,
+                    first.height() as f64 / scale));
+            }
+        }
+
+        tag_uri_link(context, &link.url, &rects);
+    }
+}
+
+// To run test for this module only:
+//
+//     * cargo test link_layout::tests
+//
+//     * cargo test link_layout::tests::test_markdown_links -- --exact [--nocapture]
+//     * cargo test link_layout::tests::test_strip_links -- --exact [--nocapture]
+//     * cargo test link_layout::tests::test_links_from -- --exact [--nocapture]
+//     * cargo test link_layout::tests::test_apply_edits -- --exact [--nocapture]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_markdown_links() {
+        let text = "See [Cairo](https://cairographics.org) and ![image](a.png), [no url]() \
+            or [spaced](a b) [x](y)";
+
+        let links = markdown_links(text);
+
+        assert_eq!(links.len(), 2, "link count");
+        assert_eq!(&text[links[0].start..links[0].end], "[Cairo](https://cairographics.org)");
+        assert_eq!(links[0].url, "https://cairographics.org");
+        assert_eq!(&text[links[1].start..links[1].end], "[x](y)");
+    }
+
+    #[test]
+    fn test_strip_links() {
+        let text = "Đọc [bài viết](https://a.vn) và [Pango](p)!";
+        let links = markdown_links(text);
+
+        let stripped = strip_links(text, &links);
+        assert_eq!(stripped, "Đọc bài viết và Pango!");
+
+        // A bold span over "Pango" in the Markdown text.
+        let start = text.find("Pango").unwrap();
+        let (start, end) = (map_offset(start, &links), map_offset(start + 5, &links));
+        assert_eq!(&stripped[start..end], "Pango");
+
+        // The label of the first link.
+        let label = map_offset(links[0].start + 1, &links)..map_offset(links[0].label_end, &links);
+        assert_eq!(&stripped[label], "bài viết");
+
+        // Offsets within the markup map to where it was.
+        assert_eq!(map_offset(links[0].label_end + 3, &links), "Đọc bài viết".len());
+    }
+
+    #[test]
+    fn test_apply_edits() {
+        let text = "Xem @fig:a, và @fig:b.";
+        let edits = vec![
+            (0..0, "Hình 1: ".to_string()),
+            (4..10, "Hình 2".to_string()),
+            (16..22, "?".to_string()),
+        ];
+
+        let edited = apply_edits(text, &edits);
+        assert_eq!(edited, "Hình 1: Xem Hình 2, và ?.");
+
+        // A span over "Xem" moves after the prefix.
+        let xem = edit_offset(0, &edits)..edit_offset(3, &edits);
+        assert_eq!(&edited[xem], "Xem");
+
+        // A span over a reference covers its replacement.
+        let reference = edit_offset(4, &edits)..edit_offset(10, &edits);
+        assert_eq!(&edited[reference], "Hình 2");
+
+        // Offsets within a replaced range map to the start of its replacement.
+        assert_eq!(edit_offset(19, &edits), edited.find('?').unwrap());
+        assert_eq!(&edited[edit_offset(22, &edits)..], ".");
+    }
+
+    #[test]
+    fn test_links_from() {
+        let link = |range: Range<usize>, url: &str| LayoutLink { range, url: url.to_string() };
+        let links = vec![link(0..5, "a"), link(8..12, "b"), link(15..20, "c")];
+
+        assert_eq!(links_from(&links, 0), links);
+        assert_eq!(links_from(&links, 10), vec![link(0..2, "b"), link(5..10, "c")]);
+        assert_eq!(links_from(&links, 12), vec![link(3..8, "c")]);
+        assert_eq!(links_from(&links, 20), vec![]);
+    }
+}
@@ -0,0 +1,282 @@
+/* 17/10/2026 */
+
+//!
+//! Layout ordered and unordered lists onto a PDF. Each item has a hanging
+//! indent: its marker, a bullet or a number, sits in the indentation to the
+//! left of the item text, and the wrapped lines of the item text align under
+//! its first line. Every nesting level adds one more indentation.
+//!
+//! An item is paginated like a paragraph, so a long item can break across
+//! pages. Its marker is drawn with its first line.
+//!
+
+use cairo::Context;
+use pango::{Layout, WrapMode};
+use pangocairo::functions::{create_layout, show_layout_line};
+
+use crate::column_layout::FrameCursor;
+use crate::config::{Config, ListConfig, ListNumbering};
+use crate::document::{ListBlock, ListItem, PositionedBlock};
+use crate::figure_numbering::Figures;
+use crate::link_layout::{LayoutLink, set_inline_content, tag_line_links};
+use crate::page_geometry::PageGeometry;
+use crate::text_layout::{
+    create_text_layout,
+    layout_width,
+    measure_line_height,
+    paginate_lines,
+    set_layout_language,
+    MinLines,
+    show_lines,
+};
+
+/// The cached [`pango::Layout`]s of a list item, reused for both measurement
+/// and rendering.
+#[derive(Debug)]
+pub struct PreparedListItem {
+    /// The bullet or number.
+    marker_layout: Layout,
+    /// Distance from the left margin to the marker.
+    marker_indent: f64,
+    /// The item text.
+    layout: Layout,
+    /// Distance from the left margin to the item text.
+    text_indent: f64,
+    /// The computed line heights for each line within `layout`.
+    line_heights: Vec<f64>,
+    /// The links within the `layout` text.
+    links: Vec<LayoutLink>,
+}
+
+impl PreparedListItem {
+    pub fn line_heights(&self) -> &[f64] {
+        &self.line_heights
+    }
+}
+
+/// `1 → a`, `26 → z`, `27 → aa`, ... `0` has no letter equivalent and stays
+/// a decimal.
+fn to_alpha(number: u32) -> String {
+    if number == 0 { return number.to_string(); }
+
+    let mut letters: Vec<char> = Vec::new();
+    let mut n = number;
+    while n > 0 {
+        n -= 1;
+        letters.push((b'a' + (n % 26) as u8) as char);
+        n /= 26;
+    }
+
+    letters.iter().rev().collect()
+}
+
+/// Lower case Roman numerals. Numbers outside `1..4000` stay decimals.
+fn to_roman(number: u32) -> String {
+    if number == 0 || number >= 4000 { return number.to_string(); }
+
+    const NUMERALS: [(u32, &str); 13] = [
+        (1000, "m"), (900, "cm"), (500, "d"), (400, "cd"),
+        (100, "c"), (90, "xc"), (50, "l"), (40, "xl"),
+        (10, "x"), (9, "ix"), (5, "v"), (4, "iv"), (1, "i"),
+    ];
+
+    let mut roman = String::new();
+    let mut n = number;
+    for (value, numeral) in NUMERALS {
+        while n >= value {
+            roman.push_str(numeral);
+            n -= value;
+        }
+    }
+
+    roman
+}
+
+/// Format an item number in one of the `[list]` numbering styles.
+pub fn format_list_number(number: u32, numbering: ListNumbering) -> String {
+    match numbering {
+        ListNumbering::Decimal => number.to_string(),
+        ListNumbering::LowerAlpha => to_alpha(number),
+        ListNumbering::UpperAlpha => to_alpha(number).to_uppercase(),
+        ListNumbering::LowerRoman => to_roman(number),
+        ListNumbering::UpperRoman => to_roman(number).to_uppercase(),
+    }
+}
+
+/// The bullet or number text of a list item.
+fn marker_text(item: &ListItem, list_config: &ListConfig) -> String {
+    if item.ordered() {
+        format!("{}{}", format_list_number(item.number(),
+            list_config.numbering(item.level())), list_config.number_suffix())
+    } else {
+        list_config.bullet(item.level()).to_string()
+    }
+}
+
+/// Create the marker and text [`pango::Layout`]s for each item of a list.
+///
+/// The item text of nesting level `n` is indented `(n + 1) * indent` from
+/// the left margin, its layout is narrowed by the same amount. The marker
+/// is right-aligned against the item text, `marker_gap` to its left.
+///
+/// # Arguments
+///
+/// * `list` — the semantic list.
+///
+/// * `config` — the paragraph font and the `[list]` configuration.
+///
+/// * `page_geometry` — the page size and margins in effect.
+///
+/// * `context` — the Cairo PDF [`Context`].
+///
+/// * `figures` — resolves the figure references in the item text.
+///
+pub fn prepare_list_items(list: &ListBlock,
+    config: &Config,
+    page_geometry: &PageGeometry,
+    context: &Context,
+    figures: &Figures
+) -> Vec<PreparedListItem> {
+    let list_config = config.list();
+    let font_spec = config.fonts().paragraph();
+
+    list.items().iter().map(|item| {
+        let text_indent = (item.level() + 1) as f64 * list_config.indent();
+        let text_width = (page_geometry.content_width() - text_indent).max(1.0);
+
+        let layout: Layout = create_layout(context);
+        layout.set_width(layout_width(text_width));
+        layout.set_wrap(WrapMode::WordChar);
+        layout.set_justify(true);
+        layout.set_font_description(Some(&font_spec.to_pango_description()));
+        set_layout_language(&layout, config.metadata().language());
+
+        let links = match item.content() {
+            Some(content) => set_inline_content(&layout, content, config.link(), figures),
+            None => Vec::new(),
+        };
+
+        let line_heights = (0..layout.line_count())
+            .map(|i| measure_line_height(i, &layout))
+            .collect();
+
+        // A width of -1 means no wrapping.
+        let marker_layout = create_text_layout(-1,
+            &marker_text(item, list_config), font_spec, false, context);
+        let (_, logical) = marker_layout.extents();
+        let marker_width = logical.width() as f64 / pango::SCALE as f64;
+
+        PreparedListItem {
+            marker_layout,
+            marker_indent: text_indent - list_config.marker_gap() - marker_width,
+            layout,
+            text_indent,
+            line_heights,
+            links,
+        }
+    }).collect()
+}
+
+/// Paginate a list: each item is split into [`PositionedBlock::ListItem`]
+/// fragments the same way a paragraph is.
+///
+/// # Arguments
+///
+/// * `items` — the prepared items of the list.
+///
+/// * `block_index` — index to the semantic list block.
+///
+/// * `pos_blocks` — the fragments are appended to this vector.
+///
+/// * `cursor` — the pagination state, updated to just after the list,
+///   including its spacing after.
+///
+/// * `config` — list spacing.
+///
+/// * `page_geometry` — the page size and margins in effect.
+///
+pub fn measure_list_block(items: &[PreparedListItem],
+    block_index: usize,
+    pos_blocks: &mut Vec<PositionedBlock>,
+    cursor: &mut FrameCursor,
+    config: &Config,
+    page_geometry: &PageGeometry,
+) {
+    cursor.y += cursor.collapse_spacing(config.block_spacing().list().before(), cursor.y);
+
+    for (item_index, item) in items.iter().enumerate() {
+        if item_index > 0 { cursor.y += config.list().item_spacing(); }
+
+        paginate_lines(item.line_heights(), cursor, page_geometry,
+            MinLines::default(), |frame, y_offset, line_start, line_end| {
+                pos_blocks.push(PositionedBlock::list_item(block_index, item_index,
+                    frame, y_offset, line_start, line_end));
+            });
+    }
+
+    cursor.y += config.block_spacing().list().after();
+    cursor.y_offset = cursor.y;
+}
+
+/// Render a [`PositionedBlock::ListItem`] fragment: the marker too when the
+/// fragment holds the first line of the item.
+pub fn render_list_item(item: &PreparedListItem,
+    y_offset: f64,
+    line_start: usize,
+    line_end: usize,
+    context: &Context,
+    page_geometry: &PageGeometry,
+) {
+    let left = page_geometry.margins().left;
+
+    if line_start == 0 && let Some(line) = item.marker_layout.line(0) {
+        context.move_to(left + item.marker_indent, y_offset);
+        show_layout_line(context, &line);
+    }
+
+    show_lines(context, &item.layout, &item.line_heights, left + item.text_indent,
+        y_offset, line_start, line_end);
+    tag_line_links(context, &item.layout, &item.links, &item.line_heights,
+        left + item.text_indent, y_offset, line_start..line_end);
+}
+
+// To run test for this module only:
+//
+//     * cargo test list_layout::tests
+//
+//     * cargo test list_layout::tests::test_format_list_number -- --exact [--nocapture]
+//     * cargo test list_layout::tests::test_list_numbering_config -- --exact [--nocapture]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_list_number() {
+        let test_data: Vec<(u32, ListNumbering, &str)> = vec![
+            (7, ListNumbering::Decimal, "7"),
+            (1, ListNumbering::LowerAlpha, "a"),
+            (26, ListNumbering::LowerAlpha, "z"),
+            (28, ListNumbering::UpperAlpha, "AB"),
+            (4, ListNumbering::LowerRoman, "iv"),
+            (1994, ListNumbering::UpperRoman, "MCMXCIV"),
+            (0, ListNumbering::LowerRoman, "0"),
+        ];
+
+        for (number, numbering, expected) in test_data {
+            assert_eq!(format_list_number(number, numbering), expected,
+                "{number} as {numbering:?}");
+        }
+    }
+
+    #[test]
+    fn test_list_numbering_config() {
+        let list_config: ListConfig = toml::from_str("numbering = [\"upper_roman\", \"lower_alpha\"]")
+            .expect("Failed to load test list config string");
+        assert_eq!(list_config.numbering(0), ListNumbering::UpperRoman, "level 0");
+        // Deeper levels repeat the last style.
+        assert_eq!(list_config.numbering(3), ListNumbering::LowerAlpha, "level 3");
+
+        assert!(toml::from_str::<ListConfig>("numbering = [\"greek\"]").is_err(),
+            "an unknown numbering style is rejected");
+    }
+}
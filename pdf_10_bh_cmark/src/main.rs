@@ -1,22 +1,16 @@
 /* 03/06/2026. */
 
 use std::{fs, process};
+use std::path::Path;
 
-use bh_cmark::{
-	ast::{AstBlock, InlineContent}, parser::parser::Parser, scanner::Scanner
-};
+use bh_cmark::ast::{AstBlock, InlineContent};
 
 use cairo::{Context, PdfSurface, ImageSurface};
 use pango::{Layout, WrapMode};
 use pangocairo::functions::*;
 
 mod page_geometry;
-use page_geometry::{
-    A4,
-    A4_DEFAULT_MARGINS,
-    a4_default_content_width,
-    a4_default_content_height,
-};
+use page_geometry::PageGeometry;
 
 mod config;
 use config::{
@@ -24,19 +18,122 @@ use config::{
     Config,
     FontSpec,
     FontConfig,
+    ThematicAtPageTop,
 };
 
 mod document;
-use document::PositionedBlock;
+use document::{Block, Frame, PositionedBlock};
 
 mod font_utils;
-use font_utils::create_font_attrs;
+
+mod link_layout;
+use link_layout::{LayoutLink, set_inline_content, set_prefixed_content, tag_line_links};
 
 mod text_layout;
-use crate::text_layout::a4_layout_width;
+use crate::text_layout::{
+    layout_width, 
+    set_layout_language,
+    measure_line_height,
+    paginate_lines,
+    show_lines,
+    MinLines,
+};
+
+mod block_parser;
+use block_parser::parse_blocks;
+
+mod front_matter;
+use front_matter::split_front_matter;
+
+mod list_layout;
+use list_layout::{
+    PreparedListItem,
+    prepare_list_items,
+    measure_list_block,
+    render_list_item,
+};
+
+mod code_layout;
+use code_layout::{create_code_layout, measure_code_block, render_code_block};
+
+mod table_layout;
+use table_layout::{PreparedTable, prepare_table, measure_table, render_table_row};
+
+mod quote_layout;
+use quote_layout::{quote_depths, quote_geometry, quote_rules, render_quote_rule};
+
+mod outline;
+use outline::{OutlineEntry, add_outline};
+
+mod toc_layout;
+use toc_layout::{
+    PreparedToc,
+    insert_toc,
+    prepare_toc,
+    measure_toc,
+    render_toc_title,
+    render_toc_entry,
+};
+
+mod date_utils;
+
+mod pdf_metadata;
+use pdf_metadata::{creation_date, set_pdf_metadata};
+
+mod running_head;
+use running_head::{HeadingMark, running_heads};
+
+mod page_template;
+use page_template::{PageFields, render_page_templates};
+
+mod image_loader;
+use image_loader::{image_search_dirs, load_image_or_placeholder};
 
 mod image_layout;
-use image_layout::{measure_image_block, render_image_block};
+use image_layout::{align_caption, measure_image_block, render_image_block};
+
+mod figure_layout;
+use figure_layout::{PreparedFigureRow, prepare_figure_row, measure_figure_row, render_figure_row};
+
+mod float_layout;
+use float_layout::{
+    FloatWrap,
+    PreparedFloat,
+    prepare_float,
+    wrap_geometry,
+    wrap_paragraph,
+    show_wrapped_lines,
+    measure_float,
+    render_float,
+};
+
+mod figure_numbering;
+use figure_numbering::Figures;
+
+mod prepare_context;
+use prepare_context::PrepareContext;
+
+mod column_layout;
+use column_layout::{
+    ColumnSet,
+    FrameCursor,
+    block_columns,
+    column_offset,
+    place_whole_block,
+    render_column_rules,
+};
+
+mod cli;
+use cli::{
+    Command,
+    CliOptions,
+    parse_args,
+    USAGE,
+    EXIT_SUCCESS,
+    EXIT_DOCUMENT_ERROR,
+    EXIT_USAGE_ERROR,
+    EXIT_CONFIG_ERROR,
+};
 
 /// `pango::Layout` computation caching:
 ///     - the shaped Pango layout
@@ -57,6 +154,8 @@ enum PreparedBlock {
         layout: Layout,
         /// The computed line heights for each line within `layout`.
         line_heights: Vec<f64>,
+        /// The links within the `layout` text.
+        links: Vec<LayoutLink>,
     },
     Paragraph {
         /// Index to the original semantic `AstBlock`.
@@ -65,14 +164,25 @@ enum PreparedBlock {
         layout: Layout,
         /// The computed line heights for each line within `layout`.
         line_heights: Vec<f64>,
+        /// The links within the `layout` text.
+        links: Vec<LayoutLink>,
+        /// The lines beside a floating image. `layout`, `line_heights` and 
+        /// `links` then hold the rest of the paragraph, see [`float_layout`].
+        wrap: Option<FloatWrap>,
     },
     Image {
         /// Index to the original semantic `AstBlock`.
         block_index: usize,
         /// Actual caption text can be blank: treated as a non-blank string.
         caption_layout: Layout,
-        /// The actual decoded PNG.
+        /// The decoded PNG, or a blank surface carrying the original JPEG.
         image_surface: ImageSurface,
+        /// The resolution recorded in the image file, in pixels per inch.
+        dpi: Option<f64>,
+        /// The path and the error of an image which could not be loaded, 
+        /// shown in a frame in its place. `image_surface` is then a blank 
+        /// stand-in.
+        placeholder: Option<String>,
     },
     Thematic {
         /// Index to the original semantic `AstBlock`.
@@ -81,33 +191,63 @@ enum PreparedBlock {
         ///     `padding_top + stroke_width + padding_bottom`.
         block_height: f64,
     },
+    List {
+        /// Index to the original semantic `Block::List`.
+        block_index: usize,
+        /// The cached layouts of each item, nested items included.
+        items: Vec<PreparedListItem>,
+    },
+    Code {
+        /// Index to the original semantic `Block::Code`.
+        block_index: usize,
+        /// The cached monospace `pango::Layout`.
+        layout: Layout,
+        /// The computed line heights for each line within `layout`.
+        line_heights: Vec<f64>,
+    },
+    Table {
+        /// Index to the original semantic `Block::Table`.
+        block_index: usize,
+        /// The cached cell layouts and column widths.
+        table: PreparedTable,
+    },
+    QuoteStart {
+        /// Index to the original semantic `Block::QuoteStart`.
+        block_index: usize,
+    },
+    QuoteEnd {
+        /// Index to the original semantic `Block::QuoteEnd`.
+        block_index: usize,
+    },
+    Toc {
+        /// Index to the original semantic `Block::Toc`.
+        block_index: usize,
+        /// The cached entry layouts, refilled on each pagination pass.
+        toc: PreparedToc,
+    },
+    FigureRow {
+        /// Index to the original semantic `Block::FigureRow`.
+        block_index: usize,
+        /// The loaded images and their caption layouts.
+        row: PreparedFigureRow,
+    },
+    FloatImage {
+        /// Index to the original semantic `Block::FloatImage`.
+        block_index: usize,
+        /// The loaded image and its caption layout.
+        float: PreparedFloat,
+    },
+    Columns {
+        /// Index to the original semantic `Block::Columns`.
+        block_index: usize,
+    },
 }
 
-fn page_number(context: &Context, 
-    page_no: usize,
-    total_pages: usize,
-    page_width: f64,
-    page_height: f64,
-    font_config: &FontConfig
-) {
-    // Draw page number centered at bottom
-    let footer_layout = create_layout(context);
-
-    footer_layout.set_text(&format!("{} of {}", page_no, total_pages));
-    footer_layout.set_font_description(Some(&font_config.page_number().to_pango_description()));
-
-    // Measure width of the page number
-    let (ink, _) = footer_layout.extents();
-    let text_width = ink.width() as f64 / pango::SCALE as f64;
+/// The most pagination passes made to settle the page numbers in a table 
+/// of contents and in figure references.
+const PAGINATION_PASSES: usize = 4;
 
-    let x = ((page_width - text_width) / 2.0) + A4_DEFAULT_MARGINS.left;
-    let y = page_height - A4_DEFAULT_MARGINS.bottom;
-
-    context.move_to(x, y);
-    show_layout(&context, &footer_layout);
-}
-
-/// Read the Markdown text file, parse and turn each line into [`Block`] enum 
+/// Read the Markdown text file, parse and turn it into [`Block`] enum 
 /// representations.
 /// 
 /// # Arguments
@@ -116,31 +256,24 @@ fn page_number(context: &Context,
 /// 
 /// # Returns
 /// 
-/// * [`Vec<Block>`] — the [`Block`] enum representations for each text line in 
-///   the Markdown text file.
+/// * [`toml::Table`] — the configuration overrides from the front matter, 
+///   `None` when the file has none. See [`front_matter`].
+/// 
+/// * [`Vec<Block>`] — the [`Block`] enum representations of the Markdown 
+///   text file.
 /// 
-/// * [`std::error::Error`] — if some error occurs during file opening and reading.
+/// * [`std::error::Error`] — if some error occurs during file opening and reading,
+///   or the front matter is not valid.
 /// 
-fn parse_blocks_from_file(file_name: &str) -> Result<Vec<AstBlock>, Box<dyn std::error::Error>> {
+fn parse_blocks_from_file(
+    file_name: &Path
+) -> Result<(Option<toml::Table>, Vec<Block>), Box<dyn std::error::Error>> {
     // Read input text file.
     let text = fs::read_to_string(file_name)?;
 
-	let mut scanner = Scanner::new(&text);
-    let tokens = match scanner.scan_tokens() {
-		Ok(tokens) => tokens,
-		Err(err) => {
-			return Err(err.into());
-		}
-	};
+    let (front_matter, text) = split_front_matter(&text)?;
 
-    let mut parser = Parser::new(&tokens);
-    let parse_output = parser.parse();
-	
-	if parse_output.has_error() {
-		return Err(parse_output.errors().join("\n").into())
-	}
-
-    Ok(parse_output.into_blocks())
+    Ok((front_matter, parse_blocks(text)?))
 }
 
 fn block_font<'a>(block: &'a AstBlock, font_config: &'a FontConfig) -> &'a FontSpec {
@@ -156,284 +289,640 @@ fn block_font<'a>(block: &'a AstBlock, font_config: &'a FontConfig) -> &'a FontS
     }
 }
 
-fn measure_line_height(line_index: i32, layout: &Layout) -> f64 {
-    if let Some(line) = layout.line(line_index) {
-        let (_ink, logical) = line.extents();
-        logical.height() as f64 / pango::SCALE as f64
-    } else {
-        panic!("measure_line_height: layout.line({line_index}) returned None");
-
-        // eprintln!("Warning: layout.line({line_index}) returned None");
-        // 0.0
-    }
-}
-
-/// Text layout for each [`AstBlock`] enum.
+/// Text layout for each [`AstBlock`] enum, and the links within its text. 
+/// An image caption starts with its figure label, see [`figure_numbering`].
 fn create_layout_for_block(block: &AstBlock, 
-    font_config: &FontConfig, 
+    block_index: usize,
+    figures: &Figures,
+    config: &Config, 
+    page_geometry: &PageGeometry,
     context: &Context
-) -> Layout {
+) -> (Layout, Vec<LayoutLink>) {
     let layout: Layout = create_layout(context);
     
     // Set width, wrap, justify
-    layout.set_width(a4_layout_width());
+    layout.set_width(layout_width(page_geometry.content_width()));
     layout.set_wrap(WrapMode::WordChar);
     layout.set_justify(true);
     
-    let font_spec = block_font(block, font_config);
+    let font_spec = block_font(block, config.fonts());
     layout.set_font_description(Some(&font_spec.to_pango_description()));
+    set_layout_language(&layout, config.metadata().language());
 
     let display_text = |inline_content: &InlineContent| {
-        let attrs = pango::AttrList::new();
-        for span in inline_content.spans() {
-            for attr in create_font_attrs(span) {
-                attrs.insert(attr);
+        set_inline_content(&layout, inline_content, config.link(), figures)
+    };
+
+    let links = match block {
+        AstBlock::Header { level: _, content } => display_text(content),
+        AstBlock::Paragraph { content } => display_text(content),
+        AstBlock::Image { path: _, alt } => set_prefixed_content(&layout, 
+            &figures.caption_prefix(block_index, 0, alt.text()), alt, config.link(), figures),
+        _ => Vec::new(),
+    };
+
+    (layout, links)
+}
+
+/// Convert a semantic [`AstBlock`] into its [`PreparedBlock`] equivalent.
+/// 
+/// Image paths are resolved against `prepare.image_dirs()`, see 
+/// [`image_loader`]. An image which cannot be loaded is replaced by a 
+/// placeholder, and the problem added to `warnings`.
+fn prepare_ast_block(
+    block_index: usize,
+    block: &AstBlock,
+    config: &Config,
+    page_geometry: &PageGeometry,
+    prepare: &PrepareContext,
+    warnings: &mut Vec<String>
+) -> PreparedBlock {
+    let (layout, links) = create_layout_for_block(block, block_index, prepare.figures(), 
+        config, page_geometry, prepare.context());
+
+    match block {
+        AstBlock::Header { level, content: _ } => {
+            let line_heights = (0..layout.line_count())
+                .map(|i| measure_line_height(i, &layout))
+                .collect();
+
+            PreparedBlock::Header {
+                block_index,
+                level: *level,
+                layout,
+                line_heights,
+                links,
+            }
+        },
+        AstBlock::Image { path, alt: _ } => {
+            let (image_surface, dpi, placeholder) = 
+                load_image_or_placeholder(path, prepare.image_dirs(), warnings);
+
+            align_caption(&layout, config);
+
+            PreparedBlock::Image {
+                block_index,
+                caption_layout: layout, 
+                image_surface: image_surface,
+                dpi,
+                placeholder,
+            }
+        },
+        AstBlock::Thematic => {
+            PreparedBlock::Thematic {
+                block_index, 
+                block_height: config.block_spacing().thematic().before() +
+                    config.horizontal_break().stroke_width() + 
+                    config.block_spacing().thematic().after()
+            }
+        },
+        _ => {
+            let line_heights = (0..layout.line_count())
+                .map(|i| measure_line_height(i, &layout))
+                .collect();
+
+            PreparedBlock::Paragraph {
+                block_index,
+                layout,
+                line_heights,
+                links,
+                wrap: None,
             }
         }
+    }
+}
 
-        layout.set_attributes(Some(&attrs));
-        layout.set_text(inline_content.text());
-    };
+/// Convert a semantic [`Block`] into its [`PreparedBlock`] equivalent. See 
+/// [`prepare_ast_block()`].
+fn prepare_block(
+    block_index: usize,
+    block: &Block,
+    config: &Config,
+    page_geometry: &PageGeometry,
+    prepare: &PrepareContext,
+    warnings: &mut Vec<String>
+) -> PreparedBlock {
+    let i = block_index;
+    let (figures, context) = (prepare.figures(), prepare.context());
 
     match block {
-        AstBlock::Header { level: _, content } => display_text(content),
-        AstBlock::Paragraph { content } => display_text(content),
-        AstBlock::Image { path: _, alt } => 
-            display_text(alt),
-        _ => {}
-    }    
+        Block::Ast(block) => prepare_ast_block(i, block, config, page_geometry, prepare, 
+            warnings),
+        Block::List(list) => PreparedBlock::List {
+            block_index: i,
+            items: prepare_list_items(list, config, page_geometry, context, figures),
+        },
+        Block::Code(code) => {
+            let layout = create_code_layout(code, config, page_geometry, context);
+            let line_heights = (0..layout.line_count())
+                .map(|i| measure_line_height(i, &layout))
+                .collect();
 
-    layout
+            PreparedBlock::Code { block_index: i, layout, line_heights }
+        },
+        Block::Table(table) => PreparedBlock::Table {
+            block_index: i,
+            table: prepare_table(table, config, page_geometry, context, figures),
+        },
+        Block::QuoteStart => PreparedBlock::QuoteStart { block_index: i },
+        Block::QuoteEnd => PreparedBlock::QuoteEnd { block_index: i },
+        // The headings are not known yet.
+        Block::Toc => PreparedBlock::Toc {
+            block_index: i,
+            toc: prepare_toc(&[], config, page_geometry, context),
+        },
+        // A labelled image is laid out as any other.
+        Block::Image(figure) => {
+            let image = AstBlock::Image { 
+                path: figure.path().to_string(), 
+                alt: figure.caption().clone(),
+            };
+
+            prepare_ast_block(i, &image, config, page_geometry, prepare, warnings)
+        },
+        Block::FigureRow(row) => PreparedBlock::FigureRow {
+            block_index: i,
+            row: prepare_figure_row(row, i, config, page_geometry, prepare, warnings),
+        },
+        Block::FloatImage(float) => PreparedBlock::FloatImage {
+            block_index: i,
+            float: prepare_float(float, i, config, page_geometry, prepare, warnings),
+        },
+        Block::Columns(_) => PreparedBlock::Columns { block_index: i },
+    }
 }
 
-/// Convert semantic [`AstBlock`]s into their [`PreparedBlock`] equivalents.
+/// Convert semantic [`Block`]s into their [`PreparedBlock`] equivalents.
+/// 
+/// Blocks within a blockquote are laid out to their narrower page geometry, 
+/// in the `[quote]` font. Image paths are resolved against 
+/// `prepare.image_dirs()`. Problems which do not stop the document, such as 
+/// an image which cannot be loaded, are added to `warnings`.
 fn prepare_blocks(
-    blocks: &[AstBlock],
+    blocks: &[Block],
+    depths: &[usize],
+    block_geometries: &[PageGeometry],
     config: &Config,
-    context: &Context
+    prepare: &PrepareContext,
+    warnings: &mut Vec<String>
 ) -> Vec<PreparedBlock> {
     let mut prepared = Vec::new();
 
+    let quote_config = config.for_quote();
+
     for (i, block) in blocks.iter().enumerate() {
-        let layout = create_layout_for_block(block, config.fonts(), context);
+        let config = if depths[i] > 0 { &quote_config } else { config };
 
-        match block {
-            AstBlock::Header { level, content: _ } => {
-                let line_heights = (0..layout.line_count())
-                    .map(|i| measure_line_height(i, &layout))
-                    .collect();
-
-                prepared.push(PreparedBlock::Header {
-                    block_index: i,
-                    level: *level,
-                    layout,
-                    line_heights,
-                });
-            },
-            AstBlock::Image { path, alt: _ } => {
-                let mut img_file = fs::File::open(path)
-                    .unwrap_or_else(|_| panic!("Failed to open image PNG file: {}", path));
-
-                let image_surface = ImageSurface::create_from_png(&mut img_file)
-                    .map_err(|e| format!("Failed to create image surface from PNG: {}", e))
-                    .expect("Failed to decode PNG image");
-
-                prepared.push(PreparedBlock::Image {
-                    block_index: i,
-                    caption_layout: layout, 
-                    image_surface: image_surface,
-                });
-            },
-            AstBlock::Thematic => {
-                prepared.push(PreparedBlock::Thematic {
-                    block_index: i, 
-                    block_height: config.block_spacing().thematic().before() +
-                        config.horizontal_break().stroke_width() + 
-                        config.block_spacing().thematic().after()
-                });
-            },
-            _ => {
-                let line_heights = (0..layout.line_count())
-                    .map(|i| measure_line_height(i, &layout))
-                    .collect();
-
-                prepared.push(PreparedBlock::Paragraph {
-                    block_index: i,
-                    layout,
-                    line_heights,
-                });
-            }
-        }
+        prepared.push(prepare_block(i, block, config, &block_geometries[i], prepare, 
+            warnings));
     }
 
+    wrap_float_paragraphs(&mut prepared, block_geometries, config);
+
     prepared
 }
 
-fn header(pos_blocks: &mut Vec<PositionedBlock>, 
-    block_index: usize, 
-    current_page: usize, 
-    y_offset: f64, 
-    start_line: usize, 
-    line_index: usize
+/// Prepare again, with the figure pages now known, the blocks holding figure 
+/// references and the paragraphs beside floating images, then wrap the 
+/// paragraphs around the floats again.
+fn refresh_references(prepared: &mut [PreparedBlock],
+    blocks: &[Block],
+    depths: &[usize],
+    block_geometries: &[PageGeometry],
+    config: &Config,
+    prepare: &PrepareContext,
 ) {
-    pos_blocks.push(PositionedBlock::header(block_index, current_page, 
-        y_offset, start_line, line_index));
+    let quote_config = config.for_quote();
+    // Already reported when the blocks were first prepared.
+    let mut warnings: Vec<String> = Vec::new();
+
+    for (i, block) in blocks.iter().enumerate() {
+        let wrapped = matches!(prepared[i], PreparedBlock::Paragraph { wrap: Some(_), .. });
+        if !wrapped && prepare.figures().referring_blocks().binary_search(&i).is_err() { 
+            continue; 
+        }
+
+        let config = if depths[i] > 0 { &quote_config } else { config };
+
+        prepared[i] = prepare_block(i, block, config, &block_geometries[i], prepare, 
+            &mut warnings);
+    }
+
+    wrap_float_paragraphs(prepared, block_geometries, config);
 }
 
-fn paragraph(pos_blocks: &mut Vec<PositionedBlock>, 
-    block_index: usize, 
-    current_page: usize, 
-    y_offset: f64, 
-    start_line: usize, 
-    line_index: usize
+/// Split the paragraphs after each floating image into the lines beside it 
+/// and the lines below it, see [`float_layout`]. The wrap stops at the first 
+/// block which is not a paragraph, or at the first paragraph which is not 
+/// beside the float in full.
+fn wrap_float_paragraphs(prepared: &mut [PreparedBlock], 
+    block_geometries: &[PageGeometry], 
+    config: &Config
 ) {
-    pos_blocks.push(PositionedBlock::paragraph(block_index, current_page, 
-        y_offset, start_line, line_index));
+    let paragraph_spacing = config.block_spacing().paragraph();
+
+    for i in 0..prepared.len() {
+        let (before, after) = prepared.split_at_mut(i + 1);
+        let PreparedBlock::FloatImage { float, .. } = &before[i] else { continue; };
+
+        let (x_offset, width) = wrap_geometry(float, config, &block_geometries[i]);
+        let mut available_height = float.height();
+
+        for block in after.iter_mut() {
+            let PreparedBlock::Paragraph { layout, line_heights, links, wrap, .. } = block else {
+                break;
+            };
+
+            available_height -= paragraph_spacing.before();
+            *wrap = wrap_paragraph(layout, line_heights, links, available_height, 
+                x_offset, width);
+
+            match wrap {
+                Some(wrap) if wrap.lines() == line_heights.len() => available_height -= 
+                    line_heights.iter().sum::<f64>() + paragraph_spacing.after(),
+                _ => break,
+            }
+        }
+    }
 }
 
 /// At this point, both [`AstBlock::Header`] and [`AstBlock::Paragraph`] have 
 /// been normalised into [`Layout`] and lines within [`Layout`].
 /// 
 /// The only difference between these two is [`AstBlock::Header`] has `level`.
+/// 
+/// `spacing` is `(before, after)` the block. Returns the fragments of the 
+/// block, one for each frame it spans.
 fn text_block(line_heights: &[f64], 
-    pos_blocks: &mut Vec<PositionedBlock>, 
     block_index: usize, 
     level: u8, 
-    current_page: &mut usize, 
-    y: &mut f64, 
-    y_offset: &mut f64, 
-    layout: &Layout,
-    spacing_before: f64, 
-    spacing_after: f64
-) {
+    cursor: &mut FrameCursor, 
+    spacing: (f64, f64), 
+    min_lines: MinLines,
+    page_geometry: &PageGeometry
+) -> Vec<PositionedBlock> {
+    let (spacing_before, spacing_after) = spacing;
+    let mut pos_blocks: Vec<PositionedBlock> = Vec::new();
+
     // Start of a new block.
-    *y += spacing_before;
-    *y_offset = *y;
+    cursor.y += spacing_before;
 
-    let mut start_line: usize = 0;        
-    for (line_index, line_height) in line_heights.iter().enumerate() {
-        if *y + line_height > a4_default_content_height() {
-            // This AstBlock spans multiple PositionedBlocks.
+    paginate_lines(line_heights, cursor, page_geometry, min_lines,
+        |frame, y_offset, line_start, line_end| {
+            // This AstBlock can span multiple PositionedBlocks.
             if level > 0 {
-                header(pos_blocks, block_index, *current_page, 
-                    *y_offset, start_line, line_index);
+                pos_blocks.push(PositionedBlock::header(block_index, frame, 
+                    y_offset, line_start, line_end));
             } else {
-                paragraph(pos_blocks, block_index, *current_page, *y_offset, 
-                    start_line, line_index);
+                pos_blocks.push(PositionedBlock::paragraph(block_index, frame, 
+                    y_offset, line_start, line_end));
             }
+        });
 
-            start_line = line_index as usize;
-            *current_page += 1; 
-            *y = A4_DEFAULT_MARGINS.top;
-            *y_offset = *y; 
-            // Advance y so the next line does not overlap.
-            // `line_height` of the line that `line_index` points to.
-            *y += line_height;
-        } else {
-            *y += line_height;
-        }
-    }
+    // Next Block
+    cursor.y += spacing_after;
+    cursor.y_offset = cursor.y;
 
-    if level > 0 {
-        header(pos_blocks, block_index, *current_page, *y_offset, 
-            start_line, layout.line_count() as usize);
-    } else {
-        paragraph(pos_blocks, block_index, *current_page, *y_offset, 
-            start_line, layout.line_count() as usize);
-    }
+    pos_blocks
+}
 
-    // Next Block
-    *y += spacing_after;
-    *y_offset = *y;
+/// Whether the start of the blocks after a heading fits on the heading's 
+/// page: `lines` lines of the next block, or the whole of an image block.
+/// 
+/// # Arguments
+/// 
+/// * `next_blocks` — the blocks after the heading.
+/// 
+/// * `next_geometries` — the page geometries of `next_blocks`.
+/// 
+/// * `lines` — the fewest lines of the next block to keep with the heading.
+/// 
+/// * `y` — where the heading ends, including its spacing after.
+/// 
+/// * `config` — block spacing, orphan control and image scaling.
+/// 
+fn fits_after_heading(next_blocks: &[PreparedBlock],
+    next_geometries: &[PageGeometry],
+    lines: usize,
+    y: f64,
+    config: &Config
+) -> bool {
+    let (Some(next), Some(page_geometry)) = (next_blocks.first(), next_geometries.first()) else {
+        return true;
+    };
+
+    let block_spacing = config.block_spacing();
+    let first_lines = |line_heights: &[f64], count: usize| -> f64 {
+        line_heights.iter().take(count).sum()
+    };
+
+    // Fewer lines than the orphans would not stay on the page anyway.
+    let text_lines = lines.max(config.pagination().orphans());
+
+    let needed = match next {
+        PreparedBlock::Header { level, line_heights, .. } => 
+            block_spacing.heading().before(*level) + first_lines(line_heights, text_lines),
+        PreparedBlock::Paragraph { line_heights, .. } => 
+            block_spacing.paragraph().before() + first_lines(line_heights, text_lines),
+        PreparedBlock::List { items, .. } => block_spacing.list().before() 
+            + items.first().map_or(0.0, |item| first_lines(item.line_heights(), lines)),
+        PreparedBlock::Code { line_heights, .. } => block_spacing.code().before() 
+            + 2.0 * config.code_block().padding() + first_lines(line_heights, lines),
+        PreparedBlock::Table { table, .. } => 
+            block_spacing.table().before() + table.leading_height(),
+        PreparedBlock::Image { caption_layout, image_surface, dpi, .. } => {
+            // An image which cannot be placed anywhere is not a reason to move.
+            return measure_image_block(
+                (image_surface.width() as f64, image_surface.height() as f64), *dpi, caption_layout, y, 
+                block_spacing.image().before(), config, page_geometry)
+                .map_or(true, |measured_info| !measured_info.new_page());
+        },
+        PreparedBlock::FigureRow { row, .. } => {
+            return measure_figure_row(row, y, block_spacing.image().before(), config, 
+                page_geometry)
+                .map_or(true, |measured_info| !measured_info.new_page());
+        },
+        PreparedBlock::FloatImage { float, .. } => block_spacing.image().before() + float.height(),
+        PreparedBlock::QuoteStart { .. } => {
+            return fits_after_heading(&next_blocks[1..], &next_geometries[1..], lines, 
+                y + block_spacing.quote().before(), config);
+        },
+        PreparedBlock::Thematic { .. } | 
+        PreparedBlock::QuoteEnd { .. } | 
+        PreparedBlock::Toc { .. } | 
+        PreparedBlock::Columns { .. } => return true,
+    };
+
+    y + needed <= page_geometry.content_bottom()
 }
 
 /// Preparing [`PositionedBlock`] vector for pagination and rendering.
 /// The number of elements in this vector can be more than in the 
 /// [`AstBlock`] vector and [`PreparedBlock`] vector.
+/// 
+/// Blocks flow down a column, then into the next column, then onto the 
+/// next page: see [`column_layout`]. `block_columns` is the number of 
+/// columns of each block, see [`block_columns()`].
+/// 
+/// # Returns
+/// 
+/// The fragments, sorted by page, and the column sets for the column rules.
 fn measure_block(prepared_blocks: &[PreparedBlock], 
-    config: &Config
-) -> Result<Vec<PositionedBlock>, Box<dyn std::error::Error>> {
-    let mut current_page = 1;
-    let mut y = A4_DEFAULT_MARGINS.top;
-    let mut y_offset = A4_DEFAULT_MARGINS.top;
+    block_geometries: &[PageGeometry],
+    block_columns: &[usize],
+    config: &Config,
+    page_geometry: &PageGeometry
+) -> Result<(Vec<PositionedBlock>, Vec<ColumnSet>), Box<dyn std::error::Error>> {
+    let mut cursor = FrameCursor::new(block_columns.first().copied().unwrap_or(1), 
+        page_geometry);
 
     let mut pos_blocks: Vec<PositionedBlock> = Vec::new();
 
-    for block in prepared_blocks {
+    // Widow and orphan control for paragraphs and headers.
+    let min_lines = MinLines::new(config.pagination().orphans(), 
+        config.pagination().widows());
+
+    // Open blockquotes: the index of their first fragment, and where they start.
+    let mut open_quotes: Vec<(usize, Frame, f64)> = Vec::new();
+
+    // The frame of the last floating image, and where the blocks after the 
+    // paragraphs beside it start.
+    let mut float_bottom: Option<(Frame, f64)> = None;
+
+    // How far a block in the current column is from the first column.
+    let frame_offset = |cursor: &FrameCursor| {
+        column_offset(cursor.frame().column(), cursor.columns(), config.columns(), 
+            page_geometry)
+    };
+
+    for (i, block) in prepared_blocks.iter().enumerate() {
+        let page_geometry = &block_geometries[i];
+
+        // Only the paragraphs beside a float flow around it: any other block 
+        // starts below it.
+        if !matches!(block, PreparedBlock::Paragraph { wrap: Some(_), .. }) 
+            && let Some((float_frame, bottom)) = float_bottom.take()
+            && float_frame == cursor.frame() && cursor.y < bottom {
+            cursor.y = bottom;
+            cursor.y_offset = cursor.y;
+        }
+
+        // A change in the number of columns, or a block spanning them, starts 
+        // below everything above it on the page.
+        if block_columns[i] != cursor.columns() {
+            cursor.set_columns(block_columns[i]);
+        }
+
         match block {
-            PreparedBlock::Header { block_index, level, layout, line_heights } => {
+            PreparedBlock::Header { block_index, level, line_heights, .. } => {
                 let block_spacing = config.block_spacing().heading();
 
-                text_block(line_heights, &mut pos_blocks, *block_index, *level, 
-                    &mut current_page, &mut y, &mut y_offset, layout,
-                    block_spacing.before(*level), block_spacing.after(*level));
-            },
-            PreparedBlock::Paragraph { block_index, layout, line_heights } => {
-                let block_spacing = config.block_spacing().paragraph();
+                // Keep the heading with the start of the next block: move both 
+                // to the next frame when they do not fit together.
+                let keep_lines = config.pagination().keep_with_next(*level);
+                let heading_end = cursor.y + block_spacing.before(*level) 
+                    + line_heights.iter().sum::<f64>() + block_spacing.after(*level);
+
+                if keep_lines > 0 && cursor.y > cursor.frame().top() 
+                    && heading_end <= page_geometry.content_bottom()
+                    && !fits_after_heading(&prepared_blocks[i + 1..], &block_geometries[i + 1..], 
+                        keep_lines, heading_end, config) {
+                    cursor.next_frame_for(heading_end - cursor.y);
+                }
 
-                text_block(line_heights, &mut pos_blocks, *block_index, 0, 
-                    &mut current_page, &mut y, &mut y_offset, layout, 
-                    block_spacing.before(), block_spacing.after());
+                let spacing_before = cursor.collapse_spacing(block_spacing.before(*level), 
+                    cursor.y);
+
+                pos_blocks.extend(text_block(line_heights, *block_index, *level, &mut cursor, 
+                    (spacing_before, block_spacing.after(*level)), min_lines, page_geometry));
             },
-            PreparedBlock::Image { block_index, caption_layout, image_surface } => {
-                let measured_info = measure_image_block(
-                    image_surface.width() as f64, image_surface.height() as f64, 
-                    caption_layout, y_offset, config
-                )?;
-
-                // Work out the page for the image block.
-                let page_for_block = if measured_info.new_page() {
-                    current_page + 1
-                } else {
-                    current_page
-                };
-
-                // Remember the page placement for the image block.
-                pos_blocks.push(
-                    PositionedBlock::image(*block_index, page_for_block, measured_info)
-                );
+            PreparedBlock::Paragraph { block_index, line_heights, .. } => {
+                let block_spacing = config.block_spacing().paragraph();
 
-                // The code below matches the text‑block logic more closely.
-                // This ensures:
-                // 
-                // - the block is placed on the correct page.
-                // - `y` is correct for the next block.
-                // - `y_offset` is correct.
-                // - pagination state matches measurement state.
-                if measured_info.new_page() {
-                    current_page += 1;
-                    y = A4_DEFAULT_MARGINS.top + measured_info.block_height();
-                    y_offset = A4_DEFAULT_MARGINS.top;
-                } else {
-                    y = measured_info.block_top_y() + measured_info.block_height();
-                    y_offset = measured_info.block_top_y();
-                }
+                let spacing_before = cursor.collapse_spacing(block_spacing.before(), cursor.y);
 
-                // Guardrail when pagination logic is out of sync with measurement logic.
-                debug_assert!((measured_info.block_top_y() - y_offset).abs() < 0.1);
-                debug_assert!((y - (measured_info.block_top_y() + measured_info.block_height())).abs() < 0.1);
+                pos_blocks.extend(text_block(line_heights, *block_index, 0, &mut cursor, 
+                    (spacing_before, block_spacing.after()), min_lines, page_geometry));
+            },
+            PreparedBlock::Image { block_index, caption_layout, image_surface, dpi, .. } => {
+                let y_offset = cursor.y_offset;
+                let spacing_before = cursor.collapse_spacing(
+                    config.block_spacing().image().before(), y_offset);
+
+                // Work out the frame for the image block.
+                let measured_info = place_whole_block(&mut cursor, y_offset, spacing_before, 
+                    |y, spacing_before| measure_image_block(
+                        (image_surface.width() as f64, image_surface.height() as f64), *dpi, 
+                        caption_layout, y, spacing_before, config, page_geometry))?;
+
+                // Remember the frame placement for the image block.
+                pos_blocks.push(PositionedBlock::image(*block_index, cursor.frame(), 
+                    measured_info.shifted(frame_offset(&cursor))));
+
+                // Like a text block, `y` is just after the image block and 
+                // `y_offset` where it starts.
+                cursor.y_offset = measured_info.block_top_y();
+                cursor.y = cursor.y_offset + measured_info.block_height();
             },
             PreparedBlock::Thematic { block_index, block_height } => {
-                // The horizontal line are to be drawn on a new page: 
-                //     TO_DO: not desirable.
-                if y + *block_height > a4_default_content_height() {
-                    current_page += 1; 
-                    y = A4_DEFAULT_MARGINS.top;
-                    y_offset = y
+                // A horizontal line never begins a frame. At the top of a frame 
+                // there is nothing above it to separate from: it is left out.
+                if cursor.at_frame_start(cursor.y) {
+                    continue;
+                }
+
+                // When it does not fit, the frame break takes its place, unless 
+                // it is configured to be dropped.
+                if cursor.y + *block_height > page_geometry.content_bottom() {
+                    if config.pagination().thematic_at_page_top() == ThematicAtPageTop::PageBreak {
+                        cursor.next_frame();
+                    }
+                    continue;
                 }
 
                 pos_blocks.push( 
-                    PositionedBlock::thematic(*block_index, current_page, 
-                        y_offset + config.block_spacing().thematic().before())
+                    PositionedBlock::thematic(*block_index, cursor.frame(), 
+                        cursor.y_offset + config.block_spacing().thematic().before())
                 );
 
-                y += *block_height;
-            }
+                cursor.y += *block_height;
+            },
+            PreparedBlock::List { block_index, items } => {
+                measure_list_block(items, *block_index, &mut pos_blocks, 
+                    &mut cursor, config, page_geometry);
+            },
+            PreparedBlock::Code { block_index, line_heights, .. } => {
+                measure_code_block(line_heights, *block_index, &mut pos_blocks, 
+                    &mut cursor, config, page_geometry);
+            },
+            PreparedBlock::Table { block_index, table } => {
+                measure_table(table, *block_index, &mut pos_blocks, 
+                    &mut cursor, config, page_geometry);
+            },
+            PreparedBlock::QuoteStart { .. } => {
+                cursor.y += cursor.collapse_spacing(config.block_spacing().quote().before(), 
+                    cursor.y);
+                cursor.y_offset = cursor.y;
+
+                open_quotes.push((pos_blocks.len(), cursor.frame(), cursor.y));
+            },
+            PreparedBlock::QuoteEnd { block_index } => {
+                if let Some((first_fragment, start_frame, start_y)) = open_quotes.pop() {
+                    let rules = quote_rules(*block_index, &pos_blocks[first_fragment..], 
+                        start_frame, start_y, cursor.y, page_geometry);
+                    pos_blocks.extend(rules);
+                }
+
+                cursor.y += config.block_spacing().quote().after();
+                cursor.y_offset = cursor.y;
+            },
+            PreparedBlock::Toc { block_index, toc } => {
+                measure_toc(toc, *block_index, &mut pos_blocks, 
+                    &mut cursor, config, page_geometry);
+            },
+            PreparedBlock::FigureRow { block_index, row } => {
+                let y_offset = cursor.y_offset;
+                let spacing_before = cursor.collapse_spacing(
+                    config.block_spacing().image().before(), y_offset);
+
+                // As for a single image block.
+                let measured_info = place_whole_block(&mut cursor, y_offset, spacing_before, 
+                    |y, spacing_before| measure_figure_row(row, y, spacing_before, config, 
+                        page_geometry))?;
+
+                pos_blocks.push(PositionedBlock::figure_row(*block_index, cursor.frame(), 
+                    measured_info.shifted(frame_offset(&cursor))));
+
+                cursor.y_offset = measured_info.block_top_y();
+                cursor.y = cursor.y_offset + measured_info.block_height();
+            },
+            PreparedBlock::FloatImage { block_index, float } => {
+                let y = cursor.y;
+                let spacing_before = cursor.collapse_spacing(
+                    config.block_spacing().image().before(), y);
+
+                let measured_info = place_whole_block(&mut cursor, y, spacing_before, 
+                    |y, spacing_before| Ok(measure_float(float, y, spacing_before, config, 
+                        page_geometry)))?;
+
+                pos_blocks.push(PositionedBlock::float_image(*block_index, cursor.frame(), 
+                    measured_info.shifted(frame_offset(&cursor))));
+
+                // The paragraphs after the float start level with it.
+                cursor.y = measured_info.block_top_y();
+                cursor.y_offset = cursor.y;
+                float_bottom = Some((cursor.frame(), cursor.y + measured_info.block_height()));
+            },
+            // The number of columns has already changed, above.
+            PreparedBlock::Columns { .. } => {},
         }
 
     }
 
-    Ok(pos_blocks)
+    // Quote rules are only known once a quote ends, after the fragments on 
+    // its later pages. Rendering goes page by page: the sort is stable, so 
+    // fragments within a page keep their order.
+    pos_blocks.sort_by_key(|pos_block| pos_block.page());
+
+    Ok((pos_blocks, cursor.finish()))
+}
+
+/// The PDF outline entries: one for each heading, at the top of the first
+/// line of its first [`PositionedBlock::Header`] fragment.
+fn outline_entries(prepared_blocks: &[PreparedBlock],
+    pos_blocks: &[PositionedBlock],
+    frame_geometry: &dyn Fn(&PositionedBlock) -> PageGeometry
+) -> Vec<OutlineEntry> {
+    // The page, left edge and baseline of each block's first header fragment.
+    let mut first_fragments: Vec<Option<(usize, f64, f64)>> = vec![None; prepared_blocks.len()];
+    for pos_block in pos_blocks {
+        if let PositionedBlock::Header { block_index, frame, y_offset, .. } = pos_block
+            && first_fragments[*block_index].is_none() {
+            first_fragments[*block_index] = Some((frame.page(), 
+                frame_geometry(pos_block).margins().left, *y_offset));
+        }
+    }
+
+    prepared_blocks.iter().filter_map(|prepared| {
+        let PreparedBlock::Header { block_index, level, layout, .. } = prepared else {
+            return None;
+        };
+        let (page, left, baseline) = first_fragments[*block_index]?;
+
+        // Lines are drawn on their baselines.
+        let ascent = layout.line(0).map_or(0.0, |line| {
+            let (_ink, logical) = line.extents();
+            -logical.y() as f64 / pango::SCALE as f64
+        });
+
+        Some(OutlineEntry::new(*level, &layout.text(), page, left, baseline - ascent))
+    }).collect()
+}
+
+/// The level 1 and 2 headings for the running heads: where each one's first
+/// [`PositionedBlock::Header`] fragment is, and whether it begins its page.
+fn heading_marks(prepared_blocks: &[PreparedBlock],
+    pos_blocks: &[PositionedBlock]
+) -> Vec<HeadingMark> {
+    let mut marks: Vec<HeadingMark> = Vec::new();
+    // The page of the last fragment seen.
+    let mut last_page = 0;
+
+    for pos_block in pos_blocks {
+        // Quote rules are drawn behind the blocks, they do not begin a page.
+        if matches!(pos_block, PositionedBlock::QuoteRule { .. }) { continue; }
+
+        if let PositionedBlock::Header { block_index, frame, line_start: 0, .. } = pos_block
+            && let PreparedBlock::Header { level: level @ 1..=2, layout, .. } =
+                &prepared_blocks[*block_index] {
+            let page = frame.page();
+            marks.push(HeadingMark::new(*level, &layout.text(), page, page != last_page));
+        }
+
+        last_page = pos_block.page();
+    }
+
+    marks
 }
 
 /// Write all [`PositionedBlock`]s to PDF using the available pagination info.
@@ -442,44 +931,40 @@ fn measure_block(prepared_blocks: &[PreparedBlock],
 /// this function 
 fn output_positioned_block(context: &Context,
     config: &Config,
+    page_geometry: &PageGeometry,
     prepared: &PreparedBlock,
     pos_block: &PositionedBlock
 ) {
     let text = |y_offset: f64, 
         line_start: usize, line_end: usize, layout: &Layout, 
-        line_heights: &[f64]| {
-
-        let mut y = y_offset;
-        for i in line_start..line_end {
-            if let Some(line) = layout.line(i as i32) {
-                context.move_to(A4_DEFAULT_MARGINS.left, y);
-                show_layout_line(context, &line);
-
-                // Use the precomputed line height.
-                y += line_heights[i];
-            }
-        }        
+        line_heights: &[f64], links: &[LayoutLink]| {
+        show_lines(context, layout, line_heights, page_geometry.margins().left, 
+            y_offset, line_start, line_end);
+        tag_line_links(context, layout, links, line_heights, page_geometry.margins().left,
+            y_offset, line_start..line_end);
     };
 
     match (pos_block, prepared) {
         (PositionedBlock::Header { y_offset, line_start, line_end, .. },
-        PreparedBlock::Header { layout, line_heights, .. }) => {
-            text(*y_offset, *line_start, *line_end, layout, line_heights);
+        PreparedBlock::Header { layout, line_heights, links, .. }) => {
+            text(*y_offset, *line_start, *line_end, layout, line_heights, links);
         },
         (PositionedBlock::Paragraph { y_offset, line_start, line_end, .. },
-        PreparedBlock::Paragraph { layout, line_heights, .. }) => {
-            text(*y_offset, *line_start, *line_end, layout, line_heights);
+        PreparedBlock::Paragraph { layout, line_heights, links, wrap, .. }) => match wrap {
+            Some(wrap) => show_wrapped_lines(context, wrap, layout, line_heights, links, 
+                (page_geometry.margins().left, *y_offset), *line_start..*line_end),
+            None => text(*y_offset, *line_start, *line_end, layout, line_heights, links),
         },
         (PositionedBlock::Image { measured_info, .. },
-        PreparedBlock::Image { caption_layout, image_surface, .. }) => {
-            let _ = render_image_block(image_surface, caption_layout, 
-                measured_info, context, config);
+        PreparedBlock::Image { caption_layout, image_surface, placeholder, .. }) => {
+            let _ = render_image_block(image_surface, placeholder.as_deref(), caption_layout, 
+                measured_info, context, config, page_geometry);
         },
         (PositionedBlock::Thematic { y_offset, .. }, PreparedBlock::Thematic { .. }) => {
             context.save().expect("Failed to save Cairo context");
 
-            context.move_to(A4_DEFAULT_MARGINS.left, *y_offset);
-            context.line_to(A4.width - A4_DEFAULT_MARGINS.right, *y_offset);
+            context.move_to(page_geometry.margins().left, *y_offset);
+            context.line_to(page_geometry.size().width - page_geometry.margins().right, *y_offset);
             
             context.set_line_width(config.horizontal_break().stroke_width());
             
@@ -491,59 +976,266 @@ fn output_positioned_block(context: &Context,
             // Restore the original context.
             context.restore().expect("Failed to restore Cairo context");
         },
+        (PositionedBlock::ListItem { item_index, y_offset, line_start, line_end, .. },
+        PreparedBlock::List { items, .. }) => {
+            render_list_item(&items[*item_index], *y_offset, *line_start, *line_end, 
+                context, page_geometry);
+        },
+        (PositionedBlock::Code { y_offset, line_start, line_end, .. },
+        PreparedBlock::Code { layout, line_heights, .. }) => {
+            render_code_block(layout, line_heights, *y_offset, *line_start..*line_end, 
+                config, context, page_geometry);
+        },
+        (PositionedBlock::TableRow { row_index, y_offset, .. }, 
+        PreparedBlock::Table { table, .. }) => {
+            render_table_row(table, *row_index, *y_offset, config, context, page_geometry);
+        },
+        (PositionedBlock::QuoteRule { y_start, y_end, .. }, PreparedBlock::QuoteEnd { .. }) => {
+            render_quote_rule(*y_start, *y_end, config, context, page_geometry);
+        },
+        (PositionedBlock::TocTitle { y_offset, .. }, PreparedBlock::Toc { toc, .. }) => {
+            render_toc_title(toc, *y_offset, context, page_geometry);
+        },
+        (PositionedBlock::TocEntry { entry_index, y_offset, .. }, 
+        PreparedBlock::Toc { toc, .. }) => {
+            render_toc_entry(toc, *entry_index, *y_offset, context, page_geometry);
+        },
+        (PositionedBlock::FigureRow { measured_info, .. }, PreparedBlock::FigureRow { row, .. }) => {
+            let _ = render_figure_row(row, measured_info, context, config);
+        },
+        (PositionedBlock::FloatImage { measured_info, .. }, 
+        PreparedBlock::FloatImage { float, .. }) => {
+            let _ = render_float(float, measured_info, context, config);
+        },
         _ => debug_assert!(false, "Mismatched PreparedBlock and PositionedBlock variants"),
     }
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let (config_file_name, pdf_file_name) = if cfg!(target_os = "windows") {
-        ("./config/config.toml", "win_essay.pdf")
-    } else {
-        ("./config/config.toml", "ubuntu_essay.pdf")
-    };
+/// Render a single Markdown file to a PDF.
+/// 
+/// The PDF is built in memory and only written out once the whole document
+/// has rendered: a document which fails leaves no partial PDF behind.
+/// 
+/// # Arguments
+/// 
+/// * `input_file_name` — the Markdown text file name.
+/// 
+/// * `pdf_file_name` — the output PDF file name.
+/// 
+/// * `config` — the loaded configuration, which the document's front matter 
+///   can override.
+/// 
+/// * `page_size` — the `--page-size` command-line option.
+/// 
+/// * `strict` — the `--strict` command-line option: whether warnings are 
+///   errors.
+/// 
+/// # Returns
+/// 
+/// * [`Vec<String>`] — the warnings: problems which did not stop the 
+///   document, such as an image which cannot be loaded and was replaced by a
+///   placeholder.
+/// 
+/// * [`std::error::Error`] — if the Markdown cannot be read or parsed, its 
+///   front matter overrides are not valid, the PDF cannot be laid out or 
+///   written, or, when `strict`, on the first warning.
+/// 
+fn render_document(input_file_name: &Path, 
+    pdf_file_name: &Path, 
+    config: &Config,
+    page_size: Option<&str>,
+    strict: bool
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let (front_matter, mut blocks) = parse_blocks_from_file(input_file_name)?;
+
+    let document_config = front_matter
+        .map(|overrides| config.with_overrides(&overrides))
+        .transpose()?;
+    let config = document_config.as_ref().unwrap_or(config);
 
-    let config = load_config(config_file_name)?;
+    let page_geometry = &config.page().page_geometry(page_size)
+        .map_err(|err| format!("[page]: {err}"))?;
+    let creation_date = &creation_date(config.metadata())?;
 
-    let blocks = match parse_blocks_from_file("./text/essay.txt") {
-		Ok(blocks) => blocks,
-		Err(err) => {
-			println!("\nError: {}", err.to_string());
-			process::exit(1);
-		}
-	};
+    insert_toc(&mut blocks, config);
 	
-    let surface = PdfSurface::new(A4.width, A4.height, pdf_file_name)?;
+    let surface = PdfSurface::for_stream(page_geometry.size().width, 
+        page_geometry.size().height, Vec::<u8>::new())?;
 
     let context = Context::new(&surface)?;
 
-    let prepared_blocks = prepare_blocks(&blocks, &config, &context);
+    // Blocks within blockquotes are indented, in the width of a column.
+    let depths = quote_depths(&blocks);
+    let block_columns = block_columns(&blocks, config.columns());
+    let block_geometries: Vec<PageGeometry> = depths.iter().zip(&block_columns)
+        .map(|(depth, columns)| quote_geometry(*depth, config, 
+            &page_geometry.first_column(*columns, config.columns().gutter())))
+        .collect();
+
+    // The content area of each fragment: its block's, moved into its column.
+    let frame_geometry = |pos_block: &PositionedBlock| {
+        let block_index = pos_block.block_index();
+        block_geometries[block_index].shifted(column_offset(pos_block.frame().column(), 
+            block_columns[block_index], config.columns(), page_geometry))
+    };
+
+    let image_dirs = image_search_dirs(input_file_name, config.image_block().assets_dir());
+
+    let mut figures = Figures::new(&blocks, config.figure());
+
+    let mut warnings: Vec<String> = figures.warnings().to_vec();
+    let mut prepared_blocks = prepare_blocks(&blocks, &depths, &block_geometries, config, 
+        &PrepareContext::new(&figures, &context, &image_dirs), &mut warnings);
+
+    if let Some(warning) = warnings.first().filter(|_| strict) {
+        return Err(warning.clone().into());
+    }
+
+    let (mut pos_blocks, mut column_sets) = measure_block(&prepared_blocks, &block_geometries, 
+        &block_columns, config, page_geometry)?;
+    let mut headings = outline_entries(&prepared_blocks, &pos_blocks, &frame_geometry);
+    let mut figures_moved = figures.place(&pos_blocks);
+
+    // The table of contents shifts the headings after it, and figure 
+    // references change length once their pages are known: paginate again 
+    // with both filled in, until the page numbers settle.
+    let has_toc = blocks.iter().any(|block| matches!(block, Block::Toc));
+    let has_references = !figures.referring_blocks().is_empty();
+
+    if has_toc || has_references {
+        for _ in 0..PAGINATION_PASSES {
+            for prepared in prepared_blocks.iter_mut() {
+                if let PreparedBlock::Toc { block_index, toc } = prepared {
+                    *toc = prepare_toc(&headings, config, 
+                        &block_geometries[*block_index], &context);
+                }
+            }
+
+            if has_references && figures_moved {
+                refresh_references(&mut prepared_blocks, &blocks, &depths, &block_geometries,
+                    config, &PrepareContext::new(&figures, &context, &image_dirs));
+            }
+
+            (pos_blocks, column_sets) = measure_block(&prepared_blocks, &block_geometries, 
+                &block_columns, config, page_geometry)?;
+
+            let placed = outline_entries(&prepared_blocks, &pos_blocks, &frame_geometry);
+            figures_moved = figures.place(&pos_blocks);
+            if placed == headings && !figures_moved { break; }
+            headings = placed;
+        }
+    }
+
+    add_outline(&surface, &headings)?;
+
+    let total_pages: usize = pos_blocks.last().map_or(1, |pos_block| pos_block.page());
+
+    let title = match config.metadata().title() {
+        Some(title) => title.to_string(),
+        None => headings.iter().find(|heading| heading.level() == 1)
+            .map_or_else(|| input_file_name.file_stem().unwrap_or_default()
+                .to_string_lossy().into_owned(), |heading| heading.title().to_string()),
+    };
+    set_pdf_metadata(&surface, config.metadata(), &title, creation_date)?;
 
-    let pos_blocks = measure_block(&prepared_blocks, &config)?;
+    let heads = running_heads(&heading_marks(&prepared_blocks, &pos_blocks), total_pages,
+        config.running_head().mark());
+    // `YYYY-MM-DD`.
+    let date = &creation_date[..10];
+
+    let page_fields = |page: usize| PageFields::new(page, total_pages, &title, 
+        &heads[page - 1], date);
 
-    let total_pages: usize = pos_blocks[pos_blocks.len() - 1].page();
     let mut current_page: usize = 1;
 
     for pos_block in pos_blocks {
         if pos_block.page() != current_page {
-            page_number(&context, current_page, total_pages, 
-                a4_default_content_width(), A4.height, 
-                &config.fonts());
+            render_column_rules(&column_sets, current_page, config, &context, page_geometry);
+            render_page_templates(&page_fields(current_page), config, &context, page_geometry);
 
-            let _ = context.show_page();
+            context.show_page()?;
             current_page = pos_block.page();
         };
 
-        output_positioned_block(&context, &config, &prepared_blocks[pos_block.block_index()], 
-            &pos_block);
+        output_positioned_block(&context, config, &frame_geometry(&pos_block), 
+            &prepared_blocks[pos_block.block_index()], &pos_block);
     }
 
-    page_number(&context, current_page, total_pages, 
-        a4_default_content_width(), A4.height, 
-        &config.fonts());
+    render_column_rules(&column_sets, current_page, config, &context, page_geometry);
+    render_page_templates(&page_fields(current_page), config, &context, page_geometry);
+
+    let pdf = surface.finish_output_stream().map_err(std::io::Error::from)?
+        .downcast::<Vec<u8>>().map_err(|_| "PDF output is not a byte buffer")?;
+    fs::write(pdf_file_name, *pdf)?;
 
-    surface.finish();
+    Ok(warnings)
+}
 
-    println!("PDF written to: {pdf_file_name}");
+/// Run the command line, returning the process exit status. See [`cli`] for 
+/// the meaning of each status.
+fn run(options: &CliOptions) -> i32 {
+    let config = match load_config(options.config()) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("Error: {}: {}", options.config().display(), err);
+            return EXIT_CONFIG_ERROR;
+        }
+    };
+
+    // Checked once for all documents here, front matter overrides are 
+    // checked with their document.
+    if let Err(err) = config.page().page_geometry(options.page_size()) {
+        eprintln!("Error: {}: [page]: {}", options.config().display(), err);
+        return EXIT_CONFIG_ERROR;
+    }
+
+    if let Err(err) = creation_date(config.metadata()) {
+        eprintln!("Error: {}: {}", options.config().display(), err);
+        return EXIT_CONFIG_ERROR;
+    }
 
-	Ok(())
-}
\ No newline at end of file
+    // With multiple inputs, `--output` names a directory.
+    let output_dir = options.output().filter(|_| options.inputs().len() > 1);
+    if let Some(Err(err)) = output_dir.map(fs::create_dir_all) {
+        eprintln!("Error: {}: {}", output_dir.unwrap().display(), err);
+        return EXIT_DOCUMENT_ERROR;
+    }
+
+    let mut status = EXIT_SUCCESS;
+
+    for input_file_name in options.inputs() {
+        let pdf_file_name = options.output_for(input_file_name);
+
+        match render_document(input_file_name, &pdf_file_name, &config, 
+            options.page_size(), options.strict()) {
+            Ok(warnings) => {
+                for warning in &warnings {
+                    eprintln!("Warning: {}: {}", input_file_name.display(), warning);
+                }
+                println!("PDF written to: {}", pdf_file_name.display());
+            },
+            Err(err) => {
+                eprintln!("Error: {}: {}", input_file_name.display(), err);
+                status = EXIT_DOCUMENT_ERROR;
+            }
+        }
+    }
+
+    status
+}
+
+fn main() {
+    let status = match parse_args(std::env::args().skip(1)) {
+        Ok(Command::Help) => {
+            println!("{USAGE}");
+            EXIT_SUCCESS
+        },
+        Ok(Command::Render(options)) => run(&options),
+        Err(err) => {
+            eprintln!("Error: {err}\n\n{USAGE}");
+            EXIT_USAGE_ERROR
+        }
+    };
+
+    process::exit(status);
+}
@@ -0,0 +1,130 @@
+/* 17/10/2026 */
+
+//!
+//! The PDF outline, the bookmarks shown in a PDF reader's navigation pane.
+//! Each heading becomes an outline entry, nested under the nearest heading
+//! of a higher level before it. A skipped level, say a level 3 heading
+//! straight after a level 1 heading, nests one step only.
+//!
+//! An entry opens the page of the heading's first fragment, scrolled to the
+//! top of its first line.
+//!
+
+use cairo::{PdfOutline, PdfSurface, PDF_OUTLINE_ROOT};
+
+/// A heading in the PDF outline.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutlineEntry {
+    /// Heading level, `1` is the top level.
+    level: u8,
+    title: String,
+    /// The 1-based page number.
+    page: usize,
+    /// Where the heading starts on its page.
+    x: f64,
+    y: f64,
+}
+
+impl OutlineEntry {
+    pub fn new(level: u8, title: &str, page: usize, x: f64, y: f64) -> Self {
+        OutlineEntry { level, title: title.to_string(), page, x, y }
+    }
+
+    pub fn level(&self) -> u8 {
+        self.level
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn page(&self) -> usize {
+        self.page
+    }
+
+    pub fn x(&self) -> f64 {
+        self.x
+    }
+
+    pub fn y(&self) -> f64 {
+        self.y
+    }
+}
+
+/// The parent of each outline entry, given the entry levels in document
+/// order: the index of the closest earlier entry with a lower level, or
+/// `None` for a top level entry.
+pub fn outline_parents(levels: &[u8]) -> Vec<Option<usize>> {
+    // The chain of entries from the top level down to the previous entry.
+    let mut open: Vec<(u8, usize)> = Vec::new();
+
+    levels.iter().enumerate().map(|(index, level)| {
+        while open.last().is_some_and(|(open_level, _)| open_level >= level) {
+            open.pop();
+        }
+
+        let parent = open.last().map(|(_, parent)| *parent);
+        open.push((*level, index));
+
+        parent
+    }).collect()
+}
+
+/// Add the outline entries to a PDF surface. Top level entries are shown
+/// expanded.
+///
+/// # Arguments
+///
+/// * `surface` — the PDF surface being written.
+///
+/// * `entries` — the outline entries in document order.
+///
+/// # Returns
+///
+/// * [`std::error::Error`] — if Cairo fails to add an entry.
+///
+pub fn add_outline(surface: &PdfSurface,
+    entries: &[OutlineEntry]
+) -> Result<(), Box<dyn std::error::Error>> {
+    let levels: Vec<u8> = entries.iter().map(|entry| entry.level()).collect();
+    let parents = outline_parents(&levels);
+
+    let mut ids: Vec<i32> = Vec::with_capacity(entries.len());
+
+    for (entry, parent) in entries.iter().zip(parents) {
+        let parent_id = parent.map_or(PDF_OUTLINE_ROOT, |parent| ids[parent]);
+        let flags = if parent.is_none() { PdfOutline::OPEN } else { PdfOutline::empty() };
+        let link_attribs = format!("page={} pos=[{} {}]", entry.page(), entry.x(), entry.y());
+
+        ids.push(surface.add_outline(parent_id, entry.title(), &link_attribs, flags)?);
+    }
+
+    Ok(())
+}
+
+// To run test for this module only:
+//
+//     * cargo test outline::tests
+//
+//     * cargo test outline::tests::test_outline_parents -- --exact [--nocapture]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_outline_parents() {
+        let test_data: Vec<(Vec<u8>, Vec<Option<usize>>)> = vec![
+            (vec![], vec![]),
+            (vec![1, 2, 2, 1, 2], vec![None, Some(0), Some(0), None, Some(3)]),
+            (vec![1, 2, 3, 3, 2, 4], vec![None, Some(0), Some(1), Some(1), Some(0), Some(4)]),
+            // Skipped levels nest one step only.
+            (vec![1, 3, 2], vec![None, Some(0), Some(0)]),
+            // No level 1 heading before the first level 2 heading.
+            (vec![2, 3, 1, 2], vec![None, Some(0), None, Some(2)]),
+        ];
+
+        for (levels, expected) in test_data {
+            assert_eq!(outline_parents(&levels), expected, "levels {levels:?}");
+        }
+    }
+}
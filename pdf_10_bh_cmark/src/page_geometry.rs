@@ -1,10 +1,14 @@
 // 24/11/2025
 
+use crate::config::{Orientation, PageConfig, PageSizeName};
+
+#[derive(Debug, Clone, Copy)]
 pub struct PageSize {
     pub width: f64,
     pub height: f64,
 }
 
+#[derive(Debug, Clone, Copy)]
 pub struct PageMargins {
     pub top: f64,
     pub right: f64,
@@ -13,7 +17,10 @@ pub struct PageMargins {
 }
 
 pub const A4: PageSize = PageSize { width: 595.22, height: 842.0 };
-// pub const A3: PageSize = PageSize { width: 842.0, height: 1191.0 };
+pub const A3: PageSize = PageSize { width: 842.0, height: 1191.0 };
+pub const A5: PageSize = PageSize { width: 420.0, height: 595.22 };
+pub const LETTER: PageSize = PageSize { width: 612.0, height: 792.0 };
+pub const LEGAL: PageSize = PageSize { width: 612.0, height: 1008.0 };
 
 // Margin: 20mm. 20 x (72 / 25.4) = 57 Postscript point.
 pub const A4_DEFAULT_MARGINS: PageMargins = PageMargins {
@@ -27,10 +34,321 @@ pub const A4_DEFAULT_MARGINS: PageMargins = PageMargins {
     // left: 200.0,
 };
 
-pub fn a4_default_content_width() -> f64 {
-    A4.width - A4_DEFAULT_MARGINS.right - A4_DEFAULT_MARGINS.left
+impl PageSizeName {
+    /// Look up a page size name: `A3`, `A4`, `A5`, `Letter` or `Legal`.
+    /// The comparison is case-insensitive.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "a3" => Some(PageSizeName::A3),
+            "a4" => Some(PageSizeName::A4),
+            "a5" => Some(PageSizeName::A5),
+            "letter" => Some(PageSizeName::Letter),
+            "legal" => Some(PageSizeName::Legal),
+            _ => None,
+        }
+    }
+
+    /// The portrait size.
+    pub fn page_size(self) -> PageSize {
+        match self {
+            PageSizeName::A3 => A3,
+            PageSizeName::A4 => A4,
+            PageSizeName::A5 => A5,
+            PageSizeName::Letter => LETTER,
+            PageSizeName::Legal => LEGAL,
+        }
+    }
+}
+
+/// Look up a page size by its name, see [`PageSizeName::from_name()`].
+pub fn named_page_size(name: &str) -> Option<PageSize> {
+    PageSizeName::from_name(name).map(PageSizeName::page_size)
+}
+
+/// The page size and margins in effect for a document. Measurement and
+/// rendering both work off this value rather than the `A4` constants.
+#[derive(Debug, Clone, Copy)]
+pub struct PageGeometry {
+    size: PageSize,
+    margins: PageMargins,
+}
+
+impl PageGeometry {
+    pub fn new(size: PageSize, margins: PageMargins) -> Self {
+        PageGeometry { size, margins }
+    }
+
+    pub fn size(&self) -> &PageSize {
+        &self.size
+    }
+
+    pub fn margins(&self) -> &PageMargins {
+        &self.margins
+    }
+
+    pub fn content_width(&self) -> f64 {
+        self.size.width - self.margins.right - self.margins.left
+    }
+
+    /// The height of the content area. This is a height, not a 
+    /// y-coordinate: compare a y against [`PageGeometry::content_bottom`].
+    pub fn content_height(&self) -> f64 {
+        self.size.height - self.margins.top - self.margins.bottom
+    }
+
+    /// The y-coordinate of the bottom of the content area: a block fits on 
+    /// the page when it ends at or above this.
+    pub fn content_bottom(&self) -> f64 {
+        self.size.height - self.margins.bottom
+    }
+
+    /// The same page with the left margin moved in by `left`: the content 
+    /// area of an indented block, such as a blockquote.
+    pub fn inset(&self, left: f64) -> PageGeometry {
+        let mut margins = self.margins;
+        margins.left += left;
+        PageGeometry::new(self.size, margins)
+    }
+
+    /// The content area of the first of `columns` columns, `gutter` apart: 
+    /// the right margin moves in to leave the other columns.
+    pub fn first_column(&self, columns: usize, gutter: f64) -> PageGeometry {
+        let columns = columns.max(1) as f64;
+        let column_width = (self.content_width() - (columns - 1.0) * gutter) / columns;
+
+        let mut margins = self.margins;
+        margins.right += self.content_width() - column_width;
+        PageGeometry::new(self.size, margins)
+    }
+
+    /// The same content area moved right by `dx`.
+    pub fn shifted(&self, dx: f64) -> PageGeometry {
+        let mut margins = self.margins;
+        margins.left += dx;
+        margins.right -= dx;
+        PageGeometry::new(self.size, margins)
+    }
+}
+
+impl Default for PageGeometry {
+    /// A4 with the default 20mm margins.
+    fn default() -> Self {
+        PageGeometry::new(A4, A4_DEFAULT_MARGINS)
+    }
+}
+
+impl PageConfig {
+    /// Work out the [`PageGeometry`] described by the `[page]` configuration
+    /// section.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `size_override` — a named page size, such as the `--page-size` 
+    ///   command-line option, which replaces both `size` and a custom 
+    ///   `width` / `height`. Orientation and margins still apply.
+    /// 
+    /// An explicit `orientation` turns the page, named or custom, to that 
+    /// orientation. Without it, a custom `width` / `height` is used as given.
+    /// 
+    /// # Returns
+    /// 
+    /// * [`PageGeometry`] — on success.
+    /// 
+    /// * [`std::error::Error`] — on an unknown `size_override`, a custom 
+    ///   size with only one of `width` and `height`, a non-positive custom 
+    ///   size, or margins which leave no content area. Unknown `size` and 
+    ///   `orientation` names are rejected when the configuration loads.
+    /// 
+    pub fn page_geometry(&self, 
+        size_override: Option<&str>
+    ) -> Result<PageGeometry, Box<dyn std::error::Error>> {
+        let size = match (size_override, self.width(), self.height()) {
+            (_, Some(_), None) | (_, None, Some(_)) => 
+                return Err("A custom page size needs both width and height".into()),
+            (Some(name), _, _) => named_page_size(name)
+                .ok_or_else(|| format!("Unknown page size: {name}"))?,
+            (None, Some(width), Some(height)) => {
+                if width <= 0.0 || height <= 0.0 {
+                    return Err(format!("Invalid page size: {width} x {height}").into());
+                }
+                PageSize { width, height }
+            },
+            (None, None, None) => self.size().page_size(),
+        };
+
+        // The named sizes are portrait already: without an orientation, the 
+        // size, custom or named, is used as it is.
+        let size = match self.orientation() {
+            None => size,
+            Some(Orientation::Portrait) => PageSize { width: size.width.min(size.height), 
+                height: size.width.max(size.height) },
+            Some(Orientation::Landscape) => PageSize { width: size.width.max(size.height), 
+                height: size.width.min(size.height) },
+        };
+
+        let margins = PageMargins {
+            top: self.margins().top(),
+            right: self.margins().right(),
+            bottom: self.margins().bottom(),
+            left: self.margins().left(),
+        };
+
+        let page_geometry = PageGeometry::new(size, margins);
+
+        if page_geometry.content_width() <= 0.0 || page_geometry.content_height() <= 0.0 {
+            return Err("Page margins leave no content area".into());
+        }
+
+        Ok(page_geometry)
+    }
 }
 
-pub fn a4_default_content_height() -> f64 {
-    A4.height - A4_DEFAULT_MARGINS.top - A4_DEFAULT_MARGINS.bottom
-}
\ No newline at end of file
+// To run test for this module only: 
+// 
+//     * cargo test page_geometry::tests
+//
+//     * cargo test page_geometry::tests::test_page_geometry_default -- --exact [--nocapture]
+//     * cargo test page_geometry::tests::test_page_geometry_landscape -- --exact [--nocapture]
+//     * cargo test page_geometry::tests::test_page_geometry_custom -- --exact [--nocapture]
+//     * cargo test page_geometry::tests::test_page_geometry_custom_orientation -- --exact [--nocapture]
+//     * cargo test page_geometry::tests::test_page_geometry_content_bottom -- --exact [--nocapture]
+//     * cargo test page_geometry::tests::test_page_geometry_invalid -- --exact [--nocapture]
+//     * cargo test page_geometry::tests::test_page_config_invalid -- --exact [--nocapture]
+//     * cargo test page_geometry::tests::test_page_geometry_columns -- --exact [--nocapture]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page_config(page_str: &str) -> PageConfig {
+        toml::from_str(page_str).expect("Failed to load test page config string")
+    }
+
+    #[test]
+    /// An empty `[page]` section is the original A4 layout.
+    fn test_page_geometry_default() {
+        let page_geometry = page_config("").page_geometry(None)
+            .expect("Expected default page geometry");
+
+        assert_eq!(page_geometry.size().width, A4.width, "width");
+        assert_eq!(page_geometry.size().height, A4.height, "height");
+        assert_eq!(page_geometry.content_width(), 595.22 - 57.0 - 57.0, "content width");
+        assert_eq!(page_geometry.content_height(), 842.0 - 57.0 - 57.0, "content height");
+    }
+
+    #[test]
+    fn test_page_geometry_landscape() {
+        let config = page_config("size = \"Letter\"\norientation = \"landscape\"");
+
+        let page_geometry = config.page_geometry(None)
+            .expect("Expected landscape page geometry");
+        assert_eq!(page_geometry.size().width, LETTER.height, "width");
+        assert_eq!(page_geometry.size().height, LETTER.width, "height");
+
+        // The override replaces the size, the orientation still applies.
+        let page_geometry = config.page_geometry(Some("a5"))
+            .expect("Expected overridden page geometry");
+        assert_eq!(page_geometry.size().width, A5.height, "override width");
+        assert_eq!(page_geometry.size().height, A5.width, "override height");
+    }
+
+    #[test]
+    fn test_page_geometry_custom() {
+        let config = page_config("size = \"A3\"\nwidth = 400.0\nheight = 600.0\n\
+            [margins]\ntop = 10.0\nright = 20.0\nbottom = 30.0\nleft = 40.0");
+
+        let page_geometry = config.page_geometry(None)
+            .expect("Expected custom page geometry");
+
+        assert_eq!(page_geometry.size().width, 400.0, "width");
+        assert_eq!(page_geometry.size().height, 600.0, "height");
+        assert_eq!(page_geometry.margins().left, 40.0, "left margin");
+        assert_eq!(page_geometry.content_width(), 400.0 - 20.0 - 40.0, "content width");
+        assert_eq!(page_geometry.content_height(), 600.0 - 10.0 - 30.0, "content height");
+    }
+
+    #[test]
+    /// A custom landscape size is kept as it is, unless an orientation is 
+    /// set explicitly.
+    fn test_page_geometry_custom_orientation() {
+        let test_data: Vec<(&str, f64, f64)> = vec![
+            ("width = 800.0\nheight = 600.0", 800.0, 600.0),
+            ("width = 800.0\nheight = 600.0\norientation = \"landscape\"", 800.0, 600.0),
+            ("width = 800.0\nheight = 600.0\norientation = \"portrait\"", 600.0, 800.0),
+            ("size = \"A5\"", A5.width, A5.height),
+        ];
+
+        for (index, (page_str, width, height)) in test_data.iter().enumerate() {
+            let page_geometry = page_config(page_str).page_geometry(None)
+                .unwrap_or_else(|_| panic!("Expected page geometry for page config {}", index));
+            assert_eq!(page_geometry.size().width, *width, "Page config {} width", index);
+            assert_eq!(page_geometry.size().height, *height, "Page config {} height", index);
+        }
+    }
+
+    #[test]
+    /// With a top margin larger than the bottom one, the content area still 
+    /// runs down to the bottom margin.
+    fn test_page_geometry_content_bottom() {
+        let config = page_config("size = \"A5\"\n\
+            [margins]\ntop = 150.0\nright = 30.0\nbottom = 40.0\nleft = 30.0");
+
+        let page_geometry = config.page_geometry(None)
+            .expect("Expected A5 page geometry");
+
+        assert_eq!(page_geometry.content_height(), 595.22 - 150.0 - 40.0, "content height");
+        assert_eq!(page_geometry.content_bottom(), 595.22 - 40.0, "content bottom");
+        assert!(page_geometry.content_bottom() > page_geometry.content_height(),
+            "content bottom is a y-coordinate below the content height");
+    }
+
+    #[test]
+    fn test_page_geometry_invalid() {
+        let test_data: Vec<(&str, &str)> = vec![
+            ("width = 0.0\nheight = 100.0", "Invalid page size"),
+            ("width = 400.0", "needs both width and height"),
+            ("size = \"A5\"\nheight = 400.0", "needs both width and height"),
+            ("size = \"A5\"\n[margins]\ntop = 300.0\nright = 10.0\nbottom = 300.0\nleft = 10.0",
+                "no content area"),
+        ];
+
+        for (index, (page_str, message)) in test_data.iter().enumerate() {
+            let err = page_config(page_str).page_geometry(None)
+                .expect_err(&format!("Expected failure for page config {}", index));
+            assert!(err.to_string().contains(message), "{}", 
+                format!("Page config {} error message", index));
+        }
+    }
+
+    #[test]
+    /// Unknown names fail when the configuration loads.
+    fn test_page_config_invalid() {
+        let test_data: Vec<&str> = vec![
+            "size = \"B5\"",
+            "orientation = \"sideways\"",
+        ];
+
+        for page_str in test_data {
+            assert!(toml::from_str::<PageConfig>(page_str).is_err(), "{page_str:?}");
+        }
+
+        assert_eq!(page_config("size = \"letter\"").size(), PageSizeName::Letter, "lower case");
+    }
+
+    #[test]
+    fn test_page_geometry_columns() {
+        // 400 wide, with 50 margins: 300 of content.
+        let page_geometry = PageGeometry::new(PageSize { width: 400.0, height: 600.0 },
+            PageMargins { top: 50.0, right: 50.0, bottom: 50.0, left: 50.0 });
+
+        let first = page_geometry.first_column(3, 15.0);
+        assert_eq!(first.content_width(), 90.0, "column width");
+        assert_eq!(first.margins().left, 50.0, "first column left");
+
+        let third = first.shifted(2.0 * (90.0 + 15.0));
+        assert_eq!(third.margins().left, 260.0, "third column left");
+        assert_eq!(third.margins().right, 50.0, "third column right");
+        assert_eq!(third.content_width(), 90.0, "third column width");
+
+        assert_eq!(page_geometry.first_column(1, 15.0).content_width(), 300.0, "one column");
+    }
+}
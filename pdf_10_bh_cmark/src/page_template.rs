@@ -0,0 +1,183 @@
+/* 17/10/2026 */
+
+//!
+//! Page headers and footers. Each of `[header]` and `[footer]` has a left,
+//! a centre and a right slot, each a text template in its own font. The
+//! placeholders in a template are replaced page by page:
+//!
+//! * `{page}` — the page number.
+//! * `{total}` — the number of pages.
+//! * `{title}` — the document title: `[metadata] title`, the first level 1
+//!   heading, or the input file name without its extension.
+//! * `{chapter}` — the level 1 heading in effect on the page.
+//! * `{section}` — the level 2 heading in effect on the page, or the
+//!   chapter when there is none. See [`crate::running_head`].
+//! * `{date}` — the PDF creation date, `YYYY-MM-DD`. See
+//!   [`crate::pdf_metadata`].
+//!
+//! Unknown placeholders are left as they are. A slot which comes out empty
+//! is not drawn.
+//!
+//! The header is centred vertically in the top margin, the footer hangs
+//! from the bottom margin.
+//!
+
+use cairo::Context;
+use pangocairo::functions::show_layout;
+
+use crate::config::{Config, PageTemplateConfig, PageTemplateSlot};
+use crate::page_geometry::PageGeometry;
+use crate::running_head::RunningHead;
+use crate::text_layout::{create_text_layout, layout_block_height, logical_width};
+
+/// The values of the template placeholders on one page.
+#[derive(Debug)]
+pub struct PageFields<'a> {
+    page: usize,
+    total: usize,
+    title: &'a str,
+    running_head: &'a RunningHead,
+    date: &'a str,
+}
+
+impl<'a> PageFields<'a> {
+    pub fn new(page: usize,
+        total: usize,
+        title: &'a str,
+        running_head: &'a RunningHead,
+        date: &'a str
+    ) -> Self {
+        PageFields { page, total, title, running_head, date }
+    }
+
+    fn value(&self, placeholder: &str) -> Option<String> {
+        match placeholder {
+            "page" => Some(self.page.to_string()),
+            "total" => Some(self.total.to_string()),
+            "title" => Some(self.title.to_string()),
+            "chapter" => Some(self.running_head.chapter().to_string()),
+            "section" => Some(self.running_head.section().to_string()),
+            "date" => Some(self.date.to_string()),
+            _ => None,
+        }
+    }
+}
+
+/// Replace the placeholders in a header or footer template.
+pub fn fill_template(template: &str, fields: &PageFields) -> String {
+    let mut filled = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        filled.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let Some(end) = rest.find('}') else { break; };
+
+        match fields.value(&rest[1..end]) {
+            Some(value) => {
+                filled.push_str(&value);
+                rest = &rest[end + 1..];
+            },
+            None => {
+                filled.push('{');
+                rest = &rest[1..];
+            },
+        }
+    }
+    filled.push_str(rest);
+
+    filled
+}
+
+/// Draw one slot of a header or footer, with the top of its text at `y`
+/// or, when `y` is `None`, centred vertically in the top margin.
+fn render_slot(slot: &PageTemplateSlot,
+    x_align: f64,
+    y: Option<f64>,
+    fields: &PageFields,
+    config: &Config,
+    context: &Context,
+    page_geometry: &PageGeometry,
+) {
+    let text = fill_template(slot.text(), fields);
+    if text.is_empty() { return; }
+
+    let font_spec = slot.font().unwrap_or(config.fonts().page_number());
+    // A width of -1 means no wrapping.
+    let layout = create_text_layout(-1, &text, font_spec, false, context);
+
+    let left = page_geometry.margins().left;
+    let x = left + (page_geometry.content_width() - logical_width(&layout)) * x_align;
+    let y = y.unwrap_or((page_geometry.margins().top - layout_block_height(&layout)) / 2.0);
+
+    context.move_to(x, y);
+    show_layout(context, &layout);
+}
+
+/// Draw the left, centre and right slots of a header or footer.
+fn render_slots(template: &PageTemplateConfig,
+    y: Option<f64>,
+    fields: &PageFields,
+    config: &Config,
+    context: &Context,
+    page_geometry: &PageGeometry,
+) {
+    if fields.page == 1 && template.skip_first_page() { return; }
+
+    let slots = [(template.left(), 0.0), (template.centre(), 0.5), (template.right(), 1.0)];
+
+    for (slot, x_align) in slots {
+        if let Some(slot) = slot {
+            render_slot(slot, x_align, y, fields, config, context, page_geometry);
+        }
+    }
+}
+
+/// Draw the `[header]` and the `[footer]` of a page.
+pub fn render_page_templates(fields: &PageFields,
+    config: &Config,
+    context: &Context,
+    page_geometry: &PageGeometry,
+) {
+    let footer_y = page_geometry.size().height - page_geometry.margins().bottom;
+
+    render_slots(config.header(), None, fields, config, context, page_geometry);
+    render_slots(config.footer(), Some(footer_y), fields, config, context, page_geometry);
+}
+
+// To run test for this module only:
+//
+//     * cargo test page_template::tests
+//
+//     * cargo test page_template::tests::test_fill_template -- --exact [--nocapture]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::running_head::{HeadingMark, running_heads};
+    use crate::config::RunningHeadMark;
+
+    #[test]
+    fn test_fill_template() {
+        let running_head = running_heads(&[HeadingMark::new(2, "Introduction", 1, true)], 3,
+            RunningHeadMark::First).pop().unwrap();
+        let fields = PageFields::new(3, 12, "Essay", &running_head, "2026-10-17");
+
+        let test_data: Vec<(&str, &str)> = vec![
+            ("{page} of {total}", "3 of 12"),
+            ("{title} — {section}", "Essay — Introduction"),
+            ("Printed {date}", "Printed 2026-10-17"),
+            ("", ""),
+            // No level 1 heading.
+            ("[{chapter}]", "[]"),
+            // Unknown and unclosed placeholders are left as they are.
+            ("{part} {page}", "{part} 3"),
+            ("{{page}}", "{3}"),
+            ("Page {page", "Page {page"),
+        ];
+
+        for (template, expected) in test_data {
+            assert_eq!(fill_template(template, &fields), expected, "{template}");
+        }
+    }
+}
@@ -0,0 +1,132 @@
+/* 17/10/2026 */
+
+//!
+//! The PDF document information: title, author, subject, keywords, creator
+//! and creation date, from the `[metadata]` section.
+//!
+//! The creation date is `[metadata] creation_date` when given. Otherwise it
+//! is the `SOURCE_DATE_EPOCH` environment variable, as set by reproducible
+//! builds, and failing that the current time. The same date fills the
+//! `{date}` placeholder of the page headers and footers, so a document
+//! written twice with a fixed date comes out the same.
+//!
+
+use cairo::{PdfMetadata, PdfSurface};
+
+use crate::config::MetadataConfig;
+use crate::date_utils::{iso_date_time, now, parse_iso_date_time};
+
+/// Work out the creation date, as an ISO 8601 date and time.
+///
+/// # Arguments
+///
+/// * `configured` — `[metadata] creation_date`.
+///
+/// * `source_date_epoch` — the `SOURCE_DATE_EPOCH` environment variable, a
+///   Unix timestamp in seconds.
+///
+/// * `now` — the current time as a Unix timestamp in seconds.
+///
+/// # Returns
+///
+/// * An error message — if `configured` or `source_date_epoch` is given but
+///   not valid.
+///
+fn resolve_creation_date(configured: Option<&str>,
+    source_date_epoch: Option<&str>,
+    now: i64
+) -> Result<String, String> {
+    if let Some(configured) = configured {
+        return parse_iso_date_time(configured).ok_or_else(|| format!("[metadata] \
+            creation_date: expected YYYY-MM-DD or YYYY-MM-DDThh:mm:ss, found '{configured}'"));
+    }
+
+    match source_date_epoch {
+        Some(epoch) => epoch.trim().parse::<i64>()
+            .map(iso_date_time)
+            .map_err(|_| format!("SOURCE_DATE_EPOCH: expected a Unix timestamp, found '{epoch}'")),
+        None => Ok(iso_date_time(now)),
+    }
+}
+
+/// The creation date of the documents, as an ISO 8601 date and time. See
+/// the module documentation for where it comes from.
+///
+/// # Returns
+///
+/// * [`std::error::Error`] — if `[metadata] creation_date` or
+///   `SOURCE_DATE_EPOCH` is not valid.
+///
+pub fn creation_date(metadata: &MetadataConfig) -> Result<String, Box<dyn std::error::Error>> {
+    let source_date_epoch = std::env::var("SOURCE_DATE_EPOCH").ok();
+
+    resolve_creation_date(metadata.creation_date(), source_date_epoch.as_deref(), now())
+        .map_err(Into::into)
+}
+
+/// Set the document information of a PDF surface. Absent values are left
+/// out.
+///
+/// # Arguments
+///
+/// * `surface` — the PDF surface being written.
+///
+/// * `metadata` — the `[metadata]` configuration.
+///
+/// * `title` — the document title, used when `[metadata] title` is absent.
+///
+/// * `creation_date` — the creation date from [`creation_date()`].
+///
+/// # Returns
+///
+/// * [`std::error::Error`] — if Cairo fails to set a value.
+///
+pub fn set_pdf_metadata(surface: &PdfSurface,
+    metadata: &MetadataConfig,
+    title: &str,
+    creation_date: &str
+) -> Result<(), Box<dyn std::error::Error>> {
+    let keywords = metadata.keywords().join(", ");
+
+    let values = [
+        (PdfMetadata::Title, Some(metadata.title().unwrap_or(title))),
+        (PdfMetadata::Author, metadata.author()),
+        (PdfMetadata::Subject, metadata.subject()),
+        (PdfMetadata::Keywords, Some(keywords.as_str())),
+        (PdfMetadata::Creator, metadata.creator()),
+        (PdfMetadata::CreateDate, Some(creation_date)),
+    ];
+
+    for (name, value) in values {
+        if let Some(value) = value.filter(|value| !value.is_empty()) {
+            surface.set_metadata(name, value)?;
+        }
+    }
+
+    Ok(())
+}
+
+// To run test for this module only:
+//
+//     * cargo test pdf_metadata::tests
+//
+//     * cargo test pdf_metadata::tests::test_resolve_creation_date -- --exact [--nocapture]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_creation_date() {
+        let now = 1_792_195_200;
+
+        assert_eq!(resolve_creation_date(Some("2026-01-26"), Some("0"), now),
+            Ok("2026-01-26T00:00:00".to_string()));
+        assert_eq!(resolve_creation_date(None, Some("86400"), now),
+            Ok("1970-01-02T00:00:00Z".to_string()));
+        assert_eq!(resolve_creation_date(None, None, now),
+            Ok("2026-10-17T00:00:00Z".to_string()));
+
+        assert!(resolve_creation_date(Some("26/01/2026"), None, now).is_err());
+        assert!(resolve_creation_date(None, Some("yesterday"), now).is_err());
+    }
+}
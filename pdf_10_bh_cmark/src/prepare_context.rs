@@ -0,0 +1,43 @@
+/* 17/10/2026 */
+
+//!
+//! What preparing the blocks of a document shares across all of them: the
+//! figure numbers, the Cairo context the layouts are created in, and where
+//! images are looked for.
+//!
+//! The configuration and the page geometry are not part of it: they change
+//! from block to block, within blockquotes and columns.
+//!
+
+use std::path::PathBuf;
+
+use cairo::Context;
+
+use crate::figure_numbering::Figures;
+
+/// The document-wide state the `prepare_*` functions lay blocks out with.
+pub struct PrepareContext<'a> {
+    figures: &'a Figures,
+    context: &'a Context,
+    /// The directories image paths are resolved against, see
+    /// [`crate::image_loader`].
+    image_dirs: &'a [PathBuf],
+}
+
+impl<'a> PrepareContext<'a> {
+    pub fn new(figures: &'a Figures, context: &'a Context, image_dirs: &'a [PathBuf]) -> Self {
+        PrepareContext { figures, context, image_dirs }
+    }
+
+    pub fn figures(&self) -> &'a Figures {
+        self.figures
+    }
+
+    pub fn context(&self) -> &'a Context {
+        self.context
+    }
+
+    pub fn image_dirs(&self) -> &'a [PathBuf] {
+        self.image_dirs
+    }
+}
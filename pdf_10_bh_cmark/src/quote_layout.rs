@@ -0,0 +1,201 @@
+/* 17/10/2026 */
+
+//!
+//! Layout blockquotes onto a PDF. The blocks within a quote are laid out as
+//! usual, only narrower: each nesting level moves the left margin in by the
+//! `[quote]` indent. A vertical rule is drawn at the start of the indent,
+//! alongside the quoted blocks.
+//!
+//! A quote which breaks across pages or columns gets a separate rule in each
+//! of them, covering only the part of the quote there.
+//!
+
+use cairo::Context;
+
+use crate::config::Config;
+use crate::document::{Block, Frame, PositionedBlock};
+use crate::page_geometry::PageGeometry;
+
+/// The blockquote nesting depth of each block, `0` for blocks outside any
+/// quote. [`Block::QuoteStart`] and [`Block::QuoteEnd`] get the depth of
+/// the quote they open or close.
+pub fn quote_depths(blocks: &[Block]) -> Vec<usize> {
+    let mut depth: usize = 0;
+
+    blocks.iter().map(|block| {
+        match block {
+            Block::QuoteStart => {
+                depth += 1;
+                depth
+            },
+            Block::QuoteEnd => {
+                depth = depth.saturating_sub(1);
+                depth + 1
+            },
+            _ => depth,
+        }
+    }).collect()
+}
+
+/// The page geometry for a block at a blockquote nesting depth.
+pub fn quote_geometry(depth: usize,
+    config: &Config,
+    page_geometry: &PageGeometry
+) -> PageGeometry {
+    page_geometry.inset(depth as f64 * config.quote().indent())
+}
+
+/// Create the [`PositionedBlock::QuoteRule`] fragments of a blockquote, one
+/// for each frame, a page or a column, the quote spans.
+///
+/// # Arguments
+///
+/// * `block_index` — index to the [`Block::QuoteEnd`] closing the quote.
+///
+/// * `fragments` — the fragments of the quoted blocks. A quote without any
+///   fragment gets no rule.
+///
+/// * `start_frame`, `start_y` — where the quote starts. The start is ignored
+///   if the first fragment went into a later frame.
+///
+/// * `end_y` — where the quote ends in the frame of its last fragment.
+///
+/// * `page_geometry` — the page size and margins in effect.
+///
+pub fn quote_rules(block_index: usize,
+    fragments: &[PositionedBlock],
+    start_frame: Frame,
+    start_y: f64,
+    end_y: f64,
+    page_geometry: &PageGeometry,
+) -> Vec<PositionedBlock> {
+    // The rules of nested quotes come after the fragments they cover: take 
+    // each frame once, in the order the quote reaches it.
+    let mut frames: Vec<Frame> = Vec::new();
+    for frame in fragments.iter().map(PositionedBlock::frame) {
+        if !frames.contains(&frame) { frames.push(frame); }
+    }
+
+    let Some(&last) = frames.last() else {
+        return Vec::new();
+    };
+
+    frames.into_iter().filter_map(|frame| {
+        let y_start = if frame == start_frame { start_y } else { frame.top() };
+        let y_end = if frame == last { end_y } else { page_geometry.content_bottom() };
+
+        (y_end > y_start).then(|| PositionedBlock::quote_rule(block_index, frame, y_start, y_end))
+    }).collect()
+}
+
+/// Render a [`PositionedBlock::QuoteRule`] fragment.
+///
+/// # Arguments
+///
+/// * `y_start`, `y_end` — the vertical extent of the rule.
+///
+/// * `config` — the rule width and colour, and the quote indent.
+///
+/// * `context` — the Cairo PDF [`Context`].
+///
+/// * `page_geometry` — the page geometry of the quoted blocks: the rule is
+///   drawn one indent to the left of their content.
+///
+pub fn render_quote_rule(y_start: f64,
+    y_end: f64,
+    config: &Config,
+    context: &Context,
+    page_geometry: &PageGeometry,
+) {
+    let quote_config = config.quote();
+    let x = page_geometry.margins().left - quote_config.indent() + quote_config.rule_width() / 2.0;
+
+    context.save().expect("Failed to save Cairo context");
+
+    context.move_to(x, y_start);
+    context.line_to(x, y_end);
+
+    context.set_line_width(quote_config.rule_width());
+
+    context.set_source_rgb(quote_config.rule_colour().r(),
+        quote_config.rule_colour().g(),
+        quote_config.rule_colour().b());
+    let _ = context.stroke();
+
+    // Restore the original context.
+    context.restore().expect("Failed to restore Cairo context");
+}
+
+// To run test for this module only:
+//
+//     * cargo test quote_layout::tests
+//
+//     * cargo test quote_layout::tests::test_quote_depths -- --exact [--nocapture]
+//     * cargo test quote_layout::tests::test_quote_rules -- --exact [--nocapture]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::CodeBlock;
+
+    fn code() -> Block {
+        Block::Code(CodeBlock::new(String::new()))
+    }
+
+    #[test]
+    fn test_quote_depths() {
+        let blocks = vec![code(), Block::QuoteStart, code(), Block::QuoteStart, code(),
+            Block::QuoteEnd, code(), Block::QuoteEnd, code()];
+
+        assert_eq!(quote_depths(&blocks), vec![0, 1, 1, 2, 2, 2, 1, 1, 0]);
+    }
+
+    #[test]
+    fn test_quote_rules() {
+        let page_geometry = PageGeometry::default();
+        let top = page_geometry.margins().top;
+        let bottom = page_geometry.content_bottom();
+
+        let page = |page: usize| Frame::new(page, 0, top);
+
+        // The quote spans pages 1 to 3.
+        let fragments = vec![
+            PositionedBlock::paragraph(1, page(1), 700.0, 0, 2),
+            PositionedBlock::paragraph(1, page(2), top, 2, 50),
+            PositionedBlock::paragraph(2, page(3), top, 0, 3),
+        ];
+
+        let rules = quote_rules(3, &fragments, page(1), 690.0, 200.0, &page_geometry);
+
+        let extents: Vec<(usize, f64, f64)> = rules.iter().map(|rule| match rule {
+            PositionedBlock::QuoteRule { frame, y_start, y_end, .. } => (frame.page(), *y_start, *y_end),
+            _ => panic!("Expected PositionedBlock::QuoteRule"),
+        }).collect();
+
+        assert_eq!(extents, vec![(1, 690.0, bottom), (2, top, bottom), (3, top, 200.0)]);
+
+        // The first fragment went onto a new page: no rule on the start page.
+        let rules = quote_rules(3, &fragments[1..], page(1), 690.0, 200.0, &page_geometry);
+        assert_eq!(rules.len(), 2, "rules from page 2");
+        assert_eq!(rules[0].page(), 2, "first rule page");
+
+        assert!(quote_rules(3, &[], page(1), 690.0, 200.0, &page_geometry).is_empty(), "empty quote");
+
+        // Two columns starting at 300: the quote moves into the second one, 
+        // after a nested quote.
+        let column = |column: usize| Frame::new(1, column, 300.0);
+        let fragments = vec![
+            PositionedBlock::paragraph(1, column(0), 700.0, 0, 2),
+            PositionedBlock::paragraph(1, column(1), 300.0, 2, 5),
+            PositionedBlock::quote_rule(2, column(0), 710.0, bottom),
+        ];
+
+        let rules = quote_rules(3, &fragments, column(0), 690.0, 400.0, &page_geometry);
+        let extents: Vec<(Frame, f64, f64)> = rules.iter().map(|rule| match rule {
+            PositionedBlock::QuoteRule { frame, y_start, y_end, .. } => (*frame, *y_start, *y_end),
+            _ => panic!("Expected PositionedBlock::QuoteRule"),
+        }).collect();
+
+        assert_eq!(extents, vec![(column(0), 690.0, bottom), (column(1), 300.0, 400.0)],
+            "columns");
+    }
+}
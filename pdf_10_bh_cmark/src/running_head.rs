@@ -0,0 +1,165 @@
+/* 17/10/2026 */
+
+//!
+//! Running heads: the chapter, a level 1 heading, and the section, a level
+//! 2 heading, in effect on each page, for the `{chapter}` and `{section}`
+//! placeholders of the page headers and footers. A new chapter clears the
+//! section.
+//!
+//! When a heading begins part way down a page, `[running_head] mark`
+//! decides which heading the page shows, after the CSS Generated Content
+//! for Paged Media `string()` function:
+//!
+//! * "first" — the first heading begun on the page, otherwise the one
+//!   carried over from the pages before.
+//! * "start" — the heading in effect at the top of the page: the one
+//!   carried over, unless the page begins with a heading.
+//! * "last" — the last heading begun on or before the page.
+//!
+
+use crate::config::RunningHeadMark;
+
+/// Where a level 1 or 2 heading begins.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeadingMark {
+    /// Heading level, `1` or `2`.
+    level: u8,
+    title: String,
+    /// The 1-based page number of the heading's first fragment.
+    page: usize,
+    /// Whether the heading is the first thing on its page.
+    page_start: bool,
+}
+
+impl HeadingMark {
+    pub fn new(level: u8, title: &str, page: usize, page_start: bool) -> Self {
+        HeadingMark { level, title: title.to_string(), page, page_start }
+    }
+}
+
+/// The headings in effect on a page.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RunningHead {
+    chapter: String,
+    section: String,
+}
+
+impl RunningHead {
+    /// The level 1 heading, empty before the first one.
+    pub fn chapter(&self) -> &str {
+        &self.chapter
+    }
+
+    /// The level 2 heading, or the chapter when it has no section in
+    /// effect.
+    pub fn section(&self) -> &str {
+        if self.section.is_empty() { &self.chapter } else { &self.section }
+    }
+}
+
+/// A value set by headings, `(page, page_start, value)`, on each page.
+fn page_values(assignments: &[(usize, bool, &str)], 
+    total_pages: usize, 
+    mark: RunningHeadMark,
+) -> Vec<String> {
+    let mut values: Vec<String> = Vec::with_capacity(total_pages);
+    let mut assignments = assignments.iter().peekable();
+    let mut carried = "";
+
+    for page in 1..=total_pages {
+        let mut on_page = Vec::new();
+        while let Some(assignment) = assignments.next_if(|(at, _, _)| *at <= page) {
+            on_page.push(*assignment);
+        }
+
+        let value = match mark {
+            RunningHeadMark::First => on_page.first().map_or(carried, |(_, _, value)| *value),
+            RunningHeadMark::Start => on_page.first()
+                .filter(|(_, page_start, _)| *page_start)
+                .map_or(carried, |(_, _, value)| *value),
+            RunningHeadMark::Last => on_page.last().map_or(carried, |(_, _, value)| *value),
+        };
+        values.push(value.to_string());
+
+        if let Some((_, _, value)) = on_page.last() { carried = value; }
+    }
+
+    values
+}
+
+/// The running head of each page.
+///
+/// # Arguments
+///
+/// * `headings` — the level 1 and 2 headings in document order. Deeper
+///   headings are ignored.
+///
+/// * `total_pages` — the number of pages.
+///
+/// * `mark` — "first", "start" or "last", see the module documentation.
+///
+pub fn running_heads(headings: &[HeadingMark], 
+    total_pages: usize, 
+    mark: RunningHeadMark,
+) -> Vec<RunningHead> {
+    let chapters: Vec<(usize, bool, &str)> = headings.iter()
+        .filter(|heading| heading.level == 1)
+        .map(|heading| (heading.page, heading.page_start, heading.title.as_str()))
+        .collect();
+
+    // A new chapter clears the section.
+    let sections: Vec<(usize, bool, &str)> = headings.iter()
+        .filter(|heading| heading.level <= 2)
+        .map(|heading| (heading.page, heading.page_start,
+            if heading.level == 1 { "" } else { heading.title.as_str() }))
+        .collect();
+
+    page_values(&chapters, total_pages, mark).into_iter()
+        .zip(page_values(&sections, total_pages, mark))
+        .map(|(chapter, section)| RunningHead { chapter, section })
+        .collect()
+}
+
+// To run test for this module only:
+//
+//     * cargo test running_head::tests
+//
+//     * cargo test running_head::tests::test_running_heads -- --exact [--nocapture]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_running_heads() {
+        let headings = vec![
+            HeadingMark::new(1, "One", 1, true),
+            HeadingMark::new(2, "One a", 2, false),
+            HeadingMark::new(2, "One b", 2, false),
+            HeadingMark::new(1, "Two", 4, false),
+            HeadingMark::new(2, "Two a", 5, true),
+            HeadingMark::new(3, "Two a i", 5, false),
+        ];
+
+        let heads = |mark: RunningHeadMark| -> Vec<(String, String)> {
+            running_heads(&headings, 6, mark).iter()
+                .map(|head| (head.chapter().to_string(), head.section().to_string()))
+                .collect()
+        };
+        let expected = |pages: &[(&str, &str)]| -> Vec<(String, String)> {
+            pages.iter().map(|(chapter, section)| (chapter.to_string(), section.to_string()))
+                .collect()
+        };
+
+        assert_eq!(heads(RunningHeadMark::First), expected(&[("One", "One"), ("One", "One a"),
+            ("One", "One b"), ("Two", "Two"), ("Two", "Two a"), ("Two", "Two a")]));
+
+        assert_eq!(heads(RunningHeadMark::Start), expected(&[("One", "One"), ("One", "One"),
+            ("One", "One b"), ("One", "One b"), ("Two", "Two a"), ("Two", "Two a")]));
+
+        assert_eq!(heads(RunningHeadMark::Last), expected(&[("One", "One"), ("One", "One b"),
+            ("One", "One b"), ("Two", "Two"), ("Two", "Two a"), ("Two", "Two a")]));
+
+        // No headings.
+        assert_eq!(running_heads(&[], 2, RunningHeadMark::First), vec![RunningHead::default(); 2]);
+    }
+}
@@ -0,0 +1,354 @@
+/* 17/10/2026 */
+
+//!
+//! Layout GFM-style tables onto a PDF.
+//!
+//! Column widths come from the Pango-measured cell content: each column gets
+//! at least its widest word, and the remaining content width is shared out in
+//! proportion to how much wider each column would be unwrapped. Cell text
+//! wraps within its column.
+//!
+//! A row never breaks across pages. When a table continues onto a new page,
+//! its header row is repeated at the top, unless `[table] repeat_header` is
+//! off.
+//!
+
+use cairo::Context;
+use pango::{Alignment, FontDescription, Layout, Weight, WrapMode};
+use pangocairo::functions::{create_layout, show_layout};
+
+use bh_cmark::ast::InlineContent;
+
+use crate::column_layout::FrameCursor;
+use crate::config::{ColourRGB, Config};
+use crate::document::{ColumnAlignment, PositionedBlock, TableBlock};
+use crate::figure_numbering::Figures;
+use crate::link_layout::{LayoutLink, set_inline_content, tag_layout_links};
+use crate::page_geometry::PageGeometry;
+use crate::text_layout::{
+    layout_block_height,
+    layout_width,
+    logical_width,
+    set_layout_language,
+};
+
+/// The cached layout of a table cell.
+#[derive(Debug)]
+pub struct PreparedTableCell {
+    layout: Layout,
+    /// The links within the `layout` text.
+    links: Vec<LayoutLink>,
+}
+
+/// The cached cell layouts of a table row.
+#[derive(Debug)]
+pub struct PreparedTableRow {
+    cells: Vec<PreparedTableCell>,
+    /// The tallest cell, plus the cell padding above and below.
+    height: f64,
+}
+
+/// The cached [`pango::Layout`]s of a table, reused for both measurement
+/// and rendering.
+#[derive(Debug)]
+pub struct PreparedTable {
+    /// The text width of each column, excluding the cell padding.
+    column_widths: Vec<f64>,
+    /// `rows[0]` is the header row.
+    rows: Vec<PreparedTableRow>,
+}
+
+impl PreparedTable {
+    /// The header row and the first body row, which are kept on the same 
+    /// page.
+    pub fn leading_height(&self) -> f64 {
+        self.rows.iter().take(2).map(|row| row.height).sum()
+    }
+}
+
+/// Work out the column widths of a table.
+///
+/// # Arguments
+///
+/// * `min_widths` — the widest word of each column.
+///
+/// * `max_widths` — the widest unwrapped cell of each column.
+///
+/// * `available` — the total width available to the columns.
+///
+/// # Returns
+///
+/// The natural widths when they all fit. Otherwise each column gets its
+/// minimum width plus a share of the remaining space, in proportion to
+/// `max - min`. When even the minimum widths do not fit, they are scaled
+/// down and words break within.
+///
+pub fn column_widths(min_widths: &[f64], max_widths: &[f64], available: f64) -> Vec<f64> {
+    let max_widths: Vec<f64> = max_widths.iter().zip(min_widths)
+        .map(|(max, min)| max.max(*min))
+        .collect();
+
+    let min_total: f64 = min_widths.iter().sum();
+    let max_total: f64 = max_widths.iter().sum();
+
+    if max_total <= available {
+        return max_widths;
+    }
+
+    if min_total >= available {
+        let scale = if min_total > 0.0 { available / min_total } else { 0.0 };
+        return min_widths.iter().map(|min| min * scale).collect();
+    }
+
+    let ratio = (available - min_total) / (max_total - min_total);
+
+    min_widths.iter().zip(&max_widths)
+        .map(|(min, max)| min + (max - min) * ratio)
+        .collect()
+}
+
+/// An unwrapped cell layout: its width is set once the column widths are
+/// known.
+fn cell_layout(content: Option<&InlineContent>,
+    font_description: &FontDescription,
+    alignment: ColumnAlignment,
+    config: &Config,
+    context: &Context,
+    figures: &Figures
+) -> PreparedTableCell {
+    let layout: Layout = create_layout(context);
+
+    layout.set_wrap(WrapMode::WordChar);
+    layout.set_font_description(Some(font_description));
+    set_layout_language(&layout, config.metadata().language());
+    layout.set_alignment(match alignment {
+        ColumnAlignment::Left => Alignment::Left,
+        ColumnAlignment::Centre => Alignment::Center,
+        ColumnAlignment::Right => Alignment::Right,
+    });
+
+    let links = match content {
+        Some(content) => set_inline_content(&layout, content, config.link(), figures),
+        None => Vec::new(),
+    };
+
+    PreparedTableCell { layout, links }
+}
+
+/// Create the cell [`pango::Layout`]s of a table, and size its columns to
+/// fit [`PageGeometry::content_width()`].
+///
+/// # Arguments
+///
+/// * `table` — the semantic table.
+///
+/// * `config` — the paragraph font and the `[table]` configuration.
+///
+/// * `page_geometry` — the page size and margins in effect.
+///
+/// * `context` — the Cairo PDF [`Context`].
+///
+/// * `figures` — resolves the figure references in the cell text.
+///
+pub fn prepare_table(table: &TableBlock,
+    config: &Config,
+    page_geometry: &PageGeometry,
+    context: &Context,
+    figures: &Figures
+) -> PreparedTable {
+    let table_config = config.table();
+    let padding = table_config.cell_padding();
+
+    let body_description = config.fonts().paragraph().to_pango_description();
+    let header_description = match table_config.header_font() {
+        Some(font_spec) => font_spec.to_pango_description(),
+        None => {
+            let mut description = body_description.clone();
+            description.set_weight(Weight::Bold);
+            description
+        },
+    };
+
+    let rows: Vec<Vec<PreparedTableCell>> = std::iter::once(table.header())
+        .chain(table.rows().iter().map(|row| row.as_slice()))
+        .enumerate()
+        .map(|(row_index, row)| {
+            let font_description = if row_index == 0 { &header_description } else { &body_description };
+
+            row.iter().zip(table.alignments())
+                .map(|(cell, alignment)| cell_layout(cell.as_ref(), font_description,
+                    *alignment, config, context, figures))
+                .collect()
+        })
+        .collect();
+
+    // Measure the widest word and the unwrapped width of each column.
+    let columns = table.alignments().len();
+    let mut min_widths = vec![0.0_f64; columns];
+    let mut max_widths = vec![0.0_f64; columns];
+
+    let word_layout: Layout = create_layout(context);
+    for row in &rows {
+        for (column, PreparedTableCell { layout, .. }) in row.iter().enumerate() {
+            max_widths[column] = max_widths[column].max(logical_width(layout));
+
+            word_layout.set_font_description(layout.font_description().as_ref());
+            for word in layout.text().split_whitespace() {
+                word_layout.set_text(word);
+                min_widths[column] = min_widths[column].max(logical_width(&word_layout));
+            }
+        }
+    }
+
+    let available = (page_geometry.content_width() - 2.0 * padding * columns as f64).max(0.0);
+    let column_widths = column_widths(&min_widths, &max_widths, available);
+
+    let rows = rows.into_iter().map(|cells| {
+        for (cell, width) in cells.iter().zip(&column_widths) {
+            cell.layout.set_width(layout_width(*width));
+        }
+
+        let height = cells.iter().map(|cell| layout_block_height(&cell.layout))
+            .fold(0.0, f64::max) + 2.0 * padding;
+
+        PreparedTableRow { cells, height }
+    }).collect();
+
+    PreparedTable { column_widths, rows }
+}
+
+/// Paginate a table into [`PositionedBlock::TableRow`] fragments.
+///
+/// The header row is kept in the same frame as the first body row. A row
+/// which does not fit goes into the next frame, a column or a new page,
+/// below a repeated header row. A row taller than a page is placed
+/// regardless, and overflows.
+///
+/// # Arguments
+///
+/// * `table` — the prepared table.
+///
+/// * `block_index` — index to the semantic table block.
+///
+/// * `pos_blocks` — the fragments are appended to this vector.
+///
+/// * `cursor` — the pagination state, updated to just after the table,
+///   including its spacing after.
+///
+/// * `config` — table spacing and header repetition.
+///
+/// * `page_geometry` — the page size and margins in effect.
+///
+pub fn measure_table(table: &PreparedTable,
+    block_index: usize,
+    pos_blocks: &mut Vec<PositionedBlock>,
+    cursor: &mut FrameCursor,
+    config: &Config,
+    page_geometry: &PageGeometry,
+) {
+    let header_height = table.rows[0].height;
+
+    cursor.y += cursor.collapse_spacing(config.block_spacing().table().before(), cursor.y);
+
+    if cursor.y + table.leading_height() > page_geometry.content_bottom()
+        && cursor.y > page_geometry.margins().top {
+        cursor.next_frame_for(table.leading_height());
+    }
+
+    cursor.y_offset = cursor.y;
+    pos_blocks.push(PositionedBlock::table_row(block_index, 0, cursor.frame(), cursor.y));
+    cursor.y += header_height;
+
+    // Body rows placed in the current frame.
+    let mut page_rows = 0;
+
+    for (row_index, row) in table.rows.iter().enumerate().skip(1) {
+        if cursor.y + row.height > page_geometry.content_bottom() && page_rows > 0 {
+            let repeat_height = if config.table().repeat_header() { header_height } else { 0.0 };
+            cursor.next_frame_for(repeat_height + row.height);
+            page_rows = 0;
+
+            if config.table().repeat_header() {
+                pos_blocks.push(PositionedBlock::table_row(block_index, 0, cursor.frame(), cursor.y));
+                cursor.y += header_height;
+            }
+        }
+
+        pos_blocks.push(PositionedBlock::table_row(block_index, row_index, cursor.frame(), cursor.y));
+        cursor.y += row.height;
+        page_rows += 1;
+    }
+
+    cursor.y += config.block_spacing().table().after();
+    cursor.y_offset = cursor.y;
+}
+
+/// Render a [`PositionedBlock::TableRow`] fragment: for each cell, the header
+/// shading, the text, then the border.
+pub fn render_table_row(table: &PreparedTable,
+    row_index: usize,
+    y_offset: f64,
+    config: &Config,
+    context: &Context,
+    page_geometry: &PageGeometry,
+) {
+    let table_config = config.table();
+    let padding = table_config.cell_padding();
+    let row = &table.rows[row_index];
+
+    let set_colour = |colour: &ColourRGB| context.set_source_rgb(colour.r(), colour.g(), colour.b());
+
+    let mut x = page_geometry.margins().left;
+
+    for (cell, column_width) in row.cells.iter().zip(&table.column_widths) {
+        let cell_width = column_width + 2.0 * padding;
+
+        if row_index == 0 && let Some(background) = table_config.header_background() {
+            context.save().expect("Failed to save Cairo context");
+            context.rectangle(x, y_offset, cell_width, row.height);
+            set_colour(background);
+            let _ = context.fill();
+            context.restore().expect("Failed to restore Cairo context");
+        }
+
+        context.move_to(x + padding, y_offset + padding);
+        show_layout(context, &cell.layout);
+        tag_layout_links(context, &cell.layout, &cell.links, x + padding, y_offset + padding);
+
+        if table_config.border_width() > 0.0 {
+            context.save().expect("Failed to save Cairo context");
+            context.rectangle(x, y_offset, cell_width, row.height);
+            context.set_line_width(table_config.border_width());
+            set_colour(table_config.border_colour());
+            let _ = context.stroke();
+            context.restore().expect("Failed to restore Cairo context");
+        }
+
+        x += cell_width;
+    }
+}
+
+// To run test for this module only:
+//
+//     * cargo test table_layout::tests
+//
+//     * cargo test table_layout::tests::test_column_widths -- --exact [--nocapture]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_column_widths() {
+        // Natural widths fit.
+        assert_eq!(column_widths(&[10.0, 20.0], &[50.0, 100.0], 200.0), vec![50.0, 100.0]);
+
+        // The remaining 60 is shared 1:3.
+        assert_eq!(column_widths(&[10.0, 20.0], &[50.0, 140.0], 90.0), vec![25.0, 65.0]);
+
+        // Not even the widest words fit.
+        assert_eq!(column_widths(&[40.0, 60.0], &[50.0, 140.0], 50.0), vec![20.0, 30.0]);
+
+        // An empty column.
+        assert_eq!(column_widths(&[0.0, 20.0], &[0.0, 140.0], 80.0), vec![0.0, 80.0]);
+    }
+}
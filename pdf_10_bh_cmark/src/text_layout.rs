@@ -4,10 +4,12 @@ use cairo::glib::translate::ToGlibPtr;
 use pango_sys::pango_layout_set_justify;
 use cairo::Context;
 use pango::{Layout, WrapMode};
-use pangocairo::functions::create_layout;
+use pangocairo::functions::{create_layout, show_layout_line};
 
-use crate::page_geometry::a4_default_content_width;
+use crate::column_layout::FrameCursor;
 use crate::config::FontSpec;
+use crate::document::Frame;
+use crate::page_geometry::PageGeometry;
 
 pub trait LayoutExtJustify {
     fn set_justify(&self, justify: bool);
@@ -22,9 +24,9 @@ impl LayoutExtJustify for Layout {
     }
 }
 
-/// A4 default content width in [`pango::SCALE`].
-pub fn a4_layout_width() -> i32 {
-    (a4_default_content_width() * pango::SCALE as f64) as i32
+/// A content width in points converted to [`pango::SCALE`].
+pub fn layout_width(width: f64) -> i32 {
+    (width * pango::SCALE as f64) as i32
 }
 
 /// Create a [`pango::Layout`] text layout for.
@@ -50,6 +52,16 @@ pub fn create_text_layout(layout_width: i32,
     layout
 }
 
+/// Set the language of the text of a [`pango::Layout`], such as "vi", which
+/// guides Pango's line breaking and shaping. `None` keeps the locale's 
+/// language.
+pub fn set_layout_language(layout: &Layout, language: Option<&str>) {
+    if let Some(language) = language {
+        layout.context().set_language(Some(&pango::Language::from_string(language)));
+        layout.context_changed();
+    }
+}
+
 /// Work out and return a fully prepared [`pango::Layout`] height for 
 /// a block of text.
 /// 
@@ -59,6 +71,12 @@ pub fn layout_block_height(layout: &Layout) -> f64 {
     height.height() as f64 / pango::SCALE as f64
 }
 
+/// The logical width of a [`pango::Layout`].
+pub fn logical_width(layout: &Layout) -> f64 {
+    let (_, logical) = layout.extents();
+    logical.width() as f64 / pango::SCALE as f64
+}
+
 /// Centre a [`pango::Layout`] block. Note, the text block is either 
 /// left and right justified, or only left-justified.
 /// 
@@ -82,3 +100,277 @@ pub fn center_layout_block(context: &Context, layout: &Layout, page_width: f64)
 
     context.rel_move_to(offset_x, 0.0);
 }
+
+/// The fewest lines of a block left on either side of a page break.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MinLines {
+    /// Lines of the block at the bottom of the page before the break.
+    orphans: usize,
+    /// Lines of the block at the top of the page after the break.
+    widows: usize,
+}
+
+impl MinLines {
+    /// Values below `1` count as `1`.
+    pub fn new(orphans: usize, widows: usize) -> Self {
+        MinLines { orphans: orphans.max(1), widows: widows.max(1) }
+    }
+
+    pub fn orphans(&self) -> usize {
+        self.orphans
+    }
+
+    pub fn widows(&self) -> usize {
+        self.widows
+    }
+}
+
+/// Breaks at the first line which does not fit.
+impl Default for MinLines {
+    fn default() -> Self {
+        MinLines::new(1, 1)
+    }
+}
+
+/// Work out where a block of lines breaks onto new frames: the columns left 
+/// on the page, then new pages.
+/// 
+/// A break goes before the first line which does not fit, then moves up to 
+/// leave `min_lines.widows()` lines after it. If that leaves fewer than 
+/// `min_lines.orphans()` lines in the block's first frame, the whole block 
+/// moves to the next frame instead. When the lines cannot be kept together 
+/// either way, such as a block starting at the top of a page, the break 
+/// stays before the first line which does not fit.
+/// 
+/// # Arguments
+/// 
+/// * `line_heights` — the height of each line in the block.
+/// 
+/// * `y` — where the first line goes.
+/// 
+/// * `next_tops` — where lines go in each of the frames left on the page, 
+///   see [`FrameCursor::next_tops()`].
+/// 
+/// * `top`, `bottom` — where lines go on a new page, and the limit they 
+///   must fit above.
+/// 
+/// * `min_lines` — the widow and orphan control.
+/// 
+/// # Returns
+/// 
+/// The index of the first line in each new frame, in order. A break at `0` 
+/// moves the whole block onto the next frame. A frame too short for a 
+/// single line gives the same break twice.
+/// 
+pub fn page_breaks(line_heights: &[f64],
+    y: f64,
+    next_tops: &[f64],
+    top: f64,
+    bottom: f64,
+    min_lines: MinLines,
+) -> Vec<usize> {
+    let line_count = line_heights.len();
+    let mut breaks: Vec<usize> = Vec::new();
+
+    // The first line of the current fragment, and where it starts.
+    let mut start_line: usize = 0;
+    let mut fragment_top = y;
+
+    let mut y = y;
+    let mut line_index: usize = 0;
+    while line_index < line_count {
+        // A fragment at the top of a page takes at least one line.
+        let may_break = line_index > start_line || fragment_top > top;
+
+        if may_break && y + line_heights[line_index] > bottom {
+            let min_before = if start_line == 0 { min_lines.orphans() } else { 1 };
+            let mut line_break = line_index.min(line_count.saturating_sub(min_lines.widows()));
+
+            if line_break < start_line + min_before {
+                line_break = if start_line == 0 && fragment_top > top { 0 } else { line_index };
+            }
+
+            breaks.push(line_break);
+            start_line = line_break;
+            line_index = line_break;
+            fragment_top = next_tops.get(breaks.len() - 1).copied().unwrap_or(top);
+            y = fragment_top;
+            continue;
+        }
+
+        y += line_heights[line_index];
+        line_index += 1;
+    }
+
+    breaks
+}
+
+/// Split a block of lines, such as a paragraph, into frame fragments.
+/// 
+/// Lines are placed from `cursor.y` downwards, breaking onto new frames 
+/// where [`page_breaks()`] says. Each new fragment starts at the top of the 
+/// next frame. 
+/// 
+/// # Arguments
+/// 
+/// * `line_heights` — the height of each line in the block.
+/// 
+/// * `cursor` — where the first line goes, advanced as the block flows onto 
+///   new frames. On return, `cursor.y` is the y-coordinate just after the 
+///   last line, and `cursor.y_offset` that of the last fragment.
+/// 
+/// * `page_geometry` — the page size and margins in effect.
+/// 
+/// * `min_lines` — the widow and orphan control.
+/// 
+/// * `emit` — called with `(frame, y_offset, line_start, line_end)` for each 
+///   non-empty fragment, `line_end` is exclusive.
+/// 
+pub fn paginate_lines(line_heights: &[f64],
+    cursor: &mut FrameCursor,
+    page_geometry: &PageGeometry,
+    min_lines: MinLines,
+    mut emit: impl FnMut(Frame, f64, usize, usize),
+) {
+    let breaks = page_breaks(line_heights, cursor.y, &cursor.next_tops(), 
+        page_geometry.margins().top, page_geometry.content_bottom(), min_lines);
+
+    cursor.y_offset = cursor.y;
+
+    let mut breaks = breaks.into_iter().peekable();
+    let mut start_line: usize = 0;
+    for (line_index, line_height) in line_heights.iter().enumerate() {
+        while breaks.next_if_eq(&line_index).is_some() {
+            // The block spans multiple fragments.
+            if line_index > start_line {
+                emit(cursor.frame(), cursor.y_offset, start_line, line_index);
+            }
+
+            start_line = line_index;
+            cursor.next_frame();
+        }
+
+        // Advance y so the next line does not overlap.
+        cursor.y += line_height;
+    }
+
+    if line_heights.len() > start_line {
+        emit(cursor.frame(), cursor.y_offset, start_line, line_heights.len());
+    }
+}
+
+/// The logical height of a single line within a [`pango::Layout`].
+pub fn measure_line_height(line_index: i32, layout: &Layout) -> f64 {
+    if let Some(line) = layout.line(line_index) {
+        let (_ink, logical) = line.extents();
+        logical.height() as f64 / pango::SCALE as f64
+    } else {
+        panic!("measure_line_height: layout.line({line_index}) returned None");
+
+        // eprintln!("Warning: layout.line({line_index}) returned None");
+        // 0.0
+    }
+}
+
+/// Draw lines `line_start..line_end` of a [`pango::Layout`], the first one at 
+/// `(x, y_offset)`, each following line advanced by its precomputed height.
+pub fn show_lines(context: &Context, 
+    layout: &Layout, 
+    line_heights: &[f64],
+    x: f64,
+    y_offset: f64,
+    line_start: usize,
+    line_end: usize,
+) {
+    let mut y = y_offset;
+    for i in line_start..line_end {
+        if let Some(line) = layout.line(i as i32) {
+            context.move_to(x, y);
+            show_layout_line(context, &line);
+
+            // Use the precomputed line height.
+            y += line_heights[i];
+        }
+    }
+}
+
+// To run test for this module only:
+//
+//     * cargo test text_layout::tests
+//
+//     * cargo test text_layout::tests::test_page_breaks -- --exact [--nocapture]
+//     * cargo test text_layout::tests::test_page_breaks_columns -- --exact [--nocapture]
+//     * cargo test text_layout::tests::test_page_breaks_asymmetric_margins -- --exact [--nocapture]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::page_geometry::{PageMargins, PageSize};
+
+    #[test]
+    fn test_page_breaks() {
+        // Lines 10 high, fitting between 10 and 100: 9 lines to a page.
+        let lines = |count: usize| vec![10.0; count];
+        let (top, bottom) = (10.0, 100.0);
+
+        let test_data: Vec<(Vec<f64>, f64, MinLines, Vec<usize>)> = vec![
+            // No control: break at the first line which does not fit.
+            (lines(10), 60.0, MinLines::default(), vec![4]),
+            (lines(20), 60.0, MinLines::default(), vec![4, 13]),
+            // Only 1 line fits: the block moves, then leaves 2 widows.
+            (lines(10), 85.0, MinLines::new(2, 2), vec![0, 8]),
+            // A single widow pulls a line over.
+            (lines(5), 60.0, MinLines::new(2, 2), vec![3]),
+            // Too short to split: the block moves.
+            (lines(3), 80.0, MinLines::new(2, 2), vec![0]),
+            // Enough widows already.
+            (lines(12), 10.0, MinLines::new(2, 2), vec![9]),
+            // A block starting at the top of a page still leaves 2 widows.
+            (lines(10), 10.0, MinLines::new(2, 2), vec![8]),
+            // Too few lines to satisfy both: break where the lines overflow.
+            (lines(10), 10.0, MinLines::new(9, 2), vec![9]),
+            // A line taller than a page stays at the top.
+            (vec![200.0], 10.0, MinLines::new(2, 2), vec![]),
+            (vec![200.0], 50.0, MinLines::default(), vec![0]),
+            (vec![], 50.0, MinLines::default(), vec![]),
+        ];
+
+        for (index, (line_heights, y, min_lines, expected)) in test_data.iter().enumerate() {
+            assert_eq!(page_breaks(line_heights, *y, &[], top, bottom, *min_lines), *expected,
+                "test data {index}");
+        }
+    }
+
+    #[test]
+    fn test_page_breaks_columns() {
+        // Lines 10 high, as above: `next_tops` gives the columns left.
+        let (top, bottom) = (10.0, 100.0);
+        let breaks = |count: usize, y: f64, next_tops: &[f64], min_lines: MinLines| {
+            page_breaks(&vec![10.0; count], y, next_tops, top, bottom, min_lines)
+        };
+
+        assert_eq!(breaks(20, 60.0, &[10.0], MinLines::default()), vec![4, 13],
+            "a second column from the top of the page, then a new page");
+        assert_eq!(breaks(10, 70.0, &[70.0, 70.0], MinLines::default()), vec![3, 6, 9],
+            "columns starting at 70: 3 lines fit in each of them");
+        assert_eq!(breaks(5, 95.0, &[95.0], MinLines::default()), vec![0, 0],
+            "columns too short for a line: the block moves through them");
+        assert_eq!(breaks(10, 80.0, &[80.0], MinLines::new(3, 2)), vec![0, 0, 8],
+            "orphan control moves the block through the short column");
+    }
+
+    #[test]
+    /// A top margin larger than the bottom one: lines run down to the 
+    /// bottom margin, not the content height measured from the page top.
+    fn test_page_breaks_asymmetric_margins() {
+        let page_geometry = PageGeometry::new(PageSize { width: 420.0, height: 595.22 },
+            PageMargins { top: 150.0, right: 30.0, bottom: 40.0, left: 30.0 });
+        let (top, bottom) = (page_geometry.margins().top, page_geometry.content_bottom());
+
+        // 20 lines of 20 from the top margin end at 550, above 555.22.
+        assert_eq!(page_breaks(&[20.0; 20], top, &[], top, bottom, MinLines::default()),
+            Vec::<usize>::new(), "all lines fit on the page");
+        // The 21st line would end at 570.
+        assert_eq!(page_breaks(&[20.0; 21], top, &[], top, bottom, MinLines::default()),
+            vec![20], "the 21st line goes onto the next page");
+    }
+}
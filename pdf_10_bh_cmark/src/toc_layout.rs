@@ -0,0 +1,342 @@
+/* 17/10/2026 */
+
+//!
+//! Layout the table of contents onto a PDF. The table of contents goes
+//! where the `[TOC]` marker is, or at the start of the document when
+//! `[toc] enabled` is on and there is no marker.
+//!
+//! Each heading down to `[toc] depth` gets an entry: its title, indented by
+//! level, then dot leaders up to its right-aligned page number. The leaders
+//! sit on a fixed grid so they line up from entry to entry. An entry is a
+//! link to its heading.
+//!
+//! The page numbers are only known once the document has been paginated,
+//! and the table of contents itself takes up pages. The document is
+//! therefore paginated first with an empty table of contents, then again
+//! with its entries filled in, until the page numbers settle.
+//!
+
+use cairo::Context;
+use pango::Layout;
+use pangocairo::functions::show_layout_line;
+
+use crate::column_layout::FrameCursor;
+use crate::config::Config;
+use crate::document::{Block, PositionedBlock};
+use crate::link_layout::tag_page_link;
+use crate::outline::OutlineEntry;
+use crate::page_geometry::PageGeometry;
+use crate::text_layout::{
+    create_text_layout,
+    layout_width,
+    logical_width,
+    measure_line_height,
+    show_lines,
+};
+
+/// Minimum space between an entry title and the page number column.
+const NUMBER_GAP: f64 = 18.0;
+
+/// The cached [`pango::Layout`]s of a table of contents entry.
+#[derive(Debug)]
+pub struct PreparedTocEntry {
+    /// The heading title.
+    layout: Layout,
+    /// Distance from the left margin to the title.
+    indent: f64,
+    /// The computed line heights for each line within `layout`.
+    line_heights: Vec<f64>,
+    /// The leaders, on the last line of the title. `None` when there is no
+    /// room for them.
+    leader_layout: Option<Layout>,
+    /// Distance from the left margin to the leaders.
+    leader_indent: f64,
+    /// The page number, right-aligned on the last line of the title.
+    number_layout: Layout,
+    /// The heading the entry links to.
+    destination: OutlineEntry,
+}
+
+impl PreparedTocEntry {
+    pub fn height(&self) -> f64 {
+        self.line_heights.iter().sum()
+    }
+}
+
+/// The cached [`pango::Layout`]s of a table of contents, reused for both
+/// measurement and rendering.
+#[derive(Debug)]
+pub struct PreparedToc {
+    /// `None` when `[toc] title` is empty.
+    title_layout: Option<Layout>,
+    /// The computed line heights for each line within `title_layout`.
+    title_line_heights: Vec<f64>,
+    entries: Vec<PreparedTocEntry>,
+}
+
+/// Put a table of contents at the start of the document, when configured
+/// and the document has no `[TOC]` marker of its own.
+pub fn insert_toc(blocks: &mut Vec<Block>, config: &Config) {
+    if config.toc().enabled() && !blocks.iter().any(|block| matches!(block, Block::Toc)) {
+        blocks.insert(0, Block::Toc);
+    }
+}
+
+/// Where the leaders go between an entry title and its page number.
+///
+/// Leaders are placed on a grid of `leader_width` from the left margin, so
+/// they line up from entry to entry, with at least one leader's width clear
+/// on either side.
+///
+/// # Arguments
+///
+/// * `title_end` — where the last line of the title ends, from the left
+///   margin.
+///
+/// * `number_start` — where the page number column starts, from the left
+///   margin.
+///
+/// * `leader_width` — the width of one leader.
+///
+/// # Returns
+///
+/// Where the first leader starts, from the left margin, and the number of
+/// leaders.
+///
+pub fn leader_span(title_end: f64, number_start: f64, leader_width: f64) -> (f64, usize) {
+    if leader_width <= 0.0 { return (title_end, 0); }
+
+    let first = (title_end / leader_width).ceil() + 1.0;
+    let last = (number_start / leader_width).floor() - 1.0;
+
+    (first * leader_width, (last - first).max(0.0) as usize)
+}
+
+fn line_heights(layout: &Layout) -> Vec<f64> {
+    (0..layout.line_count())
+        .map(|i| measure_line_height(i, layout))
+        .collect()
+}
+
+/// Create the [`pango::Layout`]s of a table of contents.
+///
+/// # Arguments
+///
+/// * `headings` — every heading of the document with where it was placed,
+///   empty before the document has been paginated. Headings deeper than
+///   `[toc] depth` are left out.
+///
+/// * `config` — the `[toc]` configuration, the paragraph font and the
+///   level 1 heading font for the title.
+///
+/// * `page_geometry` — the page size and margins in effect.
+///
+/// * `context` — the Cairo PDF [`Context`].
+///
+pub fn prepare_toc(headings: &[OutlineEntry],
+    config: &Config,
+    page_geometry: &PageGeometry,
+    context: &Context
+) -> PreparedToc {
+    let toc_config = config.toc();
+    let font_spec = toc_config.font().unwrap_or(config.fonts().paragraph());
+    let content_width = page_geometry.content_width();
+
+    let title_layout = (!toc_config.title().is_empty()).then(|| {
+        create_text_layout(layout_width(content_width), toc_config.title(),
+            &config.fonts().headers()[0], false, context)
+    });
+    let title_line_heights = title_layout.as_ref().map_or(Vec::new(), line_heights);
+
+    let headings: Vec<&OutlineEntry> = headings.iter()
+        .filter(|heading| heading.level() <= toc_config.depth())
+        .collect();
+
+    // A width of -1 means no wrapping.
+    let number_layouts: Vec<Layout> = headings.iter()
+        .map(|heading| create_text_layout(-1, &heading.page().to_string(),
+            font_spec, false, context))
+        .collect();
+    let number_column = number_layouts.iter().map(logical_width).fold(0.0, f64::max);
+
+    let leader_width = if toc_config.leader().is_empty() {
+        0.0
+    } else {
+        logical_width(&create_text_layout(-1, toc_config.leader(), font_spec, false, context))
+    };
+
+    let entries = headings.into_iter().zip(number_layouts).map(|(heading, number_layout)| {
+        let indent = heading.level().saturating_sub(1) as f64 * toc_config.indent();
+        let title_width = (content_width - indent - number_column - NUMBER_GAP).max(1.0);
+
+        let layout = create_text_layout(layout_width(title_width), heading.title(),
+            font_spec, false, context);
+        let line_heights = line_heights(&layout);
+
+        let last_line_width = layout.line(layout.line_count() - 1).map_or(0.0, |line| {
+            let (_ink, logical) = line.extents();
+            logical.width() as f64 / pango::SCALE as f64
+        });
+
+        let (leader_indent, leader_count) = leader_span(indent + last_line_width,
+            content_width - number_column, leader_width);
+        let leader_layout = (leader_count > 0).then(|| {
+            create_text_layout(-1, &toc_config.leader().repeat(leader_count),
+                font_spec, false, context)
+        });
+
+        PreparedTocEntry {
+            layout,
+            indent,
+            line_heights,
+            leader_layout,
+            leader_indent,
+            number_layout,
+            destination: heading.clone(),
+        }
+    }).collect();
+
+    PreparedToc { title_layout, title_line_heights, entries }
+}
+
+/// Paginate a table of contents into [`PositionedBlock::TocTitle`] and
+/// [`PositionedBlock::TocEntry`] fragments. An entry never breaks across
+/// frames, and the title is kept in the same frame as the first entry.
+///
+/// # Arguments
+///
+/// * `toc` — the prepared table of contents.
+///
+/// * `block_index` — index to the semantic [`Block::Toc`].
+///
+/// * `pos_blocks` — the fragments are appended to this vector.
+///
+/// * `cursor` — the pagination state, updated to just after the table of
+///   contents, including its spacing after.
+///
+/// * `config` — table of contents spacing.
+///
+/// * `page_geometry` — the page size and margins in effect.
+///
+pub fn measure_toc(toc: &PreparedToc,
+    block_index: usize,
+    pos_blocks: &mut Vec<PositionedBlock>,
+    cursor: &mut FrameCursor,
+    config: &Config,
+    page_geometry: &PageGeometry,
+) {
+    let top = page_geometry.margins().top;
+    let first_entry_height = toc.entries.first().map_or(0.0, |entry| entry.height());
+
+    cursor.y += cursor.collapse_spacing(config.block_spacing().toc().before(), cursor.y);
+
+    if toc.title_layout.is_some() {
+        let title_height: f64 = toc.title_line_heights.iter().sum();
+        let title_spacing = config.block_spacing().heading().after(1);
+        let leading_height = title_height + title_spacing + first_entry_height;
+
+        if cursor.y + leading_height > page_geometry.content_bottom() && cursor.y > top {
+            cursor.next_frame_for(leading_height);
+        }
+
+        pos_blocks.push(PositionedBlock::toc_title(block_index, cursor.frame(), cursor.y));
+        cursor.y += title_height + title_spacing;
+    }
+
+    for (entry_index, entry) in toc.entries.iter().enumerate() {
+        if entry_index > 0 { cursor.y += config.toc().entry_spacing(); }
+
+        if cursor.y + entry.height() > page_geometry.content_bottom() && cursor.y > top {
+            cursor.next_frame_for(entry.height());
+        }
+
+        pos_blocks.push(PositionedBlock::toc_entry(block_index, entry_index, cursor.frame(), cursor.y));
+        cursor.y += entry.height();
+    }
+
+    cursor.y += config.block_spacing().toc().after();
+    cursor.y_offset = cursor.y;
+}
+
+/// Render a [`PositionedBlock::TocTitle`] fragment.
+pub fn render_toc_title(toc: &PreparedToc,
+    y_offset: f64,
+    context: &Context,
+    page_geometry: &PageGeometry,
+) {
+    if let Some(layout) = &toc.title_layout {
+        show_lines(context, layout, &toc.title_line_heights, page_geometry.margins().left,
+            y_offset, 0, toc.title_line_heights.len());
+    }
+}
+
+/// Render a [`PositionedBlock::TocEntry`] fragment: the title, then the
+/// leaders and the page number on its last line, all tagged as a link to
+/// the heading.
+pub fn render_toc_entry(toc: &PreparedToc,
+    entry_index: usize,
+    y_offset: f64,
+    context: &Context,
+    page_geometry: &PageGeometry,
+) {
+    let entry = &toc.entries[entry_index];
+    let left = page_geometry.margins().left;
+    let line_count = entry.line_heights.len();
+
+    show_lines(context, &entry.layout, &entry.line_heights, left + entry.indent,
+        y_offset, 0, line_count);
+
+    // Lines are drawn on their baselines.
+    let last_baseline = y_offset + entry.line_heights[..line_count - 1].iter().sum::<f64>();
+
+    if let Some(line) = entry.leader_layout.as_ref().and_then(|layout| layout.line(0)) {
+        context.move_to(left + entry.leader_indent, last_baseline);
+        show_layout_line(context, &line);
+    }
+
+    if let Some(line) = entry.number_layout.line(0) {
+        let number_width = logical_width(&entry.number_layout);
+        context.move_to(left + page_geometry.content_width() - number_width, last_baseline);
+        show_layout_line(context, &line);
+    }
+
+    let ascent = entry.layout.line(0).map_or(0.0, |line| {
+        let (_ink, logical) = line.extents();
+        -logical.y() as f64 / pango::SCALE as f64
+    });
+
+    let destination = &entry.destination;
+    tag_page_link(context, destination.page(), destination.x(), destination.y(),
+        (left + entry.indent, y_offset - ascent,
+            page_geometry.content_width() - entry.indent, entry.height()));
+}
+
+// To run test for this module only:
+//
+//     * cargo test toc_layout::tests
+//
+//     * cargo test toc_layout::tests::test_leader_span -- --exact [--nocapture]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_leader_span() {
+        let test_data: Vec<(f64, f64, f64, (f64, usize))> = vec![
+            // Leaders from 16 up to 96.
+            (10.0, 100.0, 4.0, (16.0, 20)),
+            // A title ending on the grid still gets a gap.
+            (12.0, 100.0, 4.0, (16.0, 20)),
+            // No room for leaders.
+            (90.0, 100.0, 4.0, (96.0, 0)),
+            (110.0, 100.0, 4.0, (116.0, 0)),
+            // No leader.
+            (10.0, 100.0, 0.0, (10.0, 0)),
+        ];
+
+        for (title_end, number_start, leader_width, expected) in test_data {
+            assert_eq!(leader_span(title_end, number_start, leader_width), expected,
+                "{title_end} to {number_start} by {leader_width}");
+        }
+    }
+}